@@ -0,0 +1,398 @@
+//! Boolean operations on simple polygons, by Greiner–Hormann clipping.
+//!
+//! Polygons are lists of vertices with an implied closing edge, in either winding order.
+//! Results are lists of rings meant to be filled with the even-odd rule, so a hole comes
+//! out as a ring inside another one.
+
+use crate::Point;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BoolOp {
+    #[default]
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl BoolOp {
+    pub const ALL: [BoolOp; 3] = [BoolOp::Union, BoolOp::Intersection, BoolOp::Difference];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoolOp::Union => "union",
+            BoolOp::Intersection => "intersection",
+            BoolOp::Difference => "difference",
+        }
+    }
+}
+
+/// Edge parameters closer than this to an end of an edge count as touching the vertex
+const EPSILON: f64 = 1e-9;
+
+/// How far the first polygon is nudged to get it off of the second's vertices and edges
+const NUDGE: f64 = 1e-6;
+
+/// Rings thinner than this (area over perimeter) are slivers from edges the nudge split
+const SLIVER: f64 = NUDGE * 10.0;
+
+struct Node {
+    p: Point,
+    next: usize,
+    prev: usize,
+    /// The same crossing in the other polygon's list, for crossings
+    neighbor: Option<usize>,
+    /// Whether following this polygon forwards from here goes into the result
+    entry: bool,
+    visited: bool,
+}
+
+/// `a` combined with `b` by `op`, as even-odd rings; both need at least three vertices
+pub fn apply(op: BoolOp, a: &[Point], b: &[Point]) -> Vec<Vec<Point>> {
+    if a.len() < 3 || b.len() < 3 {
+        return Vec::new();
+    }
+
+    // Crossings through a vertex or along an edge don't fit the algorithm, so `a` is
+    // moved a hair until there are none. The shift is far below anything visible.
+    let mut a = a.to_vec();
+    let mut crossings = crossings_of(&a, b);
+    for attempt in 1..=4 {
+        let Err(()) = crossings else { break };
+        let nudge = NUDGE * attempt as f64;
+        for p in a.iter_mut() {
+            p.x += nudge;
+            p.y += nudge * 0.7;
+        }
+        crossings = crossings_of(&a, b);
+    }
+    let crossings = crossings.unwrap_or_default();
+
+    if crossings.is_empty() {
+        return disjoint(op, &a, b);
+    }
+
+    let mut nodes = Vec::new();
+    let a_order = link(&mut nodes, &a, &crossings, |c| (c.a_edge, c.a_t));
+    let b_order = link(&mut nodes, b, &crossings, |c| (c.b_edge, c.b_t));
+
+    // Each crossing sits in both lists, `link` recorded where
+    let mut in_a = vec![0; crossings.len()];
+    let mut in_b = vec![0; crossings.len()];
+    for (order, slots) in [(&a_order, &mut in_a), (&b_order, &mut in_b)] {
+        for (node, crossing) in order {
+            if let Some(k) = crossing {
+                slots[*k] = *node;
+            }
+        }
+    }
+    for k in 0..crossings.len() {
+        nodes[in_a[k]].neighbor = Some(in_b[k]);
+        nodes[in_b[k]].neighbor = Some(in_a[k]);
+    }
+
+    // Crossings alternate between entering and leaving the other polygon. Union keeps
+    // the parts of both outside the other, difference the parts of `a` outside `b` and
+    // of `b` inside `a`.
+    let (flip_a, flip_b) = match op {
+        BoolOp::Intersection => (false, false),
+        BoolOp::Union => (true, true),
+        BoolOp::Difference => (true, false),
+    };
+    let mut entry = !contains(b, a[0]) ^ flip_a;
+    for (node, crossing) in &a_order {
+        if crossing.is_some() {
+            nodes[*node].entry = entry;
+            entry = !entry;
+        }
+    }
+    let mut entry = !contains(&a, b[0]) ^ flip_b;
+    for (node, crossing) in &b_order {
+        if crossing.is_some() {
+            nodes[*node].entry = entry;
+            entry = !entry;
+        }
+    }
+
+    let mut rings = Vec::new();
+    while let Some(start) = in_a.iter().copied().find(|&n| !nodes[n].visited) {
+        let mut ring = vec![nodes[start].p];
+        let mut current = start;
+        // Bounded in case rounding leaves the crossings inconsistent
+        for _ in 0..nodes.len() {
+            nodes[current].visited = true;
+            if let Some(neighbor) = nodes[current].neighbor {
+                nodes[neighbor].visited = true;
+            }
+
+            let forward = nodes[current].entry;
+            loop {
+                current = if forward {
+                    nodes[current].next
+                } else {
+                    nodes[current].prev
+                };
+                if nodes[current].neighbor.is_some() {
+                    break;
+                }
+                ring.push(nodes[current].p);
+            }
+
+            if nodes[current].visited {
+                break;
+            }
+            ring.push(nodes[current].p);
+            current = nodes[current].neighbor.unwrap();
+        }
+        if ring.len() >= 3 && signed_area(&ring).abs() >= SLIVER * perimeter(&ring) {
+            rings.push(ring);
+        }
+    }
+    rings
+}
+
+/// The area covered by `rings` filled with the even-odd rule
+pub fn area(rings: &[Vec<Point>]) -> f64 {
+    rings
+        .iter()
+        .enumerate()
+        .map(|(i, ring)| {
+            let depth = rings
+                .iter()
+                .enumerate()
+                .filter(|(j, other)| *j != i && contains(other, ring[0]))
+                .count();
+            let sign = if depth % 2 == 0 { 1.0 } else { -1.0 };
+            sign * signed_area(ring).abs()
+        })
+        .sum()
+}
+
+/// Positive for rings going clockwise on screen (y pointing down)
+pub fn signed_area(ring: &[Point]) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (p, q) = (ring[i], ring[(i + 1) % n]);
+            p.x * q.y - q.x * p.y
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+fn perimeter(ring: &[Point]) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let d = sub(ring[(i + 1) % n], ring[i]);
+            d.x.hypot(d.y)
+        })
+        .sum()
+}
+
+/// Whether `p` is inside `polygon`, by the even-odd rule
+pub fn contains(polygon: &[Point], p: Point) -> bool {
+    let n = polygon.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// When the outlines don't cross, one is inside the other or they're apart
+fn disjoint(op: BoolOp, a: &[Point], b: &[Point]) -> Vec<Vec<Point>> {
+    let (a_in_b, b_in_a) = (contains(b, a[0]), contains(a, b[0]));
+    let (a, b) = (a.to_vec(), b.to_vec());
+    match op {
+        BoolOp::Union if a_in_b => vec![b],
+        BoolOp::Union if b_in_a => vec![a],
+        BoolOp::Union => vec![a, b],
+        BoolOp::Intersection if a_in_b => vec![a],
+        BoolOp::Intersection if b_in_a => vec![b],
+        BoolOp::Intersection => Vec::new(),
+        BoolOp::Difference if a_in_b => Vec::new(),
+        BoolOp::Difference if b_in_a => vec![a, b],
+        BoolOp::Difference => vec![a],
+    }
+}
+
+/// Where edge `a_edge` crosses edge `b_edge`, at fractions `a_t` and `b_t` along them
+struct Crossing {
+    p: Point,
+    a_edge: usize,
+    a_t: f64,
+    b_edge: usize,
+    b_t: f64,
+}
+
+/// Every proper crossing of the two outlines, or Err if they touch at a vertex or edge
+fn crossings_of(a: &[Point], b: &[Point]) -> Result<Vec<Crossing>, ()> {
+    let mut crossings = Vec::new();
+    for i in 0..a.len() {
+        let (p1, p2) = (a[i], a[(i + 1) % a.len()]);
+        for j in 0..b.len() {
+            let (q1, q2) = (b[j], b[(j + 1) % b.len()]);
+            let (r, s) = (sub(p2, p1), sub(q2, q1));
+            let denominator = cross(r, s);
+            let scale = (r.x.hypot(r.y) * s.x.hypot(s.y)).max(f64::MIN_POSITIVE);
+            if denominator.abs() / scale < EPSILON {
+                // Parallel. Only a problem if they lie along each other.
+                let offset = cross(sub(q1, p1), r) / r.x.hypot(r.y).max(f64::MIN_POSITIVE);
+                if offset.abs() < NUDGE / 10.0 && overlaps(p1, p2, q1, q2) {
+                    return Err(());
+                }
+                continue;
+            }
+
+            let a_t = cross(sub(q1, p1), s) / denominator;
+            let b_t = cross(sub(q1, p1), r) / denominator;
+            let within = |t: f64| (-EPSILON..=1.0 + EPSILON).contains(&t);
+            if !within(a_t) || !within(b_t) {
+                continue;
+            }
+            let inside = |t: f64| t > EPSILON && t < 1.0 - EPSILON;
+            if !inside(a_t) || !inside(b_t) {
+                return Err(());
+            }
+            crossings.push(Crossing {
+                p: Point {
+                    x: p1.x + r.x * a_t,
+                    y: p1.y + r.y * a_t,
+                },
+                a_edge: i,
+                a_t,
+                b_edge: j,
+                b_t,
+            });
+        }
+    }
+    Ok(crossings)
+}
+
+/// Whether collinear segments `p1 p2` and `q1 q2` share more than a point
+fn overlaps(p1: Point, p2: Point, q1: Point, q2: Point) -> bool {
+    let r = sub(p2, p1);
+    let along = |q: Point| dot(sub(q, p1), r) / dot(r, r).max(f64::MIN_POSITIVE);
+    let (t1, t2) = (along(q1), along(q2));
+    t1.max(t2) > -EPSILON && t1.min(t2) < 1.0 + EPSILON
+}
+
+/// Links a polygon's vertices and crossings into `nodes` circularly, returning them in order
+fn link(
+    nodes: &mut Vec<Node>,
+    polygon: &[Point],
+    crossings: &[Crossing],
+    place: impl Fn(&Crossing) -> (usize, f64),
+) -> Vec<(usize, Option<usize>)> {
+    let mut order = Vec::new();
+    for (i, &p) in polygon.iter().enumerate() {
+        order.push((p, None));
+        let mut on_edge: Vec<(f64, usize)> = crossings
+            .iter()
+            .enumerate()
+            .filter_map(|(k, c)| {
+                let (edge, t) = place(c);
+                (edge == i).then_some((t, k))
+            })
+            .collect();
+        on_edge.sort_by(|a, b| a.0.total_cmp(&b.0));
+        order.extend(on_edge.into_iter().map(|(_, k)| (crossings[k].p, Some(k))));
+    }
+
+    let first = nodes.len();
+    let n = order.len();
+    order
+        .into_iter()
+        .enumerate()
+        .map(|(i, (p, crossing))| {
+            nodes.push(Node {
+                p,
+                next: first + (i + 1) % n,
+                prev: first + (i + n - 1) % n,
+                neighbor: None,
+                entry: false,
+                visited: false,
+            });
+            (first + i, crossing)
+        })
+        .collect()
+}
+
+fn sub(a: Point, b: Point) -> Point {
+    Point {
+        x: a.x - b.x,
+        y: a.y - b.y,
+    }
+}
+
+fn cross(a: Point, b: Point) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+fn dot(a: Point, b: Point) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, w: f64, h: f64) -> Vec<Point> {
+        [(x, y), (x + w, y), (x + w, y + h), (x, y + h)]
+            .iter()
+            .map(|&(x, y)| Point { x, y })
+            .collect()
+    }
+
+    fn area_of(op: BoolOp, a: &[Point], b: &[Point]) -> f64 {
+        area(&apply(op, a, b))
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn overlapping() {
+        let (a, b) = (rect(0.0, 0.0, 2.0, 2.0), rect(1.0, 1.0, 2.0, 2.0));
+        assert!(close(area_of(BoolOp::Union, &a, &b), 7.0));
+        assert!(close(area_of(BoolOp::Intersection, &a, &b), 1.0));
+        assert!(close(area_of(BoolOp::Difference, &a, &b), 3.0));
+    }
+
+    #[test]
+    fn contained() {
+        let (outer, inner) = (rect(0.0, 0.0, 4.0, 4.0), rect(1.0, 1.0, 1.0, 1.0));
+        assert!(close(area_of(BoolOp::Union, &outer, &inner), 16.0));
+        assert!(close(area_of(BoolOp::Intersection, &outer, &inner), 1.0));
+        assert!(close(area_of(BoolOp::Difference, &outer, &inner), 15.0));
+        assert!(close(area_of(BoolOp::Difference, &inner, &outer), 0.0));
+    }
+
+    #[test]
+    fn disjoint_shapes() {
+        let (a, b) = (rect(0.0, 0.0, 1.0, 1.0), rect(3.0, 0.0, 1.0, 1.0));
+        assert!(close(area_of(BoolOp::Union, &a, &b), 2.0));
+        assert!(apply(BoolOp::Intersection, &a, &b).is_empty());
+        assert!(close(area_of(BoolOp::Difference, &a, &b), 1.0));
+    }
+
+    #[test]
+    fn shared_edge() {
+        let (a, b) = (rect(0.0, 0.0, 1.0, 1.0), rect(1.0, 0.0, 1.0, 1.0));
+        assert!(close(area_of(BoolOp::Union, &a, &b), 2.0));
+        assert!(apply(BoolOp::Intersection, &a, &b).is_empty());
+        assert!(close(area_of(BoolOp::Difference, &a, &b), 1.0));
+    }
+
+    #[test]
+    fn identical() {
+        let a = rect(0.0, 0.0, 1.0, 1.0);
+        assert!(close(area_of(BoolOp::Union, &a, &a), 1.0));
+        assert!(close(area_of(BoolOp::Intersection, &a, &a), 1.0));
+        assert!(apply(BoolOp::Difference, &a, &a).is_empty());
+    }
+}
@@ -0,0 +1,467 @@
+//! A small arithmetic expression language, for values like `10 + 5 * sin(i / 4)`.
+//!
+//! Supports numbers, `+ - * / % ^`, unary minus, parentheses, the constants `pi` and
+//! `e`, a handful of math functions, and free variables which are looked up at
+//! evaluation time, whose names may carry a path like `step[3].center.x`. The trig
+//! functions work in whichever `AngleUnit` they're evaluated with.
+
+/// The unit angles are written in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AngleUnit {
+    #[default]
+    Degrees,
+    Radians,
+}
+
+impl AngleUnit {
+    pub fn to_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleUnit::Degrees => angle.to_radians(),
+            AngleUnit::Radians => angle,
+        }
+    }
+
+    pub fn from_radians(self, radians: f64) -> f64 {
+        match self {
+            AngleUnit::Degrees => radians.to_degrees(),
+            AngleUnit::Radians => radians,
+        }
+    }
+
+    /// `angle`, given in `from`, in this unit
+    pub fn convert(self, angle: f64, from: AngleUnit) -> f64 {
+        self.from_radians(from.to_radians(angle))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AngleUnit::Degrees => "degrees",
+            AngleUnit::Radians => "radians",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+impl BinOp {
+    /// (left, right) binding power. `^` is right associative.
+    fn binding_power(&self) -> (u8, u8) {
+        match self {
+            BinOp::Add | BinOp::Sub => (1, 2),
+            BinOp::Mul | BinOp::Div | BinOp::Rem => (3, 4),
+            BinOp::Pow => (6, 5),
+        }
+    }
+
+    fn apply(&self, l: f64, r: f64) -> f64 {
+        match self {
+            BinOp::Add => l + r,
+            BinOp::Sub => l - r,
+            BinOp::Mul => l * r,
+            BinOp::Div => l / r,
+            BinOp::Rem => l % r,
+            BinOp::Pow => l.powf(r),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Num(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let n = text
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid number '{}'", text))?;
+                tokens.push(Token::Num(n));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '.' | '[' | ']'))
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' => {
+                tokens.push(Token::Op(c));
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            _ => return Err(format!("Unexpected character '{}'", c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(t) if t == expected => Ok(()),
+            Some(t) => Err(format!("Expected {:?}, found {:?}", expected, t)),
+            None => Err(format!("Expected {:?}, found end of input", expected)),
+        }
+    }
+
+    fn expr(&mut self, min_bp: u8) -> Result<Expr, String> {
+        let mut lhs = match self.next() {
+            Some(Token::Num(n)) => Expr::Num(n),
+            Some(Token::Ident(name)) => {
+                if self.peek() == Some(&Token::LParen) {
+                    self.next();
+                    let mut args = Vec::new();
+                    if self.peek() != Some(&Token::RParen) {
+                        loop {
+                            args.push(self.expr(0)?);
+                            if self.peek() == Some(&Token::Comma) {
+                                self.next();
+                            } else {
+                                break;
+                            }
+                        }
+                    }
+                    self.expect(Token::RParen)?;
+                    Expr::Call(name, args)
+                } else {
+                    Expr::Var(name)
+                }
+            }
+            // Unary minus binds tighter than everything except `^`, so -2^2 == -4
+            Some(Token::Op('-')) => Expr::Neg(Box::new(self.expr(5)?)),
+            Some(Token::LParen) => {
+                let inner = self.expr(0)?;
+                self.expect(Token::RParen)?;
+                inner
+            }
+            Some(t) => return Err(format!("Unexpected {:?}", t)),
+            None => return Err("Unexpected end of input".to_string()),
+        };
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op('+')) => BinOp::Add,
+                Some(Token::Op('-')) => BinOp::Sub,
+                Some(Token::Op('*')) => BinOp::Mul,
+                Some(Token::Op('/')) => BinOp::Div,
+                Some(Token::Op('%')) => BinOp::Rem,
+                Some(Token::Op('^')) => BinOp::Pow,
+                _ => break,
+            };
+            let (l_bp, r_bp) = op.binding_power();
+            if l_bp < min_bp {
+                break;
+            }
+            self.next();
+            let rhs = self.expr(r_bp)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+}
+
+pub fn parse(src: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+    };
+    let expr = parser.expr(0)?;
+    match parser.next() {
+        None => Ok(expr),
+        Some(t) => Err(format!("Unexpected {:?}", t)),
+    }
+}
+
+impl Expr {
+    /// The names of the free variables the expression uses, in order of first use
+    pub fn vars(&self) -> Vec<String> {
+        let mut vars = Vec::new();
+        self.collect_vars(&mut vars);
+        vars
+    }
+
+    fn collect_vars(&self, vars: &mut Vec<String>) {
+        match self {
+            Expr::Num(_) => {}
+            Expr::Var(name) => {
+                if name != "pi" && name != "e" && !vars.contains(name) {
+                    vars.push(name.clone());
+                }
+            }
+            Expr::Neg(inner) => inner.collect_vars(vars),
+            Expr::Binary(_, l, r) => {
+                l.collect_vars(vars);
+                r.collect_vars(vars);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|a| a.collect_vars(vars)),
+        }
+    }
+
+    /// Evaluates the expression with free variables from `vars` and trig in `unit`
+    pub fn eval(&self, vars: &dyn Fn(&str) -> Option<f64>, unit: AngleUnit) -> Result<f64, String> {
+        match self {
+            Expr::Num(n) => Ok(*n),
+            Expr::Var(name) => match name.as_str() {
+                "pi" => Ok(std::f64::consts::PI),
+                "e" => Ok(std::f64::consts::E),
+                _ => vars(name).ok_or_else(|| format!("Unknown variable '{}'", name)),
+            },
+            Expr::Neg(inner) => Ok(-inner.eval(vars, unit)?),
+            Expr::Binary(op, l, r) => Ok(op.apply(l.eval(vars, unit)?, r.eval(vars, unit)?)),
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|a| a.eval(vars, unit))
+                    .collect::<Result<Vec<f64>, String>>()?;
+                match name.as_str() {
+                    "sin" | "cos" | "tan" => {
+                        let radians: Vec<f64> = args.iter().map(|a| unit.to_radians(*a)).collect();
+                        call(name, &radians)
+                    }
+                    "asin" | "acos" | "atan" | "atan2" => {
+                        call(name, &args).map(|r| unit.from_radians(r))
+                    }
+                    _ => call(name, &args),
+                }
+            }
+        }
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> Result<f64, String> {
+    let unary = |f: fn(f64) -> f64| match args {
+        [x] => Ok(f(*x)),
+        _ => Err(format!("'{}' takes 1 argument, got {}", name, args.len())),
+    };
+    let binary = |f: fn(f64, f64) -> f64| match args {
+        [x, y] => Ok(f(*x, *y)),
+        _ => Err(format!("'{}' takes 2 arguments, got {}", name, args.len())),
+    };
+
+    match name {
+        "sin" => unary(f64::sin),
+        "cos" => unary(f64::cos),
+        "tan" => unary(f64::tan),
+        "asin" => unary(f64::asin),
+        "acos" => unary(f64::acos),
+        "atan" => unary(f64::atan),
+        "sqrt" => unary(f64::sqrt),
+        "abs" => unary(f64::abs),
+        "exp" => unary(f64::exp),
+        "ln" => unary(f64::ln),
+        "floor" => unary(f64::floor),
+        "ceil" => unary(f64::ceil),
+        "round" => unary(f64::round),
+        "atan2" => binary(f64::atan2),
+        "pow" => binary(f64::powf),
+        // Anything but zero counts as true, so bool data works as the condition
+        "if" => match args {
+            [condition, then, otherwise] => Ok(if *condition != 0.0 { *then } else { *otherwise }),
+            _ => Err(format!("'if' takes 3 arguments, got {}", args.len())),
+        },
+        "min" | "max" if !args.is_empty() => {
+            let fold: fn(f64, f64) -> f64 = if name == "min" { f64::min } else { f64::max };
+            Ok(args[1..].iter().copied().fold(args[0], fold))
+        }
+        _ => Err(format!("Unknown function '{}'", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(src: &str) -> f64 {
+        parse(src)
+            .unwrap()
+            .eval(&|_| None, AngleUnit::Radians)
+            .unwrap()
+    }
+
+    fn eval_in(src: &str, unit: AngleUnit) -> f64 {
+        parse(src).unwrap().eval(&|_| None, unit).unwrap()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("10 - 4 - 3"), 3.0);
+        assert_eq!(eval("12 / 3 / 2"), 2.0);
+        assert_eq!(eval("7 % 4 * 2"), 6.0);
+        assert_eq!(eval("2 * 3 ^ 2"), 18.0);
+    }
+
+    #[test]
+    fn pow_is_right_associative() {
+        assert_eq!(eval("2 ^ 3 ^ 2"), 512.0);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(eval("-2 ^ 2"), -4.0);
+        assert_eq!(eval("(-2) ^ 2"), 4.0);
+        assert_eq!(eval("3 * -2"), -6.0);
+        assert_eq!(eval("--3"), 3.0);
+    }
+
+    #[test]
+    fn functions_and_constants() {
+        assert!(close(eval("pi"), std::f64::consts::PI));
+        assert_eq!(eval("max(1, 5, 3)"), 5.0);
+        assert_eq!(eval("min(4, -1)"), -1.0);
+        assert_eq!(eval("if(0, 1, 2)"), 2.0);
+        assert_eq!(eval("sqrt(16) + abs(-1)"), 5.0);
+        assert!(parse("sqrt(1, 2)")
+            .unwrap()
+            .eval(&|_| None, AngleUnit::Radians)
+            .is_err());
+    }
+
+    #[test]
+    fn trig_follows_the_unit() {
+        assert!(close(eval_in("sin(90)", AngleUnit::Degrees), 1.0));
+        assert!(close(eval_in("sin(pi / 2)", AngleUnit::Radians), 1.0));
+        assert!(close(eval_in("atan2(1, 1)", AngleUnit::Degrees), 45.0));
+        assert!(close(
+            eval_in("acos(0)", AngleUnit::Radians),
+            std::f64::consts::FRAC_PI_2
+        ));
+        // Non-trig functions don't care
+        assert_eq!(eval_in("sqrt(9)", AngleUnit::Degrees), 3.0);
+    }
+
+    #[test]
+    fn angle_unit_convert() {
+        assert!(close(
+            AngleUnit::Degrees.convert(std::f64::consts::PI, AngleUnit::Radians),
+            180.0
+        ));
+        assert!(close(
+            AngleUnit::Radians.convert(90.0, AngleUnit::Degrees),
+            std::f64::consts::FRAC_PI_2
+        ));
+        assert!(close(
+            AngleUnit::Degrees.convert(30.0, AngleUnit::Degrees),
+            30.0
+        ));
+    }
+
+    #[test]
+    fn variables() {
+        let expr = parse("2 * x + y").unwrap();
+        let vars = |name: &str| match name {
+            "x" => Some(3.0),
+            "y" => Some(1.0),
+            _ => None,
+        };
+        assert_eq!(expr.eval(&vars, AngleUnit::Radians), Ok(7.0));
+        assert_eq!(
+            parse("z").unwrap().eval(&vars, AngleUnit::Radians),
+            Err("Unknown variable 'z'".to_string())
+        );
+    }
+
+    #[test]
+    fn ref_paths_are_single_names() {
+        assert_eq!(
+            parse("2 * step[3].length + data[0]").unwrap(),
+            Expr::Binary(
+                BinOp::Add,
+                Box::new(Expr::Binary(
+                    BinOp::Mul,
+                    Box::new(Expr::Num(2.0)),
+                    Box::new(Expr::Var("step[3].length".to_string()))
+                )),
+                Box::new(Expr::Var("data[0]".to_string()))
+            )
+        );
+        assert_eq!(
+            parse("data[1][2].x").unwrap(),
+            Expr::Var("data[1][2].x".to_string())
+        );
+    }
+
+    #[test]
+    fn vars_in_order_without_constants_or_repeats() {
+        let expr = parse("sin(step[2].center.x) * pi + step[2].center.x - e * data[0]").unwrap();
+        assert_eq!(expr.vars(), vec!["step[2].center.x", "data[0]"]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse("1 +").is_err());
+        assert!(parse("(1 + 2").is_err());
+        assert!(parse("1 2").is_err());
+        assert!(parse("1 $ 2").is_err());
+    }
+}
@@ -1,19 +1,55 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
 use leptos::*;
 
+pub mod clip;
+pub mod expr;
+
+use clip::BoolOp;
+use expr::AngleUnit;
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct PointSignal {
     pub x: RwSignal<ResolvableTo<NumberSignal>>,
     pub y: RwSignal<ResolvableTo<NumberSignal>>,
 }
 
+impl PointSignal {
+    pub fn literal(cx: Scope, point: Point) -> Self {
+        Self {
+            x: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, point.x))),
+            y: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, point.y))),
+        }
+    }
+}
+
 pub type NumberSignal = RwSignal<f64>;
 
+/// An angle field's literal value, kept in whichever unit its step uses (see `resolve_in`)
+pub type AngleSignal = RwSignal<f64>;
+
+/// Any CSS color
+pub type ColorSignal = RwSignal<String>;
+
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
 }
 
+impl Point {
+    /// What a ref which doesn't lead to a point resolves to
+    pub const NAN: Point = Point {
+        x: f64::NAN,
+        y: f64::NAN,
+    };
+
+    pub fn distance(&self, other: Point) -> f64 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
 trait ResolveToNumber {
     fn resolve(&self, cx: Scope) -> f64;
 }
@@ -29,6 +65,8 @@ where
 {
     T(T),
     Ref(DataRef),
+    /// An expression over refs, e.g. `2 * step[3].length + data[0]`
+    Expr(String),
 }
 
 impl ResolveToPoint for ResolvableTo<PointSignal> {
@@ -39,6 +77,7 @@ impl ResolveToPoint for ResolvableTo<PointSignal> {
                 y: point.y.get().resolve(cx),
             },
             ResolvableTo::Ref(r) => ResolveToPoint::resolve(r, cx),
+            ResolvableTo::Expr(_) => Point::NAN,
         }
     }
 }
@@ -48,17 +87,349 @@ impl ResolveToNumber for ResolvableTo<NumberSignal> {
         match self {
             ResolvableTo::T(n) => n.get(),
             ResolvableTo::Ref(r) => ResolveToNumber::resolve(r, cx),
+            ResolvableTo::Expr(src) => expr_value(cx, src).unwrap_or(f64::NAN),
+        }
+    }
+}
+
+impl ResolvableTo<ColorSignal> {
+    pub fn literal(cx: Scope, color: &str) -> Self {
+        ResolvableTo::T(create_rw_signal(cx, color.to_string()))
+    }
+
+    /// The color to draw with; anything that isn't color data comes out black
+    pub fn resolve_color(&self, cx: Scope) -> String {
+        match self {
+            ResolvableTo::T(c) => c.get(),
+            ResolvableTo::Ref(r) => match r.data_id().and_then(|id| find_data(cx, id)) {
+                Some(Data {
+                    data: DataData::Color(c),
+                    ..
+                }) => c.get(),
+                _ => "#000000".to_string(),
+            },
+            ResolvableTo::Expr(_) => "#000000".to_string(),
+        }
+    }
+
+    /// A literal copy of this color, so it can be edited apart from the original
+    pub fn duplicate(&self, cx: Scope) -> Self {
+        match self {
+            ResolvableTo::T(c) => ResolvableTo::T(create_rw_signal(cx, c.get())),
+            ResolvableTo::Ref(r) => ResolvableTo::Ref(r.clone()),
+            ResolvableTo::Expr(src) => ResolvableTo::Expr(src.clone()),
+        }
+    }
+}
+
+impl ResolvableTo<AngleSignal> {
+    /// The value of an angle field kept in `unit`, converting refs and expressions into it
+    pub fn resolve_in(&self, cx: Scope, unit: AngleUnit) -> f64 {
+        match self {
+            ResolvableTo::T(t) => t.get(),
+            ResolvableTo::Ref(r) => {
+                let value = ResolveToNumber::resolve(r, cx);
+                match ref_angle_unit(cx, r) {
+                    Some(stored) => unit.convert(value, stored),
+                    None => value,
+                }
+            }
+            ResolvableTo::Expr(src) => {
+                unit.convert(expr_value(cx, src).unwrap_or(f64::NAN), angle_unit(cx))
+            }
         }
     }
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum StepData {
-    DrawPoint(RwSignal<ResolvableTo<PointSignal>>),
+    DrawPoint {
+        point: RwSignal<ResolvableTo<PointSignal>>,
+        marker: RwSignal<PointMarker>,
+        /// Marker radius in world units
+        size: RwSignal<ResolvableTo<NumberSignal>>,
+    },
     DrawLine {
         start: RwSignal<ResolvableTo<PointSignal>>,
         end: RwSignal<ResolvableTo<PointSignal>>,
     },
+    /// A bitmap with its top left corner at `position`, drawn beneath everything else
+    DrawImage {
+        /// A URL, or a data URL for images picked from a file
+        src: RwSignal<String>,
+        position: RwSignal<ResolvableTo<PointSignal>>,
+        /// World units per image pixel
+        scale: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// `content` with its baseline starting at `anchor`, or running along `path` if set
+    DrawText {
+        anchor: RwSignal<ResolvableTo<PointSignal>>,
+        content: RwSignal<String>,
+        /// Font size in world units
+        size: RwSignal<ResolvableTo<NumberSignal>>,
+        /// A line, curve or shape for the text to run along
+        path: RwSignal<Option<DataRef>>,
+    },
+    /// A bar `length` long from `origin`, split into `divisions` and labelled in `unit`
+    ScaleBar {
+        origin: RwSignal<ResolvableTo<PointSignal>>,
+        length: RwSignal<ResolvableTo<NumberSignal>>,
+        /// Rounded to a whole number from 1 to `MAX_ARRAY_COUNT`
+        divisions: RwSignal<ResolvableTo<NumberSignal>>,
+        unit: RwSignal<String>,
+        /// Label font size in world units, which the bar's thickness follows too
+        size: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// x/y axes crossing at `origin`, ticked every `spacing`, with y counting up like a plot
+    Axes {
+        origin: RwSignal<ResolvableTo<PointSignal>>,
+        x_min: RwSignal<ResolvableTo<NumberSignal>>,
+        x_max: RwSignal<ResolvableTo<NumberSignal>>,
+        y_min: RwSignal<ResolvableTo<NumberSignal>>,
+        y_max: RwSignal<ResolvableTo<NumberSignal>>,
+        spacing: RwSignal<ResolvableTo<NumberSignal>>,
+        /// Label font size in world units, which the tick length follows too
+        size: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// A text label at `anchor` with a leader line out to `tip`
+    Callout {
+        anchor: RwSignal<ResolvableTo<PointSignal>>,
+        tip: RwSignal<ResolvableTo<PointSignal>>,
+        content: RwSignal<String>,
+        /// Font size in world units
+        size: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// The average of `points`, for later steps to snap to and reference
+    Centroid {
+        points: RwSignal<Vec<RwSignal<ResolvableTo<PointSignal>>>>,
+    },
+    /// Connected segments through each of `points` in turn
+    DrawPath {
+        points: RwSignal<Vec<RwSignal<ResolvableTo<PointSignal>>>>,
+    },
+    /// A Catmull-Rom spline through each of `points`
+    Spline {
+        points: RwSignal<Vec<RwSignal<ResolvableTo<PointSignal>>>>,
+    },
+    /// A regular polygon inscribed in the circle around `center`, with a vertex at the top
+    DrawPolygon {
+        center: RwSignal<ResolvableTo<PointSignal>>,
+        radius: RwSignal<ResolvableTo<NumberSignal>>,
+        /// Rounded to a whole number of at least 3
+        sides: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// `rows` by `cols` points from `origin`, addressable as `points[row][col]`
+    PointGrid {
+        origin: RwSignal<ResolvableTo<PointSignal>>,
+        col_spacing: RwSignal<ResolvableTo<NumberSignal>>,
+        row_spacing: RwSignal<ResolvableTo<NumberSignal>>,
+        /// Both rounded to a whole number from 1 to `MAX_ARRAY_COUNT`
+        cols: RwSignal<ResolvableTo<NumberSignal>>,
+        rows: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// A ring between two circles around `center`, filled even-odd so the hole stays empty
+    Annulus {
+        center: RwSignal<ResolvableTo<PointSignal>>,
+        inner_radius: RwSignal<ResolvableTo<NumberSignal>>,
+        outer_radius: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// An axis-aligned rectangle with its top left corner at `origin`
+    DrawRect {
+        origin: RwSignal<ResolvableTo<PointSignal>>,
+        width: RwSignal<ResolvableTo<NumberSignal>>,
+        height: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// A rect whose corners are rounded off with arcs of `radius`, clamped to fit
+    DrawRoundedRect {
+        origin: RwSignal<ResolvableTo<PointSignal>>,
+        width: RwSignal<ResolvableTo<NumberSignal>>,
+        height: RwSignal<ResolvableTo<NumberSignal>>,
+        radius: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// Starts at `origin` and runs on forever through `through`
+    DrawRay {
+        origin: RwSignal<ResolvableTo<PointSignal>>,
+        through: RwSignal<ResolvableTo<PointSignal>>,
+    },
+    /// Runs forever both ways through `a` and `b`
+    DrawInfiniteLine {
+        a: RwSignal<ResolvableTo<PointSignal>>,
+        b: RwSignal<ResolvableTo<PointSignal>>,
+    },
+    /// A line with an arrowhead at `end`
+    DrawArrow {
+        start: RwSignal<ResolvableTo<PointSignal>>,
+        end: RwSignal<ResolvableTo<PointSignal>>,
+        /// Arrowhead length in world units
+        head_size: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// Angles in radians, drawn clockwise on screen from `start_angle` to `end_angle`
+    DrawArc {
+        center: RwSignal<ResolvableTo<PointSignal>>,
+        radius: RwSignal<ResolvableTo<NumberSignal>>,
+        start_angle: RwSignal<ResolvableTo<AngleSignal>>,
+        end_angle: RwSignal<ResolvableTo<AngleSignal>>,
+    },
+    /// The point halfway between `a` and `b`, for later steps to snap to and reference
+    Midpoint {
+        a: RwSignal<ResolvableTo<PointSignal>>,
+        b: RwSignal<ResolvableTo<PointSignal>>,
+    },
+    /// Measures from `a` to `b` as the number prop `value`
+    Distance {
+        a: RwSignal<ResolvableTo<PointSignal>>,
+        b: RwSignal<ResolvableTo<PointSignal>>,
+    },
+    /// Measures the angle at `vertex` from `a` round to `b` as the number prop `value`
+    Angle {
+        a: RwSignal<ResolvableTo<PointSignal>>,
+        vertex: RwSignal<ResolvableTo<PointSignal>>,
+        b: RwSignal<ResolvableTo<PointSignal>>,
+    },
+    /// A pie slice: the arc from `start_angle` to `end_angle` closed off through `center`
+    DrawSector {
+        center: RwSignal<ResolvableTo<PointSignal>>,
+        radius: RwSignal<ResolvableTo<NumberSignal>>,
+        start_angle: RwSignal<ResolvableTo<AngleSignal>>,
+        end_angle: RwSignal<ResolvableTo<AngleSignal>>,
+    },
+    /// A sketched stroke: literal (already simplified) pointer samples in world space
+    Freehand {
+        points: RwSignal<Vec<Point>>,
+        smooth: RwSignal<bool>,
+    },
+    /// The smallest upright rectangle around all the referenced steps
+    BoundingBox {
+        sources: RwSignal<Vec<RwSignal<Option<DataRef>>>>,
+    },
+    /// Clips every subsequent step to the closed shape of the referenced step
+    Clip { shape: RwSignal<Option<DataRef>> },
+    /// Closed shapes `a` and `b` combined by `op`
+    ShapeBoolean {
+        op: RwSignal<BoolOp>,
+        a: RwSignal<Option<DataRef>>,
+        b: RwSignal<Option<DataRef>>,
+    },
+    /// The infinite line through `through` at right angles to the referenced line step
+    Perpendicular {
+        line: RwSignal<Option<DataRef>>,
+        through: RwSignal<ResolvableTo<PointSignal>>,
+    },
+    /// The line from `from` touching the referenced circle, on the side `other_side` picks
+    Tangent {
+        circle: RwSignal<Option<DataRef>>,
+        from: RwSignal<ResolvableTo<PointSignal>>,
+        other_side: RwSignal<bool>,
+    },
+    /// The referenced line moved `distance` to its left
+    ParallelOffset {
+        line: RwSignal<Option<DataRef>>,
+        distance: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// The referenced line cut or extended to meet the `boundary` line
+    TrimExtend {
+        line: RwSignal<Option<DataRef>>,
+        boundary: RwSignal<Option<DataRef>>,
+        keep_end: RwSignal<bool>,
+    },
+    /// The referenced path moved `distance` to one side (outwards when closed)
+    OffsetCurve {
+        source: RwSignal<Option<DataRef>>,
+        distance: RwSignal<ResolvableTo<NumberSignal>>,
+        join: RwSignal<LineJoin>,
+    },
+    /// A live reflected copy of the `source` step across the `axis` line step
+    Mirror {
+        source: RwSignal<Option<DataRef>>,
+        axis: RwSignal<Option<DataRef>>,
+    },
+    /// A live copy of `source` rotated by `angle` degrees about `center`
+    RotateCopy {
+        source: RwSignal<Option<DataRef>>,
+        center: RwSignal<ResolvableTo<PointSignal>>,
+        angle: RwSignal<ResolvableTo<AngleSignal>>,
+    },
+    /// A live copy of the `source` step moved by `(dx, dy)`
+    TranslateCopy {
+        source: RwSignal<Option<DataRef>>,
+        dx: RwSignal<ResolvableTo<NumberSignal>>,
+        dy: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// A live copy of `source` moved by the referenced transform data
+    TransformCopy {
+        source: RwSignal<Option<DataRef>>,
+        transform: RwSignal<Option<DataRef>>,
+    },
+    /// `count` copies of `source`, counting itself, each moved on by `(dx, dy)`
+    LinearArray {
+        source: RwSignal<Option<DataRef>>,
+        count: RwSignal<ResolvableTo<NumberSignal>>,
+        dx: RwSignal<ResolvableTo<NumberSignal>>,
+        dy: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// `count` copies of `source`, counting itself, spread over `sweep` degrees about `center`
+    PolarArray {
+        source: RwSignal<Option<DataRef>>,
+        center: RwSignal<ResolvableTo<PointSignal>>,
+        count: RwSignal<ResolvableTo<NumberSignal>>,
+        sweep: RwSignal<ResolvableTo<AngleSignal>>,
+    },
+    /// The point `t` of the way along the referenced line, 0 at its first point and 1 at its second
+    PointAlongLine {
+        line: RwSignal<Option<DataRef>>,
+        t: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// The foot of the perpendicular from `point` onto the referenced line
+    Projection {
+        point: RwSignal<ResolvableTo<PointSignal>>,
+        line: RwSignal<Option<DataRef>>,
+    },
+    /// Where the two referenced line steps cross, taking both as infinite lines
+    Intersection {
+        line_a: RwSignal<Option<DataRef>>,
+        line_b: RwSignal<Option<DataRef>>,
+    },
+    /// Plots `y = f(x)` for `x` from `x_min` to `x_max`
+    FunctionPlot {
+        f: RwSignal<String>,
+        x_min: RwSignal<ResolvableTo<NumberSignal>>,
+        x_max: RwSignal<ResolvableTo<NumberSignal>>,
+    },
+    /// Traces `(x(t), y(t))` at `samples` evenly spaced values of `t`
+    ParametricCurve {
+        x: RwSignal<String>,
+        y: RwSignal<String>,
+        t_min: RwSignal<ResolvableTo<NumberSignal>>,
+        t_max: RwSignal<ResolvableTo<NumberSignal>>,
+        samples: RwSignal<usize>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PointMarker {
+    Dot,
+    Cross,
+    Square,
+    #[default]
+    Ring,
+}
+
+impl PointMarker {
+    pub const ALL: [PointMarker; 4] = [
+        PointMarker::Dot,
+        PointMarker::Cross,
+        PointMarker::Square,
+        PointMarker::Ring,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PointMarker::Dot => "dot",
+            PointMarker::Cross => "cross",
+            PointMarker::Square => "square",
+            PointMarker::Ring => "ring",
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -67,6 +438,10 @@ pub enum DataRefPathEl {
     Data,
     WithId(usize),
     PropName(&'static str),
+    /// An argument to the preceding prop, e.g. the `20` in `.cross_y(20)`
+    Param(f64),
+    /// An element of the preceding list prop, e.g. the `2` in `.points[2]`
+    Index(usize),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -81,780 +456,13793 @@ impl DataRef {
                 DataRefPathEl::Data => "data".to_string(),
                 DataRefPathEl::WithId(id) => format!("[{}]", id),
                 DataRefPathEl::PropName(name) => format!(".{}", name),
+                DataRefPathEl::Param(v) => format!("({})", v),
+                DataRefPathEl::Index(i) => format!("[{}]", i),
             })
             .collect::<Vec<String>>()
             .join("")
     }
+
+    /// The id of the step this ref points into, if it points into a step at all.
+    pub fn step_id(&self) -> Option<usize> {
+        match self.0[..] {
+            [DataRefPathEl::Step, DataRefPathEl::WithId(id), ..] => Some(id),
+            _ => None,
+        }
+    }
+
+    /// The id of the data this ref points into, if it points into data at all.
+    pub fn data_id(&self) -> Option<usize> {
+        match self.0[..] {
+            [DataRefPathEl::Data, DataRefPathEl::WithId(id), ..] => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Rewrites a step ref by `id_map` (old id -> new id), leaving other refs untouched
+    pub fn remapped(&self, id_map: &HashMap<usize, usize>) -> DataRef {
+        match self.step_id().and_then(|id| id_map.get(&id)) {
+            Some(new_id) => {
+                let mut path = self.0.clone();
+                path[1] = DataRefPathEl::WithId(*new_id);
+                DataRef(path)
+            }
+            None => self.clone(),
+        }
+    }
 }
 
 impl ResolveToNumber for DataRef {
     fn resolve(&self, cx: Scope) -> f64 {
-        match self.0[0] {
-            DataRefPathEl::Step => {
-                let step_id = match self.0[1] {
-                    DataRefPathEl::WithId(i) => i,
-                    _ => todo!(),
-                };
-                let step = use_context::<RwSignal<Vec<Step>>>(cx)
-                    .unwrap()
-                    .with(|steps| {
-                        steps
-                            .iter()
-                            .find(|d| d.id == step_id)
-                            .cloned()
-                            .expect("Invalid step id")
-                    });
-                match step.data {
-                    StepData::DrawPoint(point) => match self.0[2] {
-                        DataRefPathEl::PropName("x") => point.get().resolve(cx).x,
-                        DataRefPathEl::PropName("y") => point.get().resolve(cx).y,
-                        _ => todo!(),
-                    },
-                    StepData::DrawLine { start, end } => match self.0[2] {
-                        DataRefPathEl::PropName("start") => match self.0[3] {
-                            DataRefPathEl::PropName("x") => start.get().resolve(cx).x,
-                            DataRefPathEl::PropName("y") => start.get().resolve(cx).y,
-                            _ => todo!(),
-                        },
-                        DataRefPathEl::PropName("end") => match self.0[3] {
-                            DataRefPathEl::PropName("x") => end.get().resolve(cx).x,
-                            DataRefPathEl::PropName("y") => end.get().resolve(cx).y,
-                            _ => todo!(),
-                        },
-                        _ => todo!(),
+        // Coordinate refs pick x or y off the point before them; anything else is a number prop
+        let Some((last, point_path)) = self.0.split_last() else {
+            return f64::NAN;
+        };
+        match last {
+            DataRefPathEl::PropName("x") => {
+                ResolveToPoint::resolve(&DataRef(point_path.to_vec()), cx).x
+            }
+            DataRefPathEl::PropName("y") => {
+                ResolveToPoint::resolve(&DataRef(point_path.to_vec()), cx).y
+            }
+            // Vector data's components, or NaN for anything else
+            DataRefPathEl::PropName(prop_name) if self.data_id().is_some() => {
+                match self
+                    .data_id()
+                    .and_then(|id| find_data(cx, id))
+                    .map(|d| d.data)
+                {
+                    Some(DataData::Vector { dx, dy }) => match *prop_name {
+                        "dx" => dx.get(),
+                        "dy" => dy.get(),
+                        "length" => dx.get().hypot(dy.get()),
+                        _ => f64::NAN,
                     },
+                    _ => f64::NAN,
                 }
             }
-            DataRefPathEl::Data => todo!(),
-            _ => todo!(),
-        }
-    }
-}
-
-impl ResolveToPoint for DataRef {
-    fn resolve(&self, cx: Scope) -> Point {
-        match self.0[0] {
-            DataRefPathEl::Step => {
-                let step_id = match self.0[1] {
-                    DataRefPathEl::WithId(i) => i,
-                    _ => todo!(),
-                };
-                let step = use_context::<RwSignal<Vec<Step>>>(cx)
-                    .unwrap()
-                    .with(|steps| {
-                        steps
-                            .iter()
-                            .find(|d| d.id == step_id)
-                            .cloned()
-                            .expect("Invalid step id")
-                    });
-                let prop_name = match self.0[2] {
-                    DataRefPathEl::PropName(s) => s,
-                    _ => todo!(),
+            DataRefPathEl::PropName(prop_name) => {
+                let Some(step) = self.step_id().and_then(|id| step_by_id(cx, id)) else {
+                    return f64::NAN;
                 };
+                if let ("fill_radius", Fill::RadialGradient { radius, .. }) =
+                    (*prop_name, step.style.with(|s| s.fill.clone()))
+                {
+                    return radius.get().resolve(cx);
+                }
                 match step.data {
-                    StepData::DrawPoint(point) => match prop_name {
-                        "self" => point.get().resolve(cx),
-                        _ => panic!(
-                            "Invalid prop name '{}': expected one of [{:?}]",
-                            prop_name, "self"
+                    StepData::DrawPoint { size, .. } => match *prop_name {
+                        "size" => size.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::TrimExtend {
+                        line,
+                        boundary,
+                        keep_end,
+                    } => match *prop_name {
+                        "length" => trimmed_line(cx, line.get(), boundary.get(), keep_end.get())
+                            .map(|(start, end)| start.distance(end))
+                            .unwrap_or(f64::NAN),
+                        _ => f64::NAN,
+                    },
+                    StepData::ParallelOffset { distance, .. }
+                    | StepData::OffsetCurve { distance, .. } => match *prop_name {
+                        "distance" => distance.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::RotateCopy { angle, .. } => match *prop_name {
+                        "angle" => angle.get().resolve_in(cx, AngleUnit::Degrees),
+                        _ => f64::NAN,
+                    },
+                    StepData::PointGrid {
+                        col_spacing,
+                        row_spacing,
+                        cols,
+                        rows,
+                        ..
+                    } => match *prop_name {
+                        "col_spacing" => col_spacing.get().resolve(cx),
+                        "row_spacing" => row_spacing.get().resolve(cx),
+                        "cols" => array_count(cols.get().resolve(cx)).unwrap_or(0) as f64,
+                        "rows" => array_count(rows.get().resolve(cx)).unwrap_or(0) as f64,
+                        _ => f64::NAN,
+                    },
+                    StepData::PointAlongLine { t, .. } => match *prop_name {
+                        "t" => t.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::PolarArray { count, sweep, .. } => match *prop_name {
+                        "count" => count.get().resolve(cx),
+                        "sweep" => sweep.get().resolve_in(cx, AngleUnit::Degrees),
+                        _ => f64::NAN,
+                    },
+                    StepData::LinearArray { count, dx, dy, .. } => match *prop_name {
+                        "count" => count.get().resolve(cx),
+                        "dx" => dx.get().resolve(cx),
+                        "dy" => dy.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::TranslateCopy { dx, dy, .. } => match *prop_name {
+                        "dx" => dx.get().resolve(cx),
+                        "dy" => dy.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::Angle { a, vertex, b } => match *prop_name {
+                        "value" => angle_between(
+                            a.get().resolve(cx),
+                            vertex.get().resolve(cx),
+                            b.get().resolve(cx),
                         ),
+                        _ => f64::NAN,
                     },
-                    StepData::DrawLine { start, end } => {
-                        let start = start.get().resolve(cx);
-                        let end = end.get().resolve(cx);
-
-                        match prop_name {
-                            "start" => start,
-                            "mid" => Point {
-                                x: (start.x + end.x) / 2.0,
-                                y: (start.y + end.y) / 2.0,
-                            },
-                            "end" => end,
-                            _ => panic!(
-                                "Invalid prop name '{}': expected one of [{:?}]",
-                                prop_name,
-                                &["start", "mid", "end"]
-                            ),
+                    StepData::Distance { a, b } => match *prop_name {
+                        "value" => a.get().resolve(cx).distance(b.get().resolve(cx)),
+                        _ => f64::NAN,
+                    },
+                    StepData::DrawLine { start, end } => match *prop_name {
+                        "length" => start.get().resolve(cx).distance(end.get().resolve(cx)),
+                        _ => f64::NAN,
+                    },
+                    StepData::DrawArrow {
+                        start,
+                        end,
+                        head_size,
+                    } => match *prop_name {
+                        "length" => start.get().resolve(cx).distance(end.get().resolve(cx)),
+                        "head_size" => head_size.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::DrawImage { scale, .. } => match *prop_name {
+                        "scale" => scale.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::ScaleBar {
+                        length,
+                        divisions,
+                        size,
+                        ..
+                    } => match *prop_name {
+                        "length" => length.get().resolve(cx),
+                        "divisions" => array_count(divisions.get().resolve(cx)).unwrap_or(0) as f64,
+                        "size" => size.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::Axes {
+                        x_min,
+                        x_max,
+                        y_min,
+                        y_max,
+                        spacing,
+                        size,
+                        ..
+                    } => match *prop_name {
+                        "x_min" => x_min.get().resolve(cx),
+                        "x_max" => x_max.get().resolve(cx),
+                        "y_min" => y_min.get().resolve(cx),
+                        "y_max" => y_max.get().resolve(cx),
+                        "spacing" => spacing.get().resolve(cx),
+                        "size" => size.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::DrawText { size, .. } | StepData::Callout { size, .. } => {
+                        match *prop_name {
+                            "size" => size.get().resolve(cx),
+                            _ => f64::NAN,
+                        }
+                    }
+                    StepData::DrawPolygon { radius, sides, .. } => match *prop_name {
+                        "radius" => radius.get().resolve(cx),
+                        "sides" => polygon_sides(sides.get().resolve(cx)) as f64,
+                        _ => f64::NAN,
+                    },
+                    StepData::Annulus {
+                        inner_radius,
+                        outer_radius,
+                        ..
+                    } => match *prop_name {
+                        "inner_radius" => inner_radius.get().resolve(cx),
+                        "outer_radius" => outer_radius.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::ShapeBoolean { op, a, b } => match *prop_name {
+                        "area" => clip::area(
+                            &boolean_shape(cx, op.get(), a.get(), b.get()).unwrap_or_default(),
+                        ),
+                        _ => f64::NAN,
+                    },
+                    StepData::BoundingBox { sources } => {
+                        let (min, max) =
+                            bounding_box(cx, sources).unwrap_or((Point::NAN, Point::NAN));
+                        match *prop_name {
+                            "width" => max.x - min.x,
+                            "height" => max.y - min.y,
+                            _ => f64::NAN,
                         }
                     }
+                    StepData::DrawRect { width, height, .. } => match *prop_name {
+                        "width" => width.get().resolve(cx),
+                        "height" => height.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::DrawRoundedRect {
+                        width,
+                        height,
+                        radius,
+                        ..
+                    } => match *prop_name {
+                        "width" => width.get().resolve(cx),
+                        "height" => height.get().resolve(cx),
+                        "radius" => radius.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::DrawArc {
+                        radius,
+                        start_angle,
+                        end_angle,
+                        ..
+                    }
+                    | StepData::DrawSector {
+                        radius,
+                        start_angle,
+                        end_angle,
+                        ..
+                    } => match *prop_name {
+                        "radius" => radius.get().resolve(cx),
+                        "start_angle" => start_angle.get().resolve_in(cx, AngleUnit::Radians),
+                        "end_angle" => end_angle.get().resolve_in(cx, AngleUnit::Radians),
+                        _ => f64::NAN,
+                    },
+                    StepData::ParametricCurve { t_min, t_max, .. } => match *prop_name {
+                        "t_min" => t_min.get().resolve(cx),
+                        "t_max" => t_max.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    StepData::FunctionPlot { x_min, x_max, .. } => match *prop_name {
+                        "x_min" => x_min.get().resolve(cx),
+                        "x_max" => x_max.get().resolve(cx),
+                        _ => f64::NAN,
+                    },
+                    _ => f64::NAN,
+                }
+            }
+            // Missing data, or data which isn't a number, comes out as NaN
+            DataRefPathEl::WithId(id) if self.data_id().is_some() => {
+                match find_data(cx, *id).map(|d| d.data) {
+                    Some(DataData::Number(n))
+                    | Some(DataData::Angle(n))
+                    | Some(DataData::Slider { value: n, .. }) => n.get(),
+                    // So a toggle can switch a value on and off, or pick one in `if(...)`
+                    Some(DataData::Bool(b)) => {
+                        if b.get() {
+                            1.0
+                        } else {
+                            0.0
+                        }
+                    }
+                    _ => f64::NAN,
                 }
             }
-            DataRefPathEl::Data => todo!(),
-            _ => todo!(),
+            _ => f64::NAN,
         }
     }
 }
 
-impl Step {
-    pub fn snap_points(&self) -> Vec<DataRef> {
-        match self.data {
-            StepData::DrawPoint(_) => vec![DataRef(vec![
-                DataRefPathEl::Step,
-                DataRefPathEl::WithId(self.id),
-                DataRefPathEl::PropName("self"),
-            ])],
-            StepData::DrawLine { .. } => vec![
-                DataRef(vec![
-                    DataRefPathEl::Step,
-                    DataRefPathEl::WithId(self.id),
-                    DataRefPathEl::PropName("start"),
-                ]),
-                DataRef(vec![
-                    DataRefPathEl::Step,
-                    DataRefPathEl::WithId(self.id),
-                    DataRefPathEl::PropName("mid"),
-                ]),
-                DataRef(vec![
-                    DataRefPathEl::Step,
-                    DataRefPathEl::WithId(self.id),
-                    DataRefPathEl::PropName("end"),
-                ]),
-            ],
-        }
+/// The corners, edge midpoints and center of a rect step
+const RECT_POINT_PROPS: [&str; 9] = [
+    "origin",
+    "top_mid",
+    "top_right",
+    "left_mid",
+    "center",
+    "right_mid",
+    "bottom_left",
+    "bottom_mid",
+    "bottom_right",
+];
+
+/// One of a rect's `RECT_POINT_PROPS`
+fn rect_point(origin: Point, width: f64, height: f64, prop_name: &str) -> Point {
+    let (dx, dy) = match prop_name {
+        "origin" => (0.0, 0.0),
+        "top_mid" => (0.5, 0.0),
+        "top_right" => (1.0, 0.0),
+        "left_mid" => (0.0, 0.5),
+        "center" => (0.5, 0.5),
+        "right_mid" => (1.0, 0.5),
+        "bottom_left" => (0.0, 1.0),
+        "bottom_mid" => (0.5, 1.0),
+        "bottom_right" => (1.0, 1.0),
+        _ => return Point::NAN,
+    };
+    Point {
+        x: origin.x + dx * width,
+        y: origin.y + dy * height,
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-pub struct Step {
-    pub id: usize,
-    pub data: StepData,
+fn polygon_sides(sides: f64) -> usize {
+    sides.round().max(3.0) as usize
 }
 
-#[derive(Clone, Copy, Debug)]
-pub enum DataData {
-    Number(RwSignal<f64>),
-    Point(RwSignal<PointSignal>),
+/// Vertices of a regular polygon, starting at the top and going clockwise on screen
+fn polygon_vertices(center: Point, radius: f64, sides: usize) -> Vec<Point> {
+    (0..sides)
+        .map(|i| {
+            let angle =
+                -std::f64::consts::FRAC_PI_2 + std::f64::consts::PI * 2.0 * i as f64 / sides as f64;
+            arc_point(center, radius, angle)
+        })
+        .collect()
 }
 
-#[derive(Clone, Copy, Debug)]
-pub struct Data {
-    pub id: usize,
-    pub data: DataData,
+fn arc_point(center: Point, radius: f64, angle: f64) -> Point {
+    Point {
+        x: center.x + radius * angle.cos(),
+        y: center.y + radius * angle.sin(),
+    }
 }
 
-#[derive(Copy, Clone, Default)]
-struct DragData {
-    initial_value: f64,
-    start: f64,
-}
+/// Where the line through `start` and `end` crosses `x = value` (or `y = value`)
+fn line_crossing(start: Point, end: Point, vertical: bool, value: f64) -> Option<Point> {
+    let (along, along_delta) = if vertical {
+        (start.x, end.x - start.x)
+    } else {
+        (start.y, end.y - start.y)
+    };
+    if along_delta.abs() < f64::EPSILON {
+        return None;
+    }
 
-#[component]
-pub fn DraggableNumView(cx: Scope, d: RwSignal<f64>) -> impl IntoView {
-    let (d, set_d) = d.split();
+    let t = (value - along) / along_delta;
+    Some(Point {
+        x: start.x + t * (end.x - start.x),
+        y: start.y + t * (end.y - start.y),
+    })
+}
 
-    let (drag_data, set_drag_data) = create_signal(cx, DragData::default());
+/// Where the infinite lines through `a1`, `a2` and through `b1`, `b2` cross
+pub fn line_intersection(a1: Point, a2: Point, b1: Point, b2: Point) -> Result<Point, String> {
+    let (da, db) = ((a2.x - a1.x, a2.y - a1.y), (b2.x - b1.x, b2.y - b1.y));
+    let denom = da.0 * db.1 - da.1 * db.0;
+    if denom.abs() < f64::EPSILON {
+        return Err("The lines are parallel".to_string());
+    }
 
-    let mousemove_callback = move |e: web_sys::MouseEvent| {
-        let delta = drag_data().start - e.y() as f64;
-        set_d(drag_data().initial_value + delta);
-    };
-    let mousemove_closure =
-        wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(mousemove_callback).into_js_value();
-    let mousemove_closure_clone = mousemove_closure.clone();
+    let t = ((b1.x - a1.x) * db.1 - (b1.y - a1.y) * db.0) / denom;
+    Ok(Point {
+        x: a1.x + t * da.0,
+        y: a1.y + t * da.1,
+    })
+}
 
-    let mouseup_callback = move |_e: web_sys::MouseEvent| {
-        document()
-            .remove_event_listener_with_callback(
-                "mousemove",
-                mousemove_closure.as_ref().unchecked_ref(),
-            )
-            .unwrap();
-    };
-    let mouseup_closure = wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(mouseup_callback);
+/// The point `t` of the way from `a` to `b`
+pub fn point_along(a: Point, b: Point, t: f64) -> Point {
+    Point {
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+    }
+}
 
-    let mousedown_callback = move |e: web_sys::MouseEvent| {
-        set_drag_data.update(|dd| {
-            dd.initial_value = d();
-            dd.start = e.y() as f64;
-        });
+/// The point `distance` along `points` and its heading in radians there
+pub fn polyline_at(points: &[Point], distance: f64) -> Option<(Point, f64)> {
+    let mut remaining = distance;
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let length = a.distance(b);
+        if length > 0.0 && remaining <= length {
+            return Some((
+                point_along(a, b, remaining / length),
+                (b.y - a.y).atan2(b.x - a.x),
+            ));
+        }
+        remaining -= length;
+    }
+    None
+}
 
-        document()
-            .add_event_listener_with_callback(
-                "mousemove",
-                mousemove_closure_clone.as_ref().unchecked_ref(),
-            )
-            .unwrap();
+/// The average of `points`, which is NaN when there are none
+pub fn centroid(points: &[Point]) -> Point {
+    let n = points.len() as f64;
+    Point {
+        x: points.iter().map(|p| p.x).sum::<f64>() / n,
+        y: points.iter().map(|p| p.y).sum::<f64>() / n,
+    }
+}
 
-        document()
-            .add_event_listener_with_callback("mouseup", mouseup_closure.as_ref().unchecked_ref())
-            .unwrap();
+/// `point_along` the referenced line, kept to the part that's drawn
+fn point_on_line(cx: Scope, line: Option<DataRef>, t: f64) -> Result<Point, String> {
+    let (a, b) = referenced_line(cx, line.clone())?;
+    let t = match line
+        .and_then(|r| r.step_id())
+        .and_then(|id| step_by_id(cx, id))
+        .map(|step| step.line_extent(cx))
+    {
+        Some(LineExtent::Segment) => t.clamp(0.0, 1.0),
+        Some(LineExtent::Ray) => t.max(0.0),
+        _ => t,
     };
+    Ok(point_along(a, b, t))
+}
 
-    view! { cx,
-        <div
-            on:mousedown=mousedown_callback
-            style="user-select: none"
-        >
-            {d}
-        </div>
+/// The two points a referenced line step runs through
+fn referenced_line(cx: Scope, line: Option<DataRef>) -> Result<(Point, Point), String> {
+    let id = line
+        .and_then(|r| r.step_id())
+        .ok_or_else(|| "Pick a line".to_string())?;
+    step_by_id(cx, id)
+        .ok_or_else(|| format!("step[{}] is missing", id))?
+        .line_points(cx)
+        .ok_or_else(|| format!("step[{}] isn't a line", id))
+}
+
+/// The foot of the perpendicular from `through` onto the referenced line, and its unit normal
+fn perpendicular_foot(
+    cx: Scope,
+    line: Option<DataRef>,
+    through: Point,
+) -> Result<(Point, Point), String> {
+    let (a, b) = referenced_line(cx, line)?;
+    let length = a.distance(b);
+    if length == 0.0 {
+        return Err("The line has no direction".to_string());
     }
+
+    let (ux, uy) = ((b.x - a.x) / length, (b.y - a.y) / length);
+    let t = (through.x - a.x) * ux + (through.y - a.y) * uy;
+    Ok((
+        Point {
+            x: a.x + t * ux,
+            y: a.y + t * uy,
+        },
+        Point { x: -uy, y: ux },
+    ))
 }
 
-#[derive(PartialEq, Copy, Clone, Debug)]
-pub enum InferTarget {
-    Number(RwSignal<ResolvableTo<NumberSignal>>),
-    Point(RwSignal<ResolvableTo<PointSignal>>),
+fn referenced_circle(cx: Scope, circle: Option<DataRef>) -> Result<(Point, f64), String> {
+    let id = circle
+        .and_then(|r| r.step_id())
+        .ok_or_else(|| "Pick a circle".to_string())?;
+    step_by_id(cx, id)
+        .ok_or_else(|| format!("step[{}] is missing", id))?
+        .circle(cx)
+        .ok_or_else(|| format!("step[{}] isn't a circle", id))
 }
 
-#[component]
-fn ResolvableToNumberView(
+/// Where the line from `from` touching the referenced circle meets it
+fn tangent_point(
     cx: Scope,
-    n: RwSignal<ResolvableTo<NumberSignal>>,
-    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
-) -> impl IntoView {
-    move || {
-        let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+    circle: Option<DataRef>,
+    from: Point,
+    other_side: bool,
+) -> Result<Point, String> {
+    let (center, radius) = referenced_circle(cx, circle)?;
+    let (radius, distance) = (radius.abs(), center.distance(from));
+    if distance <= radius {
+        return Err("The point must be outside the circle".to_string());
+    }
 
-        if let Some(InferTarget::Number(it)) = context_infer_target.get() {
-            if n == it {
-                return view! { cx,
-                    <div class="flex flex-row">
-                        <p>"..."</p>
-                        <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
-                            context_infer_target.set(None);
-                        }>
-                            "C"
-                        </button>
-                    </div>
-                }
-                .into_view(cx);
-            } else {
-                return view! { cx,
-                    <div class="flex flex-row">
-                        <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
-                            it.set(ResolvableTo::Ref(DataRef(data_ref_path.get())));
-                            context_infer_target.set(None);
-                        }>
-                            "O"
-                        </button>
-                    </div>
-                }
-                .into_view(cx);
-            }
-        }
+    // The radius to the touching point is square to the tangent, acos(r / d) round from `from`
+    let spread = (radius / distance).acos();
+    let angle =
+        (from.y - center.y).atan2(from.x - center.x) + if other_side { -spread } else { spread };
+    Ok(Point {
+        x: center.x + radius * angle.cos(),
+        y: center.y + radius * angle.sin(),
+    })
+}
 
-        match n.get() {
-            ResolvableTo::T(t) => view! { cx,
-                <div class="flex flex-row">
-                    <DraggableNumView d=t />
-                    <button class="border-2 border-gray-800" on:click=move |_| {
-                        context_infer_target.set(Some(InferTarget::Number(n)));
-                    }>"I"</button>
-                </div>
-            }
-            .into_view(cx),
-            ResolvableTo::Ref(r) => view! { cx,
-                <div class="flex flex-col">
-                    <p>{r.desc()}</p>
-                    // <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
-                    //     n.set(ResolvableTo::T(create_signal(cx, 0.0)));
-                    // }>
-                    //     "Cancel Infer"
-                    // </button>
-                </div>
-            }
-            .into_view(cx),
-        }
+/// The referenced line's two points moved `distance` along its left-hand normal
+fn offset_line(cx: Scope, line: Option<DataRef>, distance: f64) -> Result<(Point, Point), String> {
+    let (a, b) = referenced_line(cx, line)?;
+    let length = a.distance(b);
+    if length == 0.0 {
+        return Err("The line has no direction".to_string());
     }
+
+    let (nx, ny) = (
+        (a.y - b.y) / length * distance,
+        (b.x - a.x) / length * distance,
+    );
+    Ok((
+        Point {
+            x: a.x + nx,
+            y: a.y + ny,
+        },
+        Point {
+            x: b.x + nx,
+            y: b.y + ny,
+        },
+    ))
 }
 
-#[component]
-fn InnerStepViewDrawPoint(
-    cx: Scope,
-    sig: RwSignal<ResolvableTo<PointSignal>>,
-    point: PointSignal,
-    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
-) -> impl IntoView {
-    move || {
-        let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
-        if let Some(InferTarget::Point(it)) = context_infer_target.get() {
-            if sig == it {
-                return view! { cx,
-                    <div class="flex flex-col">
-                        <p>"..."</p>
-                        <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
-                            context_infer_target.set(None);
-                        }>
-                            "Cancel Infer"
-                        </button>
-                    </div>
-                }
-                .into_view(cx);
-            }
-        }
+/// How far a miter may reach, in offset distances, before it's beveled instead
+const MITER_LIMIT: f64 = 4.0;
 
-        let mut x_path = data_ref_path.get();
-        x_path.push(DataRefPathEl::PropName("x"));
-        let x_path = store_value(cx, x_path);
+/// `points` moved `distance` to their left (outwards when `closed`), corners met by `join`
+pub fn offset_polyline(
+    points: &[Point],
+    closed: bool,
+    distance: f64,
+    join: LineJoin,
+) -> Vec<Point> {
+    let mut points = points.to_vec();
+    points.dedup_by(|p, q| p.distance(*q) < 1e-9);
+    if closed && points.len() > 1 && points[0].distance(points[points.len() - 1]) < 1e-9 {
+        points.pop();
+    }
+    let n = points.len();
+    if n < 2 {
+        return Vec::new();
+    }
 
-        let mut y_path = data_ref_path.get();
-        y_path.push(DataRefPathEl::PropName("y"));
-        let y_path = store_value(cx, y_path);
+    // Clockwise on screen, the left-hand normal points inwards
+    let distance = if closed && clip::signed_area(&points) > 0.0 {
+        -distance
+    } else {
+        distance
+    };
+    let segments = if closed { n } else { n - 1 };
+    let offset = |i: usize| {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        let length = a.distance(b);
+        let (nx, ny) = (
+            (a.y - b.y) / length * distance,
+            (b.x - a.x) / length * distance,
+        );
+        (
+            Point {
+                x: a.x + nx,
+                y: a.y + ny,
+            },
+            Point {
+                x: b.x + nx,
+                y: b.y + ny,
+            },
+        )
+    };
 
-        view! { cx,
-            <div class="flex flex-col">
-                <p>"Draw Point"</p>
-                <div class="flex flex-row">
-                    <p>"x: "</p>
-                    <ResolvableToNumberView n={point.x} data_ref_path=x_path />
-                    <p class="ml-3">"y: "</p>
-                    <ResolvableToNumberView n={point.y} data_ref_path=y_path />
-                </div>
-                <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
-                    context_infer_target.set(Some(InferTarget::Point(sig)));
-                }>
-                    "Infer"
-                </button>
-            </div>
-        }
-        .into_view(cx)
+    let mut offset_points = Vec::new();
+    if !closed {
+        offset_points.push(offset(0).0);
     }
+    // Corner `i` is where segment `i - 1` meets segment `i`
+    let corners = if closed { 0..n } else { 1..n - 1 };
+    for i in corners {
+        let (before, after) = (offset((i + segments - 1) % segments), offset(i));
+        offset_points.extend(offset_corner(points[i], before, after, distance, join));
+    }
+    if !closed {
+        offset_points.push(offset(segments - 1).1);
+    }
+    offset_points
 }
 
-#[component]
-fn InnerStepViewResolveableToPoint(
-    cx: Scope,
-    point: RwSignal<ResolvableTo<PointSignal>>,
-    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
-) -> impl IntoView {
-    move || match point() {
-        ResolvableTo::T(p) => view! { cx,
-            <InnerStepViewDrawPoint sig=point point=p data_ref_path=data_ref_path />
-        }
-        .into_view(cx),
-        ResolvableTo::Ref(dr) => {
-            let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
-            view! { cx,
-                <div>{dr.desc()}</div>
-                <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
-                    context_infer_target.set(Some(InferTarget::Point(point)));
-                }>
-                    "Infer"
-                </button>
+/// Where the offset segments `before` and `after` meet around `corner`
+fn offset_corner(
+    corner: Point,
+    (a1, b1): (Point, Point),
+    (a2, b2): (Point, Point),
+    distance: f64,
+    join: LineJoin,
+) -> Vec<Point> {
+    let (d1, d2) = ((b1.x - a1.x, b1.y - a1.y), (b2.x - a2.x, b2.y - a2.y));
+    let turn = d1.0 * d2.1 - d1.1 * d2.0;
+    let scale = (d1.0.hypot(d1.1) * d2.0.hypot(d2.1)).max(f64::MIN_POSITIVE);
+    if turn.abs() / scale < 1e-9 {
+        // Carrying straight on
+        return vec![b1];
+    }
+
+    let t = ((a2.x - a1.x) * d2.1 - (a2.y - a1.y) * d2.0) / turn;
+    let meet = Point {
+        x: a1.x + d1.0 * t,
+        y: a1.y + d1.1 * t,
+    };
+    // Turning towards the offset side puts it on the inside
+    let outside = turn * distance < 0.0;
+    match join {
+        _ if !outside => vec![meet],
+        LineJoin::Miter if meet.distance(corner) <= MITER_LIMIT * distance.abs() => vec![meet],
+        LineJoin::Round => {
+            let start = (b1.y - corner.y).atan2(b1.x - corner.x);
+            let end = (a2.y - corner.y).atan2(a2.x - corner.x);
+            // The short way round
+            let mut sweep = end - start;
+            if sweep > std::f64::consts::PI {
+                sweep -= std::f64::consts::PI * 2.0;
+            } else if sweep < -std::f64::consts::PI {
+                sweep += std::f64::consts::PI * 2.0;
             }
+            arc_points(corner, distance.abs(), start, start + sweep)
         }
-        .into_view(cx),
+        _ => vec![b1, a2],
     }
 }
 
-#[component]
-fn InnerStepViewDrawLine(
+/// The paths of the referenced step offset by `distance`, each with whether it's closed
+fn offset_curve(
     cx: Scope,
-    start: RwSignal<ResolvableTo<PointSignal>>,
-    end: RwSignal<ResolvableTo<PointSignal>>,
-    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
-) -> impl IntoView {
-    let mut start_path = data_ref_path.get();
-    start_path.push(DataRefPathEl::PropName("start"));
-    let start_path = store_value(cx, start_path);
-
-    let mut end_path = data_ref_path.get();
-    end_path.push(DataRefPathEl::PropName("start"));
-    let end_path = store_value(cx, end_path);
+    source: Option<DataRef>,
+    distance: f64,
+    join: LineJoin,
+) -> Result<Vec<(Vec<Point>, bool)>, String> {
+    let id = source
+        .and_then(|r| r.step_id())
+        .ok_or_else(|| "Pick a line, curve or shape".to_string())?;
+    let paths = step_by_id(cx, id)
+        .ok_or_else(|| format!("step[{}] is missing", id))?
+        .paths(cx)
+        .ok_or_else(|| format!("step[{}] has no path to offset", id))?;
+    Ok(paths
+        .into_iter()
+        .map(|(points, closed)| (offset_polyline(&points, closed, distance, join), closed))
+        .filter(|(points, _)| points.len() >= 2)
+        .collect())
+}
 
-    view! { cx,
-        <div class="flex flex-col">
-            <p>"Draw Line"</p>
+/// The angle in degrees at `vertex` from `a` to `b`, in -180..=180 and clockwise when positive
+pub fn angle_between(a: Point, vertex: Point, b: Point) -> f64 {
+    let from = (a.y - vertex.y).atan2(a.x - vertex.x);
+    let to = (b.y - vertex.y).atan2(b.x - vertex.x);
+    let degrees = (to - from).to_degrees();
+    if degrees > 180.0 {
+        degrees - 360.0
+    } else if degrees <= -180.0 {
+        degrees + 360.0
+    } else {
+        degrees
+    }
+}
 
-            <p>"start: "</p>
-            <InnerStepViewResolveableToPoint point={start} data_ref_path=start_path />
+/// Mirrors `p` across the infinite line through `a` and `b`
+pub fn reflect(p: Point, a: Point, b: Point) -> Point {
+    let length = a.distance(b);
+    if length == 0.0 {
+        return p;
+    }
 
-            <p>"end: "</p>
-            <InnerStepViewResolveableToPoint point={end} data_ref_path=end_path />
-        </div>
+    let (ux, uy) = ((b.x - a.x) / length, (b.y - a.y) / length);
+    let t = (p.x - a.x) * ux + (p.y - a.y) * uy;
+    let foot = Point {
+        x: a.x + t * ux,
+        y: a.y + t * uy,
+    };
+    Point {
+        x: 2.0 * foot.x - p.x,
+        y: 2.0 * foot.y - p.y,
     }
 }
 
-#[component]
-pub fn InnerStepView(cx: Scope, step: Step) -> impl IntoView {
-    move || {
-        let data_ref_path = store_value(
-            cx,
-            vec![DataRefPathEl::Step, DataRefPathEl::WithId(step.id)],
-        );
+/// The step a whole-step ref points at, if it's set and the step still exists
+fn referenced_step(cx: Scope, r: Option<DataRef>) -> Option<Step> {
+    let id = r?.step_id()?;
+    use_context::<RwSignal<Vec<Step>>>(cx)
+        .unwrap()
+        .with(|steps| steps.iter().find(|s| s.id == id).copied())
+}
 
-        match step.data {
-            StepData::DrawPoint(point) => match point.get() {
-                ResolvableTo::T(p) => view! { cx,
-                    <InnerStepViewDrawPoint sig=point point=p data_ref_path />
-                }
-                .into_view(cx),
-                ResolvableTo::Ref(dr) => {
-                    view! { cx,
-                        <div>"TODO"</div>
-                    <div>{dr.desc()}</div>
-                    }
-                }
-                .into_view(cx),
-            },
-            StepData::DrawLine { start, end } => view! { cx,
-                <InnerStepViewDrawLine start end data_ref_path />
-            }
-            .into_view(cx),
-        }
+/// The (top left, bottom right) corners of the box around every referenced step's bounds
+fn bounding_box(
+    cx: Scope,
+    sources: RwSignal<Vec<RwSignal<Option<DataRef>>>>,
+) -> Result<(Point, Point), String> {
+    let mut corners: Option<(Point, Point)> = None;
+    for source in sources.get() {
+        let step = referenced_step(cx, source.get()).ok_or_else(|| "Pick a step".to_string())?;
+        let Some((min, max)) = step.bounds(cx) else {
+            continue;
+        };
+        corners = Some(match corners {
+            None => (min, max),
+            Some((lo, hi)) => (
+                Point {
+                    x: lo.x.min(min.x),
+                    y: lo.y.min(min.y),
+                },
+                Point {
+                    x: hi.x.max(max.x),
+                    y: hi.y.max(max.y),
+                },
+            ),
+        });
     }
+    corners.ok_or_else(|| "None of the picked steps have any points to bound".to_string())
 }
 
-#[component]
-pub fn StepView(cx: Scope, step: Step) -> impl IntoView {
-    view! { cx,
-        <div class="p-2 m-1 shadow bg-white w-[90%] rounded-lg relative group">
-            <button
-                class="absolute left-[90%] opacity-0 group-hover:opacity-100 transition-all"
-                on:click=move |_| {
-                    use_context::<RwSignal<Vec<Step>>>(cx).unwrap().update(|s| {
-                        s.retain(|s| s.id != step.id);
-                    });
-                }>
-                "x"
-            </button>
-            <div class="w-full h-full flex flex-col">
-                <p>"Step #" {step.id}</p>
-                <InnerStepView step/>
-            </div>
-        </div>
+/// Points along an arc from `start` to `end` (radians), both ends included
+fn arc_points(center: Point, radius: f64, start: f64, end: f64) -> Vec<Point> {
+    let segments = ((end - start).abs() / (std::f64::consts::PI * 2.0) * 64.0)
+        .ceil()
+        .max(1.0) as usize;
+    (0..=segments)
+        .map(|i| {
+            arc_point(
+                center,
+                radius,
+                start + (end - start) * i as f64 / segments as f64,
+            )
+        })
+        .collect()
+}
+
+fn referenced_outline(cx: Scope, shape: Option<DataRef>) -> Result<Vec<Point>, String> {
+    let id = shape
+        .and_then(|r| r.step_id())
+        .ok_or_else(|| "Pick a shape".to_string())?;
+    step_by_id(cx, id)
+        .ok_or_else(|| format!("step[{}] is missing", id))?
+        .outline(cx)
+        .ok_or_else(|| format!("step[{}] isn't a single closed shape", id))
+}
+
+/// The outlines of the referenced shapes combined by `op`, as even-odd rings
+fn boolean_shape(
+    cx: Scope,
+    op: BoolOp,
+    a: Option<DataRef>,
+    b: Option<DataRef>,
+) -> Result<Vec<Vec<Point>>, String> {
+    let (a, b) = (referenced_outline(cx, a)?, referenced_outline(cx, b)?);
+    let rings = clip::apply(op, &a, &b);
+    if rings.is_empty() {
+        return Err("Nothing is left of the shapes".to_string());
     }
+    Ok(rings)
 }
 
-#[component]
-pub fn InnerDataViewPoint(
+/// The step a mirror reflects, and the two points its axis runs through
+fn mirror_parts(
     cx: Scope,
-    point: PointSignal,
-    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
-) -> impl IntoView {
-    let mut x_path = data_ref_path.get();
-    x_path.push(DataRefPathEl::PropName("x"));
-    let x_path = store_value(cx, x_path);
+    source: Option<DataRef>,
+    axis: Option<DataRef>,
+) -> Result<(Step, (Point, Point)), String> {
+    let source = referenced_step(cx, source).ok_or_else(|| "Pick a step to mirror".to_string())?;
+    let axis = referenced_line(cx, axis)?;
+    if axis.0.distance(axis.1) == 0.0 {
+        return Err("The axis has no direction".to_string());
+    }
+    Ok((source, axis))
+}
 
-    let mut y_path = data_ref_path.get();
-    y_path.push(DataRefPathEl::PropName("y"));
-    let y_path = store_value(cx, y_path);
+/// Most copies an array step will make, so a stray huge count can't stall drawing
+pub const MAX_ARRAY_COUNT: usize = 1000;
 
-    move || {
-        view! { cx,
-            <div class="flex flex-row">
-                <p>"x: "</p>
-                <ResolvableToNumberView n={point.x} data_ref_path=x_path />
-                <p class="ml-3">"y: "</p>
-                <ResolvableToNumberView n={point.y} data_ref_path=y_path />
-            </div>
-        }
+/// An array's `count` as a whole number of copies
+fn array_count(count: f64) -> Result<usize, String> {
+    let count = count.round();
+    if !(1.0..=MAX_ARRAY_COUNT as f64).contains(&count) {
+        return Err(format!("The count must be from 1 to {}", MAX_ARRAY_COUNT));
     }
+    Ok(count as usize)
 }
 
-#[component]
-pub fn InnerDataView(cx: Scope, data: Data) -> impl IntoView {
-    move || match data.data {
-        DataData::Number(n) => view! { cx,
-            <div>
-                <p>"Number"</p>
-                <DraggableNumView d={n} />
-            </div>
-        }
-        .into_view(cx),
-        DataData::Point(p) => view! { cx,
-            <div>
-                <p>"Point"</p>
-                <InnerDataViewPoint point={p.get()} data_ref_path={store_value(cx, vec![
-                    DataRefPathEl::Data,
-                    DataRefPathEl::WithId(data.id),
-                ])} />
-            </div>
-        }
-        .into_view(cx),
+/// Tick offsets from the origin: every multiple of `spacing` from `min` to `max`
+fn axis_ticks(min: f64, max: f64, spacing: f64) -> Result<Vec<f64>, String> {
+    if spacing.is_nan() || spacing <= 0.0 {
+        return Err("The tick spacing must be more than 0".to_string());
+    }
+    if min.is_nan() || max.is_nan() || min > max {
+        return Err("An axis's min must not be more than its max".to_string());
+    }
+    let (first, last) = ((min / spacing).ceil(), (max / spacing).floor());
+    if last - first >= MAX_ARRAY_COUNT as f64 {
+        return Err(format!(
+            "An axis can have at most {} ticks; widen the spacing",
+            MAX_ARRAY_COUNT
+        ));
     }
+    Ok((first as i64..=last as i64)
+        .map(|i| i as f64 * spacing)
+        .collect())
 }
 
-#[component]
-pub fn DataView(cx: Scope, data: Data) -> impl IntoView {
-    view! { cx,
-        <div class="p-2 m-1 shadow bg-white w-[90%] rounded-lg relative group">
-            <button
-                class="absolute left-[90%] opacity-0 group-hover:opacity-100 transition-all"
-                on:click=move |_| {
-                    use_context::<RwSignal<Vec<Data>>>(cx).unwrap().update(|s| {
-                        s.retain(|d| d.id != data.id);
-                    });
-                }>
-                "x"
-            </button>
-            <div class="w-full h-full flex flex-col">
-                <p>"Data #" {data.id}</p>
-                <InnerDataView data/>
-            </div>
-        </div>
-    }
+/// An axes step's (x ticks, y ticks)
+fn axes_ticks(cx: Scope, data: StepData) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let StepData::Axes {
+        x_min,
+        x_max,
+        y_min,
+        y_max,
+        spacing,
+        ..
+    } = data
+    else {
+        return Err("Not an axes step".to_string());
+    };
+    let spacing = spacing.get().resolve(cx);
+    Ok((
+        axis_ticks(x_min.get().resolve(cx), x_max.get().resolve(cx), spacing)?,
+        axis_ticks(y_min.get().resolve(cx), y_max.get().resolve(cx), spacing)?,
+    ))
 }
 
-#[component]
-pub fn DrawlingCanvasView(cx: Scope, steps: RwSignal<Vec<Step>>) -> impl IntoView {
-    let scale_factor = 16.0f64;
+/// A point grid's (rows, cols)
+fn grid_size(
+    cx: Scope,
+    rows: RwSignal<ResolvableTo<NumberSignal>>,
+    cols: RwSignal<ResolvableTo<NumberSignal>>,
+) -> Result<(usize, usize), String> {
+    Ok((
+        array_count(rows.get().resolve(cx))?,
+        array_count(cols.get().resolve(cx))?,
+    ))
+}
 
-    let canvas = view! { cx,
-        <canvas class="border-2 border-gray-800 max-w-screen max-h-screen" />
+/// Where the point at (`row`, `col`) of a point grid is
+pub fn grid_point(
+    origin: Point,
+    col_spacing: f64,
+    row_spacing: f64,
+    row: usize,
+    col: usize,
+) -> Point {
+    Point {
+        x: origin.x + col_spacing * col as f64,
+        y: origin.y + row_spacing * row as f64,
+    }
+}
+
+/// The step a copy or array step is of, and how each of its copies moves it
+fn copy_parts(cx: Scope, data: StepData) -> Result<(Step, Vec<Transform>), String> {
+    let (source, transforms) = match data {
+        StepData::RotateCopy {
+            source,
+            center,
+            angle,
+        } => (
+            source,
+            vec![Transform::Rotate {
+                about: center.get().resolve(cx),
+                angle: angle.get().resolve_in(cx, AngleUnit::Degrees),
+            }],
+        ),
+        StepData::TranslateCopy { source, dx, dy } => (
+            source,
+            vec![Transform::Translate {
+                dx: dx.get().resolve(cx),
+                dy: dy.get().resolve(cx),
+            }],
+        ),
+        StepData::TransformCopy { source, transform } => {
+            (source, vec![data_transform(cx, transform.get())?])
+        }
+        StepData::LinearArray {
+            source,
+            count,
+            dx,
+            dy,
+        } => {
+            let count = array_count(count.get().resolve(cx))?;
+            let (dx, dy) = (dx.get().resolve(cx), dy.get().resolve(cx));
+            (
+                source,
+                // The source itself stands in for the first copy
+                (1..count)
+                    .map(|i| Transform::Translate {
+                        dx: dx * i as f64,
+                        dy: dy * i as f64,
+                    })
+                    .collect(),
+            )
+        }
+        StepData::PolarArray {
+            source,
+            center,
+            count,
+            sweep,
+        } => {
+            let count = array_count(count.get().resolve(cx))?;
+            let sweep = sweep.get().resolve_in(cx, AngleUnit::Degrees);
+            let step = if sweep.abs() >= 360.0 || count == 1 {
+                sweep / count as f64
+            } else {
+                sweep / (count - 1) as f64
+            };
+            let about = center.get().resolve(cx);
+            (
+                source,
+                (1..count)
+                    .map(|i| Transform::Rotate {
+                        about,
+                        angle: step * i as f64,
+                    })
+                    .collect(),
+            )
+        }
+        _ => return Err("Not a copy step".to_string()),
+    };
+    let source =
+        referenced_step(cx, source.get()).ok_or_else(|| "Pick a step to copy".to_string())?;
+    Ok((source, transforms))
+}
+
+/// The move transform data makes, for a transformed copy
+fn data_transform(cx: Scope, r: Option<DataRef>) -> Result<Transform, String> {
+    match r
+        .and_then(|r| r.data_id())
+        .and_then(|id| find_data(cx, id))
+        .filter(|d| !d.trashed.get())
+        .map(|d| d.data)
+    {
+        Some(DataData::Transform {
+            dx,
+            dy,
+            angle,
+            scale,
+            about_x,
+            about_y,
+        }) => Ok(Transform::similarity(
+            Point {
+                x: about_x.get(),
+                y: about_y.get(),
+            },
+            scale.get(),
+            angle.get(),
+            dx.get(),
+            dy.get(),
+        )),
+        _ => Err("Pick a transform".to_string()),
+    }
+}
+
+/// A copy's `points[i]`: its source's snap points, moved along with each copy in turn
+fn copied_snap_point(cx: Scope, data: StepData, r: &DataRef) -> Point {
+    let index = match r.0.get(3) {
+        Some(DataRefPathEl::Index(i)) => *i,
+        _ => return Point::NAN,
+    };
+    copy_parts(cx, data)
+        .ok()
+        .and_then(|(source, transforms)| {
+            let snap_points = source.snap_points(cx);
+            let transform = transforms.get(index / snap_points.len().max(1))?;
+            let p = snap_points.get(index % snap_points.len().max(1)).cloned()?;
+            Some(transform.apply(ResolveToPoint::resolve(&p, cx)))
+        })
+        .unwrap_or(Point::NAN)
+}
+
+/// How far a line step runs past the two points it's defined by
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineExtent {
+    Segment,
+    Ray,
+    Infinite,
+}
+
+fn intersection_point(
+    cx: Scope,
+    line_a: Option<DataRef>,
+    line_b: Option<DataRef>,
+) -> Result<Point, String> {
+    let (a1, a2) = referenced_line(cx, line_a)?;
+    let (b1, b2) = referenced_line(cx, line_b)?;
+    line_intersection(a1, a2, b1, b2)
+}
+
+/// A trim/extend step's segment, from the kept point of `line` to `boundary`
+fn trimmed_line(
+    cx: Scope,
+    line: Option<DataRef>,
+    boundary: Option<DataRef>,
+    keep_end: bool,
+) -> Result<(Point, Point), String> {
+    let (a, b) = referenced_line(cx, line)?;
+    let (c, d) = referenced_line(cx, boundary)?;
+    let meet = line_intersection(a, b, c, d)?;
+    Ok((if keep_end { b } else { a }, meet))
+}
+
+fn distance_to_segment(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq == 0.0 {
+        0.0
+    } else {
+        (((p.x - a.x) * dx + (p.y - a.y) * dy) / len_sq).clamp(0.0, 1.0)
+    };
+    ((p.x - (a.x + t * dx)).powi(2) + (p.y - (a.y + t * dy)).powi(2)).sqrt()
+}
+
+/// Ramer–Douglas–Peucker: drops every sample within `tolerance` of the simplified stroke
+pub fn simplify_polyline(points: &[Point], tolerance: f64) -> Vec<Point> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let (split, max_dist) = points[1..points.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i + 1, distance_to_segment(*p, first, last)))
+        .fold((0, 0.0), |max, d| if d.1 > max.1 { d } else { max });
+
+    if max_dist <= tolerance {
+        return vec![first, last];
+    }
+
+    let mut simplified = simplify_polyline(&points[..=split], tolerance);
+    simplified.pop();
+    simplified.extend(simplify_polyline(&points[split..], tolerance));
+    simplified
+}
+
+/// Most control points a stroke converted to a spline gets
+const MAX_SPLINE_POINTS: usize = 12;
+
+/// At most `MAX_SPLINE_POINTS` control points for a spline following the stroke `points`
+pub fn fit_spline(points: &[Point]) -> Vec<Point> {
+    let Some(&first) = points.first() else {
+        return Vec::new();
+    };
+    let (min, max) = points.iter().fold((first, first), |(min, max), p| {
+        (
+            Point {
+                x: min.x.min(p.x),
+                y: min.y.min(p.y),
+            },
+            Point {
+                x: max.x.max(p.x),
+                y: max.y.max(p.y),
+            },
+        )
+    });
+    let mut tolerance = (min.distance(max) * 0.02).max(SKETCH_TOLERANCE);
+    loop {
+        let fitted = simplify_polyline(points, tolerance);
+        if fitted.len() <= MAX_SPLINE_POINTS {
+            return fitted;
+        }
+        tolerance *= 1.5;
+    }
+}
+
+/// Samples `y = f(x)` over `[x_min, x_max]` adaptively, one run per stretch where it's defined
+pub fn sample_function(f: &expr::Expr, x_min: f64, x_max: f64) -> Vec<Vec<Point>> {
+    const INITIAL_SAMPLES: usize = 32;
+    const MAX_DEPTH: u32 = 8;
+
+    fn refine(
+        eval: &dyn Fn(f64) -> Option<Point>,
+        a: Point,
+        b: Point,
+        depth: u32,
+        tolerance: f64,
+        samples: &mut Vec<Option<Point>>,
+    ) {
+        if depth == 0 {
+            return;
+        }
+        let Some(mid) = eval((a.x + b.x) / 2.0) else {
+            samples.push(None);
+            return;
+        };
+        if distance_to_segment(mid, a, b) <= tolerance {
+            return;
+        }
+        refine(eval, a, mid, depth - 1, tolerance, samples);
+        samples.push(Some(mid));
+        refine(eval, mid, b, depth - 1, tolerance, samples);
+    }
+
+    let eval = |x: f64| {
+        f.eval(&|name| (name == "x").then_some(x), AngleUnit::Radians)
+            .ok()
+            .filter(|y| y.is_finite())
+            .map(|y| Point { x, y })
+    };
+    let tolerance = (x_max - x_min).abs() / 1000.0;
+
+    let mut samples = vec![eval(x_min)];
+    for i in 1..=INITIAL_SAMPLES {
+        let b = eval(x_min + (x_max - x_min) * i as f64 / INITIAL_SAMPLES as f64);
+        if let (Some(Some(a)), Some(b)) = (samples.last().copied(), b) {
+            refine(&eval, a, b, MAX_DEPTH, tolerance, &mut samples);
+        }
+        samples.push(b);
+    }
+
+    samples
+        .split(|s| s.is_none())
+        .filter(|run| run.len() >= 2)
+        .map(|run| run.iter().flatten().copied().collect())
+        .collect()
+}
+
+/// The sampled runs of a function plot step; empty if `f` doesn't parse
+fn function_plot_runs(
+    cx: Scope,
+    f: RwSignal<String>,
+    x_min: RwSignal<ResolvableTo<NumberSignal>>,
+    x_max: RwSignal<ResolvableTo<NumberSignal>>,
+) -> Vec<Vec<Point>> {
+    match f.with(|f| expr::parse(f)) {
+        Ok(f) => sample_function(&f, x_min.get().resolve(cx), x_max.get().resolve(cx)),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Samples `(x(t), y(t))` evenly over `[t_min, t_max]`, one run per stretch where it's defined
+pub fn sample_parametric(
+    x: &expr::Expr,
+    y: &expr::Expr,
+    t_min: f64,
+    t_max: f64,
+    samples: usize,
+) -> Vec<Vec<Point>> {
+    let steps = samples.max(2) - 1;
+    let points: Vec<Option<Point>> = (0..=steps)
+        .map(|i| {
+            let t = t_min + (t_max - t_min) * i as f64 / steps as f64;
+            let vars = |name: &str| (name == "t").then_some(t);
+            let p = Point {
+                x: x.eval(&vars, AngleUnit::Radians).ok()?,
+                y: y.eval(&vars, AngleUnit::Radians).ok()?,
+            };
+            (p.x.is_finite() && p.y.is_finite()).then_some(p)
+        })
+        .collect();
+
+    points
+        .split(|p| p.is_none())
+        .filter(|run| run.len() >= 2)
+        .map(|run| run.iter().flatten().copied().collect())
+        .collect()
+}
+
+/// The sampled runs of a parametric curve step; empty if either expression doesn't parse
+fn parametric_curve_runs(
+    cx: Scope,
+    x: RwSignal<String>,
+    y: RwSignal<String>,
+    t_min: RwSignal<ResolvableTo<NumberSignal>>,
+    t_max: RwSignal<ResolvableTo<NumberSignal>>,
+    samples: RwSignal<usize>,
+) -> Vec<Vec<Point>> {
+    match (x.with(|x| expr::parse(x)), y.with(|y| expr::parse(y))) {
+        (Ok(x), Ok(y)) => sample_parametric(
+            &x,
+            &y,
+            t_min.get().resolve(cx),
+            t_max.get().resolve(cx),
+            samples.get(),
+        ),
+        _ => Vec::new(),
+    }
+}
+
+impl StepData {
+    /// The number props a ref into the step can name, e.g. `length` in `step[3].length`
+    pub fn number_props(&self) -> &'static [&'static str] {
+        match self {
+            StepData::DrawPoint { .. } | StepData::DrawText { .. } | StepData::Callout { .. } => {
+                &["size"]
+            }
+            StepData::TrimExtend { .. } | StepData::DrawLine { .. } => &["length"],
+            StepData::ParallelOffset { .. } | StepData::OffsetCurve { .. } => &["distance"],
+            StepData::RotateCopy { .. } => &["angle"],
+            StepData::PointGrid { .. } => &["col_spacing", "row_spacing", "cols", "rows"],
+            StepData::PointAlongLine { .. } => &["t"],
+            StepData::PolarArray { .. } => &["count", "sweep"],
+            StepData::LinearArray { .. } => &["count", "dx", "dy"],
+            StepData::TranslateCopy { .. } => &["dx", "dy"],
+            StepData::Angle { .. } | StepData::Distance { .. } => &["value"],
+            StepData::DrawArrow { .. } => &["length", "head_size"],
+            StepData::DrawImage { .. } => &["scale"],
+            StepData::ScaleBar { .. } => &["length", "divisions", "size"],
+            StepData::Axes { .. } => &["x_min", "x_max", "y_min", "y_max", "spacing", "size"],
+            StepData::DrawPolygon { .. } => &["radius", "sides"],
+            StepData::Annulus { .. } => &["inner_radius", "outer_radius"],
+            StepData::ShapeBoolean { .. } => &["area"],
+            StepData::BoundingBox { .. } | StepData::DrawRect { .. } => &["width", "height"],
+            StepData::DrawRoundedRect { .. } => &["width", "height", "radius"],
+            StepData::DrawArc { .. } | StepData::DrawSector { .. } => {
+                &["radius", "start_angle", "end_angle"]
+            }
+            StepData::ParametricCurve { .. } => &["t_min", "t_max"],
+            StepData::FunctionPlot { .. } => &["x_min", "x_max"],
+            _ => &[],
+        }
+    }
+
+    /// Which of `number_props` are angles, and the unit each resolves in
+    pub fn angle_props(&self) -> &'static [(&'static str, AngleUnit)] {
+        match self {
+            StepData::RotateCopy { .. } => &[("angle", AngleUnit::Degrees)],
+            StepData::PolarArray { .. } => &[("sweep", AngleUnit::Degrees)],
+            StepData::Angle { .. } => &[("value", AngleUnit::Degrees)],
+            StepData::DrawArc { .. } | StepData::DrawSector { .. } => &[
+                ("start_angle", AngleUnit::Radians),
+                ("end_angle", AngleUnit::Radians),
+            ],
+            _ => &[],
+        }
+    }
+
+    /// Drawn square to the axes whatever its points do, so a literal copy can't be rotated
+    pub fn keeps_upright(&self) -> bool {
+        matches!(
+            self,
+            StepData::DrawRect { .. }
+                | StepData::DrawRoundedRect { .. }
+                | StepData::DrawPolygon { .. }
+                | StepData::DrawImage { .. }
+                | StepData::DrawText { .. }
+                | StepData::Callout { .. }
+                | StepData::ScaleBar { .. }
+                | StepData::Axes { .. }
+                | StepData::PointGrid { .. }
+        )
+    }
+
+    /// The sampled runs of a curve step, or None if this isn't one
+    fn curve_runs(&self, cx: Scope) -> Option<Vec<Vec<Point>>> {
+        match *self {
+            StepData::FunctionPlot { f, x_min, x_max } => {
+                Some(function_plot_runs(cx, f, x_min, x_max))
+            }
+            StepData::ParametricCurve {
+                x,
+                y,
+                t_min,
+                t_max,
+                samples,
+            } => Some(parametric_curve_runs(cx, x, y, t_min, t_max, samples)),
+            _ => None,
+        }
+    }
+}
+
+fn find_data(cx: Scope, data_id: usize) -> Option<Data> {
+    use_context::<RwSignal<Vec<Data>>>(cx)
+        .unwrap()
+        .with(|datas| datas.iter().find(|d| d.id == data_id).copied())
+}
+
+/// `v` to 6 places for an input, hiding the float noise of converting it between units
+fn shown_value(v: f64) -> String {
+    ((v * 1e6).round() / 1e6).to_string()
+}
+
+/// The document's angle unit
+fn angle_unit(cx: Scope) -> AngleUnit {
+    use_context::<RwSignal<DocumentSettings>>(cx)
+        .unwrap()
+        .with(|s| s.angle_unit)
+}
+
+/// The unit `r` is kept in, if it's an angle
+fn ref_angle_unit(cx: Scope, r: &DataRef) -> Option<AngleUnit> {
+    if is_angle_data(cx, r) {
+        return Some(AngleUnit::Radians);
+    }
+    let [DataRefPathEl::Step, DataRefPathEl::WithId(id), DataRefPathEl::PropName(prop)] = r.0[..]
+    else {
+        return None;
+    };
+    let step = step_by_id(cx, id)?;
+    step.data
+        .angle_props()
+        .iter()
+        .find(|(p, _)| *p == prop)
+        .map(|(_, unit)| *unit)
+}
+
+fn is_angle_data(cx: Scope, r: &DataRef) -> bool {
+    matches!(
+        r.data_id().and_then(|id| find_data(cx, id)),
+        Some(Data {
+            data: DataData::Angle(_),
+            ..
+        })
+    )
+}
+
+/// The step or data a name in an expression points into, e.g. `(Step, 3)` for `step[3].length`
+fn expr_name_target(name: &str) -> Option<(DataRefPathEl, usize)> {
+    let (kind, rest) = match (name.strip_prefix("step["), name.strip_prefix("data[")) {
+        (Some(rest), _) => (DataRefPathEl::Step, rest),
+        (_, Some(rest)) => (DataRefPathEl::Data, rest),
+        _ => return None,
+    };
+    let id = rest.split_once(']')?.0.parse::<usize>().ok()?;
+    Some((kind, id))
+}
+
+/// The ref a name in an expression stands for, going by `DataRef::desc`, if it resolves
+fn expr_ref(cx: Scope, name: &str) -> Option<DataRef> {
+    let with_prop = |r: &DataRef, prop: &'static str| {
+        let mut path = r.0.clone();
+        path.push(DataRefPathEl::PropName(prop));
+        DataRef(path)
+    };
+    let coords = |r: DataRef| [with_prop(&r, "x"), with_prop(&r, "y")];
+
+    let candidates: Vec<DataRef> = match expr_name_target(name)? {
+        (DataRefPathEl::Step, step_id) => {
+            let step = use_context::<RwSignal<Vec<Step>>>(cx)
+                .unwrap()
+                .with(|steps| steps.iter().find(|s| s.id == step_id).copied())?;
+            step.snap_points(cx)
+                .into_iter()
+                .flat_map(coords)
+                .chain(step.data.number_props().iter().map(|&p| step.prop_ref(p)))
+                .collect()
+        }
+        (_, data_id) => {
+            let data = find_data(cx, data_id)?;
+            let r = DataRef(vec![DataRefPathEl::Data, DataRefPathEl::WithId(data_id)]);
+            match data.data {
+                DataData::Number(_)
+                | DataData::Slider { .. }
+                | DataData::Angle(_)
+                | DataData::Bool(_) => vec![r],
+                DataData::Point(_) => coords(r).to_vec(),
+                DataData::PointList(points) => (0..points.with(|p| p.len()))
+                    .flat_map(|i| {
+                        let mut path = r.0.clone();
+                        path.push(DataRefPathEl::Index(i));
+                        coords(DataRef(path))
+                    })
+                    .collect(),
+                DataData::Vector { .. } => {
+                    ["dx", "dy", "length"].map(|p| with_prop(&r, p)).to_vec()
+                }
+                DataData::Color(_) | DataData::Transform { .. } => Vec::new(),
+            }
+        }
+    };
+    candidates.into_iter().find(|r| r.desc() == name)
+}
+
+thread_local! {
+    /// The expressions `expr_value` is in the middle of, innermost last
+    static EVALUATING: std::cell::RefCell<Vec<String>> = std::cell::RefCell::new(Vec::new());
+}
+
+/// The value of a number field's expression, with angles in the document's unit
+pub fn expr_value(cx: Scope, src: &str) -> Result<f64, String> {
+    let expr = expr::parse(src)?;
+    if EVALUATING.with(|e| e.borrow().iter().any(|s| s == src)) {
+        return Err("The expression depends on its own value".to_string());
+    }
+    EVALUATING.with(|e| e.borrow_mut().push(src.to_string()));
+    let value = expr.eval(&|name: &str| expr_name_value(cx, name), angle_unit(cx));
+    EVALUATING.with(|e| e.borrow_mut().pop());
+    value
+}
+
+/// What a name in an expression comes to, with angles in the document's unit
+fn expr_name_value(cx: Scope, name: &str) -> Option<f64> {
+    let r = expr_ref(cx, name)?;
+    let value = ResolveToNumber::resolve(&r, cx);
+    Some(match ref_angle_unit(cx, &r) {
+        Some(stored) => angle_unit(cx).convert(value, stored),
+        None => value,
+    })
+}
+
+/// The steps and data an expression names, as whole-step and whole-data refs
+pub fn expr_mentions(src: &str) -> Vec<DataRef> {
+    let Ok(expr) = expr::parse(src) else {
+        return Vec::new();
+    };
+    expr.vars()
+        .iter()
+        .filter_map(|name| expr_name_target(name))
+        .map(|(kind, id)| DataRef(vec![kind, DataRefPathEl::WithId(id)]))
+        .collect()
+}
+
+/// The first name in `src` whose step or data leads back to `slot`'s owner
+fn expr_cycle(cx: Scope, slot: RwSignal<ResolvableTo<NumberSignal>>, src: &str) -> Option<String> {
+    let names = expr::parse(src).map(|e| e.vars()).unwrap_or_default();
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+    use_context::<RwSignal<Vec<Step>>>(cx)
+        .unwrap()
+        .with(|steps| {
+            datas.with(|datas| {
+                names.into_iter().find_map(|name| {
+                    let (kind, id) = expr_name_target(&name)?;
+                    let mention = DataRef(vec![kind, DataRefPathEl::WithId(id)]);
+                    (!binding_allowed(steps, datas, &InferTarget::Number(slot), &mention))
+                        .then(|| format!("{} refers back to this field", name))
+                })
+            })
+        })
+}
+
+/// `src` with each name `replace` gives text for swapped for that text
+fn replaced_expr_names(src: &str, replace: impl Fn(&str) -> Option<String>) -> String {
+    // Tokenized like the parser does, so a number's digits and a name's aren't confused
+    let is_name_start = |c: char| c.is_alphabetic() || c == '_';
+    let is_name = |c: char| c.is_alphanumeric() || matches!(c, '_' | '.' | '[' | ']');
+    let is_number = |c: char| c.is_ascii_digit() || c == '.';
+
+    let mut replaced = String::new();
+    let mut rest = src;
+    while let Some(c) = rest.chars().next() {
+        let end = |part: fn(char) -> bool| rest.find(|c: char| !part(c)).unwrap_or(rest.len());
+        let len = if is_name_start(c) {
+            end(is_name)
+        } else if is_number(c) {
+            end(is_number)
+        } else {
+            c.len_utf8()
+        };
+        let (token, after) = rest.split_at(len);
+        match is_name_start(c).then(|| replace(token)).flatten() {
+            Some(text) => replaced.push_str(&text),
+            None => replaced.push_str(token),
+        }
+        rest = after;
+    }
+    replaced
+}
+
+/// Bakes the current value into every expression naming `kind` `id` (a step or data)
+fn bake_expr_names(cx: Scope, kind: DataRefPathEl, id: usize) {
+    let slots: Vec<InferTarget> = use_context::<RwSignal<Vec<Step>>>(cx)
+        .unwrap()
+        .with(|steps| {
+            steps
+                .iter()
+                .flat_map(|s| s.infer_targets())
+                .collect::<Vec<_>>()
+        })
+        .into_iter()
+        .chain(
+            use_context::<RwSignal<Vec<Data>>>(cx)
+                .unwrap()
+                .with(|datas| {
+                    datas
+                        .iter()
+                        .flat_map(|d| d.infer_targets())
+                        .collect::<Vec<_>>()
+                }),
+        )
+        .collect();
+    for slot in slots {
+        let InferTarget::Number(n) = slot else {
+            continue;
+        };
+        let ResolvableTo::Expr(src) = n.get() else {
+            continue;
+        };
+        let baked = replaced_expr_names(&src, |name| {
+            if expr_name_target(name) != Some((kind, id)) {
+                return None;
+            }
+            match expr_name_value(cx, name)? {
+                v if !v.is_finite() => None,
+                v if v < 0.0 => Some(format!("({})", v)),
+                v => Some(v.to_string()),
+            }
+        });
+        if baked != src {
+            n.set(ResolvableTo::Expr(baked));
+        }
+    }
+}
+
+/// `src` with the steps it names remapped through `id_map`, like `DataRef::remapped`
+fn remapped_expr(src: &str, id_map: &HashMap<usize, usize>) -> String {
+    replaced_expr_names(src, |name| {
+        let (DataRefPathEl::Step, id) = expr_name_target(name)? else {
+            return None;
+        };
+        let (_, rest) = name.split_once(']')?;
+        Some(format!("step[{}]{}", id_map.get(&id)?, rest))
+    })
+}
+
+/// The step with id `step_id`, if there is one
+fn step_by_id(cx: Scope, step_id: usize) -> Option<Step> {
+    use_context::<RwSignal<Vec<Step>>>(cx)
+        .unwrap()
+        .with(|steps| steps.iter().find(|d| d.id == step_id).cloned())
+}
+
+/// The step with id `step_id`, for fresh ids sure to be there; otherwise use `step_by_id`
+fn find_step(cx: Scope, step_id: usize) -> Step {
+    use_context::<RwSignal<Vec<Step>>>(cx)
+        .unwrap()
+        .with(|steps| {
+            steps
+                .iter()
+                .find(|d| d.id == step_id)
+                .cloned()
+                .expect("Invalid step id")
+        })
+}
+
+impl ResolveToPoint for DataRef {
+    fn resolve(&self, cx: Scope) -> Point {
+        match self.0.as_slice() {
+            [DataRefPathEl::Step, DataRefPathEl::WithId(step_id), ..] => {
+                let Some(step) = step_by_id(cx, *step_id) else {
+                    return Point::NAN;
+                };
+                let prop_name = match self.0.get(2) {
+                    Some(DataRefPathEl::PropName(s)) => *s,
+                    None => "self",
+                    _ => return Point::NAN,
+                };
+                if let Some(point) = step.style_point(prop_name) {
+                    return point.get().resolve(cx);
+                }
+                match step.data {
+                    StepData::DrawPoint { point, .. } => match prop_name {
+                        "self" => point.get().resolve(cx),
+                        _ => Point::NAN,
+                    },
+                    StepData::ParallelOffset { .. } | StepData::TrimExtend { .. } => {
+                        let Some((start, end)) = step.line_points(cx) else {
+                            return Point::NAN;
+                        };
+                        match prop_name {
+                            "start" => start,
+                            "mid" => point_along(start, end, 0.5),
+                            "end" => end,
+                            "cross_x" | "cross_y" => {
+                                let value = match self.0.get(3) {
+                                    Some(DataRefPathEl::Param(v)) => *v,
+                                    _ => return Point::NAN,
+                                };
+                                line_crossing(start, end, prop_name == "cross_x", value)
+                                    .unwrap_or(Point::NAN)
+                            }
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::Perpendicular { line, through } => {
+                        let through = through.get().resolve(cx);
+                        let found = perpendicular_foot(cx, line.get(), through);
+                        match prop_name {
+                            "through" => through,
+                            "foot" => found.map_or(Point::NAN, |(foot, _)| foot),
+                            "cross_x" | "cross_y" => {
+                                let (Ok((_, normal)), Some(DataRefPathEl::Param(value))) =
+                                    (found, self.0.get(3))
+                                else {
+                                    return Point::NAN;
+                                };
+                                let ahead = Point {
+                                    x: through.x + normal.x,
+                                    y: through.y + normal.y,
+                                };
+                                line_crossing(through, ahead, prop_name == "cross_x", *value)
+                                    .unwrap_or(Point::NAN)
+                            }
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::Tangent {
+                        circle,
+                        from,
+                        other_side,
+                    } => {
+                        let from = from.get().resolve(cx);
+                        let touch = tangent_point(cx, circle.get(), from, other_side.get())
+                            .unwrap_or(Point::NAN);
+                        match prop_name {
+                            "from" => from,
+                            "touch" => touch,
+                            "cross_x" | "cross_y" => {
+                                let value = match self.0.get(3) {
+                                    Some(DataRefPathEl::Param(v)) => *v,
+                                    _ => return Point::NAN,
+                                };
+                                line_crossing(from, touch, prop_name == "cross_x", value)
+                                    .unwrap_or(Point::NAN)
+                            }
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::RotateCopy { center, .. } | StepData::PolarArray { center, .. } => {
+                        match prop_name {
+                            "center" => center.get().resolve(cx),
+                            "points" => copied_snap_point(cx, step.data, self),
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::TranslateCopy { .. }
+                    | StepData::TransformCopy { .. }
+                    | StepData::LinearArray { .. } => match prop_name {
+                        "points" => copied_snap_point(cx, step.data, self),
+                        _ => Point::NAN,
+                    },
+                    StepData::Mirror { source, axis } => match prop_name {
+                        "points" => {
+                            let index = match self.0.get(3) {
+                                Some(DataRefPathEl::Index(i)) => *i,
+                                _ => return Point::NAN,
+                            };
+                            mirror_parts(cx, source.get(), axis.get())
+                                .ok()
+                                .and_then(|(source, (a, b))| {
+                                    let r = source.snap_points(cx).get(index).cloned()?;
+                                    Some(reflect(ResolveToPoint::resolve(&r, cx), a, b))
+                                })
+                                .unwrap_or(Point::NAN)
+                        }
+                        _ => Point::NAN,
+                    },
+                    StepData::Projection { point, line } => {
+                        let point = point.get().resolve(cx);
+                        match prop_name {
+                            "point" => point,
+                            // Like intersections, unresolvable projections come out as NaN
+                            "self" => perpendicular_foot(cx, line.get(), point)
+                                .map(|(foot, _)| foot)
+                                .unwrap_or(Point::NAN),
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::PointAlongLine { line, t } => match prop_name {
+                        // Like intersections, unresolvable points come out as NaN
+                        "self" => {
+                            point_on_line(cx, line.get(), t.get().resolve(cx)).unwrap_or(Point::NAN)
+                        }
+                        _ => Point::NAN,
+                    },
+                    StepData::Intersection { line_a, line_b } => match prop_name {
+                        // Unresolvable intersections come out as NaN, which draws nothing
+                        "self" => {
+                            intersection_point(cx, line_a.get(), line_b.get()).unwrap_or(Point::NAN)
+                        }
+                        _ => Point::NAN,
+                    },
+                    StepData::Angle { a, vertex, b } => match prop_name {
+                        "a" => a.get().resolve(cx),
+                        "vertex" => vertex.get().resolve(cx),
+                        "b" => b.get().resolve(cx),
+                        _ => Point::NAN,
+                    },
+                    StepData::Distance { a, b } => match prop_name {
+                        "a" => a.get().resolve(cx),
+                        "b" => b.get().resolve(cx),
+                        _ => Point::NAN,
+                    },
+                    StepData::Centroid { points } => {
+                        let points: Vec<Point> = points
+                            .get()
+                            .into_iter()
+                            .map(|p| p.get().resolve(cx))
+                            .collect();
+                        match prop_name {
+                            "self" => centroid(&points),
+                            "points" => {
+                                let index = match self.0.get(3) {
+                                    Some(DataRefPathEl::Index(i)) => *i,
+                                    _ => return Point::NAN,
+                                };
+                                points.get(index).copied().unwrap_or(Point::NAN)
+                            }
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::Midpoint { a, b } => {
+                        let (a, b) = (a.get().resolve(cx), b.get().resolve(cx));
+                        match prop_name {
+                            "self" => Point {
+                                x: (a.x + b.x) / 2.0,
+                                y: (a.y + b.y) / 2.0,
+                            },
+                            "a" => a,
+                            "b" => b,
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::DrawRay {
+                        origin: a,
+                        through: b,
+                    }
+                    | StepData::DrawInfiniteLine { a, b } => {
+                        let names = match step.data {
+                            StepData::DrawRay { .. } => ["origin", "through"],
+                            _ => ["a", "b"],
+                        };
+                        let (a, b) = (a.get().resolve(cx), b.get().resolve(cx));
+
+                        match prop_name {
+                            _ if prop_name == names[0] => a,
+                            _ if prop_name == names[1] => b,
+                            "cross_x" | "cross_y" => {
+                                let value = match self.0.get(3) {
+                                    Some(DataRefPathEl::Param(v)) => *v,
+                                    _ => return Point::NAN,
+                                };
+                                line_crossing(a, b, prop_name == "cross_x", value)
+                                    .unwrap_or(Point::NAN)
+                            }
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::DrawLine { start, end } | StepData::DrawArrow { start, end, .. } => {
+                        let start = start.get().resolve(cx);
+                        let end = end.get().resolve(cx);
+
+                        match prop_name {
+                            "start" => start,
+                            "mid" => point_along(start, end, 0.5),
+                            "end" => end,
+                            "cross_x" | "cross_y" => {
+                                let value = match self.0.get(3) {
+                                    Some(DataRefPathEl::Param(v)) => *v,
+                                    _ => return Point::NAN,
+                                };
+                                line_crossing(start, end, prop_name == "cross_x", value)
+                                    .unwrap_or(Point::NAN)
+                            }
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::Freehand { points, .. } => {
+                        let points = points.get();
+                        match prop_name {
+                            "start" => points.first().copied().unwrap_or(Point::NAN),
+                            "end" => points.last().copied().unwrap_or(Point::NAN),
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::DrawImage { position, .. } => match prop_name {
+                        "position" => position.get().resolve(cx),
+                        _ => Point::NAN,
+                    },
+                    StepData::Axes {
+                        origin,
+                        x_min,
+                        x_max,
+                        y_min,
+                        y_max,
+                        ..
+                    } => {
+                        let origin = origin.get().resolve(cx);
+                        let along_x = |x: RwSignal<ResolvableTo<NumberSignal>>| Point {
+                            x: origin.x + x.get().resolve(cx),
+                            y: origin.y,
+                        };
+                        let along_y = |y: RwSignal<ResolvableTo<NumberSignal>>| Point {
+                            x: origin.x,
+                            y: origin.y - y.get().resolve(cx),
+                        };
+                        match prop_name {
+                            "origin" => origin,
+                            "x_start" => along_x(x_min),
+                            "x_end" => along_x(x_max),
+                            "y_start" => along_y(y_min),
+                            "y_end" => along_y(y_max),
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::ScaleBar { origin, length, .. } => {
+                        let origin = origin.get().resolve(cx);
+                        match prop_name {
+                            "origin" => origin,
+                            "end" => Point {
+                                x: origin.x + length.get().resolve(cx),
+                                y: origin.y,
+                            },
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::Callout { anchor, tip, .. } => match prop_name {
+                        "anchor" => anchor.get().resolve(cx),
+                        "tip" => tip.get().resolve(cx),
+                        _ => Point::NAN,
+                    },
+                    StepData::DrawText { anchor, .. } => match prop_name {
+                        "anchor" => anchor.get().resolve(cx),
+                        _ => Point::NAN,
+                    },
+                    StepData::DrawPath { points } | StepData::Spline { points } => {
+                        let points = points.get();
+                        match prop_name {
+                            "points" => {
+                                let index = match self.0.get(3) {
+                                    Some(DataRefPathEl::Index(i)) => *i,
+                                    _ => return Point::NAN,
+                                };
+                                points.get(index).map(|p| p.get().resolve(cx))
+                            }
+                            "start" => points.first().map(|p| p.get().resolve(cx)),
+                            "end" => points.last().map(|p| p.get().resolve(cx)),
+                            _ => None,
+                        }
+                        .unwrap_or(Point::NAN)
+                    }
+                    StepData::PointGrid {
+                        origin,
+                        col_spacing,
+                        row_spacing,
+                        ..
+                    } => match prop_name {
+                        "origin" => origin.get().resolve(cx),
+                        "points" => {
+                            let (row, col) = match (self.0.get(3), self.0.get(4)) {
+                                (
+                                    Some(DataRefPathEl::Index(row)),
+                                    Some(DataRefPathEl::Index(col)),
+                                ) => (*row, *col),
+                                _ => return Point::NAN,
+                            };
+                            grid_point(
+                                origin.get().resolve(cx),
+                                col_spacing.get().resolve(cx),
+                                row_spacing.get().resolve(cx),
+                                row,
+                                col,
+                            )
+                        }
+                        _ => Point::NAN,
+                    },
+                    StepData::Annulus { center, .. } => match prop_name {
+                        "center" => center.get().resolve(cx),
+                        _ => Point::NAN,
+                    },
+                    StepData::DrawPolygon {
+                        center,
+                        radius,
+                        sides,
+                    } => {
+                        let center = center.get().resolve(cx);
+                        match prop_name {
+                            "center" => center,
+                            "vertices" => {
+                                let index = match self.0.get(3) {
+                                    Some(DataRefPathEl::Index(i)) => *i,
+                                    _ => return Point::NAN,
+                                };
+                                polygon_vertices(
+                                    center,
+                                    radius.get().resolve(cx),
+                                    polygon_sides(sides.get().resolve(cx)),
+                                )
+                                .get(index)
+                                .copied()
+                                .unwrap_or(Point::NAN)
+                            }
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::DrawRect {
+                        origin,
+                        width,
+                        height,
+                    }
+                    | StepData::DrawRoundedRect {
+                        origin,
+                        width,
+                        height,
+                        ..
+                    } => {
+                        let origin = origin.get().resolve(cx);
+                        let (w, h) = (width.get().resolve(cx), height.get().resolve(cx));
+                        rect_point(origin, w, h, prop_name)
+                    }
+                    // Like intersections, a box around nothing comes out as NaN
+                    StepData::BoundingBox { sources } => match bounding_box(cx, sources) {
+                        Ok((min, max)) => rect_point(min, max.x - min.x, max.y - min.y, prop_name),
+                        Err(_) => Point::NAN,
+                    },
+                    StepData::DrawArc {
+                        center,
+                        radius,
+                        start_angle,
+                        end_angle,
+                    }
+                    | StepData::DrawSector {
+                        center,
+                        radius,
+                        start_angle,
+                        end_angle,
+                    } => {
+                        let center = center.get().resolve(cx);
+                        let radius = radius.get().resolve(cx);
+                        let (start_angle, end_angle) = (
+                            start_angle.get().resolve_in(cx, AngleUnit::Radians),
+                            end_angle.get().resolve_in(cx, AngleUnit::Radians),
+                        );
+
+                        match prop_name {
+                            "center" => center,
+                            "start" => arc_point(center, radius, start_angle),
+                            "mid" => arc_point(center, radius, (start_angle + end_angle) / 2.0),
+                            "end" => arc_point(center, radius, end_angle),
+                            _ => Point::NAN,
+                        }
+                    }
+                    StepData::Clip { .. } => Point::NAN,
+                    StepData::ShapeBoolean { .. } => Point::NAN,
+                    StepData::FunctionPlot { .. }
+                    | StepData::ParametricCurve { .. }
+                    | StepData::OffsetCurve { .. } => {
+                        let runs: Vec<Vec<Point>> = match step.data {
+                            StepData::OffsetCurve {
+                                source,
+                                distance,
+                                join,
+                            } => offset_curve(
+                                cx,
+                                source.get(),
+                                distance.get().resolve(cx),
+                                join.get(),
+                            )
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|(points, _)| points)
+                            .collect(),
+                            _ => step.data.curve_runs(cx).unwrap_or_default(),
+                        };
+                        match prop_name {
+                            "start" => runs.first().and_then(|r| r.first()).copied(),
+                            "end" => runs.last().and_then(|r| r.last()).copied(),
+                            _ => None,
+                        }
+                        .unwrap_or(Point::NAN)
+                    }
+                }
+            }
+            [DataRefPathEl::Data, ..] => match self.data_id().and_then(|id| find_data(cx, id)) {
+                Some(Data {
+                    data: DataData::Point(p),
+                    ..
+                }) => p.get().resolve(cx),
+                Some(Data {
+                    data: DataData::PointList(points),
+                    ..
+                }) => {
+                    let entry = match self.0.get(2) {
+                        Some(DataRefPathEl::Index(i)) => points.with(|p| p.get(*i).copied()),
+                        _ => None,
+                    };
+                    entry.map_or(Point::NAN, |p| ResolvableTo::T(p).resolve(cx))
+                }
+                _ => Point::NAN,
+            },
+            _ => Point::NAN,
+        }
+    }
+}
+
+impl Step {
+    /// Suppressed by the user, or sitting in the trash
+    pub fn is_suppressed(&self) -> bool {
+        self.suppressed.get() || self.trashed.get()
+    }
+
+    /// The gradient anchor behind a `fill_*` prop, if the step's fill has one
+    pub fn style_point(&self, prop_name: &str) -> Option<RwSignal<ResolvableTo<PointSignal>>> {
+        match (prop_name, self.style.with(|s| s.fill.clone())) {
+            ("fill_start", Fill::LinearGradient { start, .. }) => Some(start),
+            ("fill_end", Fill::LinearGradient { end, .. }) => Some(end),
+            ("fill_center", Fill::RadialGradient { center, .. }) => Some(center),
+            _ => None,
+        }
+    }
+
+    pub fn on_artboard(&self, artboard_id: usize) -> bool {
+        self.artboard.with(|a| a.map_or(true, |a| a == artboard_id))
+    }
+
+    pub fn prop_ref(&self, prop_name: &'static str) -> DataRef {
+        DataRef(vec![
+            DataRefPathEl::Step,
+            DataRefPathEl::WithId(self.id),
+            DataRefPathEl::PropName(prop_name),
+        ])
+    }
+
+    /// A ref to the `index`th element of the list prop `prop_name`
+    pub fn indexed_prop_ref(&self, prop_name: &'static str, index: usize) -> DataRef {
+        let mut r = self.prop_ref(prop_name);
+        r.0.push(DataRefPathEl::Index(index));
+        r
+    }
+
+    /// A ref to the element at (`row`, `col`) of the grid prop `prop_name`
+    pub fn grid_prop_ref(&self, prop_name: &'static str, row: usize, col: usize) -> DataRef {
+        let mut r = self.indexed_prop_ref(prop_name, row);
+        r.0.push(DataRefPathEl::Index(col));
+        r
+    }
+
+    pub fn snap_points(&self, cx: Scope) -> Vec<DataRef> {
+        match self.data {
+            StepData::Annulus { .. } => vec![self.prop_ref("center")],
+            StepData::PointGrid { rows, cols, .. } => {
+                let (rows, cols) = grid_size(cx, rows, cols).unwrap_or((0, 0));
+                (0..rows)
+                    .flat_map(|row| (0..cols).map(move |col| (row, col)))
+                    .map(|(row, col)| self.grid_prop_ref("points", row, col))
+                    .collect()
+            }
+            StepData::DrawPolygon { sides, .. } => {
+                let mut points = vec![self.prop_ref("center")];
+                points.extend(
+                    (0..polygon_sides(sides.get().resolve(cx)))
+                        .map(|i| self.indexed_prop_ref("vertices", i)),
+                );
+                points
+            }
+            StepData::DrawImage { .. } => vec![self.prop_ref("position")],
+            StepData::DrawText { .. } => vec![self.prop_ref("anchor")],
+            StepData::Callout { .. } => vec![self.prop_ref("anchor"), self.prop_ref("tip")],
+            StepData::ScaleBar { .. } => vec![self.prop_ref("origin"), self.prop_ref("end")],
+            StepData::Axes { .. } => ["origin", "x_start", "x_end", "y_start", "y_end"]
+                .into_iter()
+                .map(|prop| self.prop_ref(prop))
+                .collect(),
+            StepData::DrawPath { points } | StepData::Spline { points } => (0..points
+                .with(|p| p.len()))
+                .map(|i| self.indexed_prop_ref("points", i))
+                .collect(),
+            StepData::DrawPoint { .. }
+            | StepData::Midpoint { .. }
+            | StepData::Centroid { .. }
+            | StepData::PointAlongLine { .. }
+            | StepData::Projection { .. }
+            | StepData::Intersection { .. } => {
+                vec![self.prop_ref("self")]
+            }
+            StepData::DrawRay { .. } => vec![self.prop_ref("origin"), self.prop_ref("through")],
+            StepData::DrawInfiniteLine { .. } => vec![self.prop_ref("a"), self.prop_ref("b")],
+            StepData::Perpendicular { .. } => {
+                vec![self.prop_ref("through"), self.prop_ref("foot")]
+            }
+            StepData::Tangent { .. } => vec![self.prop_ref("from"), self.prop_ref("touch")],
+            StepData::ParallelOffset { .. } | StepData::TrimExtend { .. } => vec![
+                self.prop_ref("start"),
+                self.prop_ref("mid"),
+                self.prop_ref("end"),
+            ],
+            // Where the source's own snap points end up in each copy
+            StepData::RotateCopy { .. }
+            | StepData::TranslateCopy { .. }
+            | StepData::TransformCopy { .. }
+            | StepData::LinearArray { .. }
+            | StepData::PolarArray { .. } => {
+                let mut points = match self.data {
+                    StepData::RotateCopy { .. } | StepData::PolarArray { .. } => {
+                        vec![self.prop_ref("center")]
+                    }
+                    _ => Vec::new(),
+                };
+                if let Ok((source, transforms)) = copy_parts(cx, self.data) {
+                    points.extend(
+                        (0..source.snap_points(cx).len() * transforms.len())
+                            .map(|i| self.indexed_prop_ref("points", i)),
+                    );
+                }
+                points
+            }
+            // The reflections of the source's own snap points
+            StepData::Mirror { source, axis } => match mirror_parts(cx, source.get(), axis.get()) {
+                Ok((source, _)) => (0..source.snap_points(cx).len())
+                    .map(|i| self.indexed_prop_ref("points", i))
+                    .collect(),
+                Err(_) => Vec::new(),
+            },
+            StepData::DrawLine { .. } | StepData::DrawArrow { .. } => vec![
+                self.prop_ref("start"),
+                self.prop_ref("mid"),
+                self.prop_ref("end"),
+            ],
+            StepData::DrawRect { .. }
+            | StepData::DrawRoundedRect { .. }
+            | StepData::BoundingBox { .. } => RECT_POINT_PROPS
+                .iter()
+                .map(|prop_name| self.prop_ref(prop_name))
+                .collect(),
+            StepData::DrawArc { .. } => vec![
+                self.prop_ref("start"),
+                self.prop_ref("mid"),
+                self.prop_ref("end"),
+            ],
+            StepData::DrawSector { .. } => vec![
+                self.prop_ref("center"),
+                self.prop_ref("start"),
+                self.prop_ref("mid"),
+                self.prop_ref("end"),
+            ],
+            StepData::Freehand { .. }
+            | StepData::FunctionPlot { .. }
+            | StepData::ParametricCurve { .. }
+            | StepData::OffsetCurve { .. } => {
+                vec![self.prop_ref("start"), self.prop_ref("end")]
+            }
+            StepData::Clip { .. }
+            | StepData::ShapeBoolean { .. }
+            | StepData::Distance { .. }
+            | StepData::Angle { .. } => Vec::new(),
+        }
+    }
+
+    /// Every resolvable slot in this step, including the coordinates of literal points.
+    pub fn infer_targets(&self) -> Vec<InferTarget> {
+        fn point_targets(point: RwSignal<ResolvableTo<PointSignal>>) -> Vec<InferTarget> {
+            let mut targets = vec![InferTarget::Point(point)];
+            if let ResolvableTo::T(p) = point.get() {
+                targets.push(InferTarget::Number(p.x));
+                targets.push(InferTarget::Number(p.y));
+            }
+            targets
+        }
+
+        let mut targets = match self.data {
+            StepData::DrawPoint { point, size, .. } => {
+                let mut targets = point_targets(point);
+                targets.push(InferTarget::Number(size));
+                targets
+            }
+            StepData::DrawLine { start, end } => {
+                let mut targets = point_targets(start);
+                targets.extend(point_targets(end));
+                targets
+            }
+            StepData::DrawRay {
+                origin: a,
+                through: b,
+            }
+            | StepData::DrawInfiniteLine { a, b }
+            | StepData::Midpoint { a, b }
+            | StepData::Distance { a, b } => {
+                let mut targets = point_targets(a);
+                targets.extend(point_targets(b));
+                targets
+            }
+            StepData::Angle { a, vertex, b } => {
+                let mut targets = point_targets(a);
+                targets.extend(point_targets(vertex));
+                targets.extend(point_targets(b));
+                targets
+            }
+            StepData::DrawArrow {
+                start,
+                end,
+                head_size,
+            } => {
+                let mut targets = point_targets(start);
+                targets.extend(point_targets(end));
+                targets.push(InferTarget::Number(head_size));
+                targets
+            }
+            StepData::DrawImage {
+                position, scale, ..
+            } => {
+                let mut targets = point_targets(position);
+                targets.push(InferTarget::Number(scale));
+                targets
+            }
+            StepData::DrawText { anchor, size, .. } => {
+                let mut targets = point_targets(anchor);
+                targets.push(InferTarget::Number(size));
+                targets
+            }
+            StepData::ScaleBar {
+                origin,
+                length,
+                divisions,
+                size,
+                ..
+            } => {
+                let mut targets = point_targets(origin);
+                targets.push(InferTarget::Number(length));
+                targets.push(InferTarget::Number(divisions));
+                targets.push(InferTarget::Number(size));
+                targets
+            }
+            StepData::Axes {
+                origin,
+                x_min,
+                x_max,
+                y_min,
+                y_max,
+                spacing,
+                size,
+            } => {
+                let mut targets = point_targets(origin);
+                targets
+                    .extend([x_min, x_max, y_min, y_max, spacing, size].map(InferTarget::Number));
+                targets
+            }
+            StepData::Callout {
+                anchor, tip, size, ..
+            } => {
+                let mut targets = point_targets(anchor);
+                targets.extend(point_targets(tip));
+                targets.push(InferTarget::Number(size));
+                targets
+            }
+            StepData::DrawPath { points }
+            | StepData::Spline { points }
+            | StepData::Centroid { points } => {
+                points.get().into_iter().flat_map(point_targets).collect()
+            }
+            StepData::DrawPolygon {
+                center,
+                radius,
+                sides,
+            } => {
+                let mut targets = point_targets(center);
+                targets.push(InferTarget::Number(radius));
+                targets.push(InferTarget::Number(sides));
+                targets
+            }
+            StepData::PointGrid {
+                origin,
+                col_spacing,
+                row_spacing,
+                cols,
+                rows,
+            } => {
+                let mut targets = point_targets(origin);
+                targets.push(InferTarget::Number(col_spacing));
+                targets.push(InferTarget::Number(row_spacing));
+                targets.push(InferTarget::Number(cols));
+                targets.push(InferTarget::Number(rows));
+                targets
+            }
+            StepData::Annulus {
+                center,
+                inner_radius,
+                outer_radius,
+            } => {
+                let mut targets = point_targets(center);
+                targets.push(InferTarget::Number(inner_radius));
+                targets.push(InferTarget::Number(outer_radius));
+                targets
+            }
+            StepData::DrawRect {
+                origin,
+                width,
+                height,
+            } => {
+                let mut targets = point_targets(origin);
+                targets.push(InferTarget::Number(width));
+                targets.push(InferTarget::Number(height));
+                targets
+            }
+            StepData::DrawRoundedRect {
+                origin,
+                width,
+                height,
+                radius,
+            } => {
+                let mut targets = point_targets(origin);
+                targets.push(InferTarget::Number(width));
+                targets.push(InferTarget::Number(height));
+                targets.push(InferTarget::Number(radius));
+                targets
+            }
+            StepData::DrawArc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            }
+            | StepData::DrawSector {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } => {
+                let mut targets = point_targets(center);
+                targets.push(InferTarget::Number(radius));
+                targets.push(InferTarget::Number(start_angle));
+                targets.push(InferTarget::Number(end_angle));
+                targets
+            }
+            StepData::FunctionPlot { x_min, x_max, .. } => {
+                vec![InferTarget::Number(x_min), InferTarget::Number(x_max)]
+            }
+            StepData::ParametricCurve { t_min, t_max, .. } => {
+                vec![InferTarget::Number(t_min), InferTarget::Number(t_max)]
+            }
+            StepData::Freehand { .. }
+            | StepData::Clip { .. }
+            | StepData::ShapeBoolean { .. }
+            | StepData::BoundingBox { .. }
+            | StepData::Intersection { .. }
+            | StepData::Mirror { .. }
+            | StepData::TransformCopy { .. }
+            | StepData::TrimExtend { .. } => Vec::new(),
+            StepData::Perpendicular { through, .. } => point_targets(through),
+            StepData::Tangent { from, .. } => point_targets(from),
+            StepData::Projection { point, .. } => point_targets(point),
+            StepData::ParallelOffset { distance, .. } | StepData::OffsetCurve { distance, .. } => {
+                vec![InferTarget::Number(distance)]
+            }
+            StepData::PointAlongLine { t, .. } => vec![InferTarget::Number(t)],
+            StepData::RotateCopy { center, angle, .. } => {
+                let mut targets = point_targets(center);
+                targets.push(InferTarget::Number(angle));
+                targets
+            }
+            StepData::TranslateCopy { dx, dy, .. } => {
+                vec![InferTarget::Number(dx), InferTarget::Number(dy)]
+            }
+            StepData::LinearArray { count, dx, dy, .. } => vec![
+                InferTarget::Number(count),
+                InferTarget::Number(dx),
+                InferTarget::Number(dy),
+            ],
+            StepData::PolarArray {
+                center,
+                count,
+                sweep,
+                ..
+            } => {
+                let mut targets = point_targets(center);
+                targets.push(InferTarget::Number(count));
+                targets.push(InferTarget::Number(sweep));
+                targets
+            }
+        };
+
+        match self.style.with(|s| s.fill.clone()) {
+            Fill::LinearGradient { start, end, .. } => {
+                targets.extend(point_targets(start));
+                targets.extend(point_targets(end));
+            }
+            Fill::RadialGradient { center, radius, .. } => {
+                targets.extend(point_targets(center));
+                targets.push(InferTarget::Number(radius));
+            }
+            Fill::None | Fill::Solid(_) => {}
+        }
+
+        targets
+    }
+
+    /// A deep copy of this step with fresh signals, with refs remapped through `id_map`.
+    pub fn duplicate(&self, cx: Scope, new_id: usize, id_map: &HashMap<usize, usize>) -> Step {
+        let number = |n: RwSignal<ResolvableTo<NumberSignal>>| {
+            create_rw_signal(
+                cx,
+                match n.get() {
+                    ResolvableTo::T(t) => ResolvableTo::T(create_rw_signal(cx, t.get())),
+                    ResolvableTo::Ref(r) => ResolvableTo::Ref(r.remapped(id_map)),
+                    ResolvableTo::Expr(src) => ResolvableTo::Expr(remapped_expr(&src, id_map)),
+                },
+            )
+        };
+        let point = |p: RwSignal<ResolvableTo<PointSignal>>| {
+            create_rw_signal(
+                cx,
+                match p.get() {
+                    ResolvableTo::T(t) => ResolvableTo::T(PointSignal {
+                        x: number(t.x),
+                        y: number(t.y),
+                    }),
+                    ResolvableTo::Ref(r) => ResolvableTo::Ref(r.remapped(id_map)),
+                    ResolvableTo::Expr(src) => ResolvableTo::Expr(remapped_expr(&src, id_map)),
+                },
+            )
+        };
+
+        Step {
+            id: new_id,
+            data: match self.data {
+                StepData::DrawPoint {
+                    point: p,
+                    marker,
+                    size,
+                } => StepData::DrawPoint {
+                    point: point(p),
+                    marker: create_rw_signal(cx, marker.get()),
+                    size: number(size),
+                },
+                StepData::DrawLine { start, end } => StepData::DrawLine {
+                    start: point(start),
+                    end: point(end),
+                },
+                StepData::DrawRay { origin, through } => StepData::DrawRay {
+                    origin: point(origin),
+                    through: point(through),
+                },
+                StepData::Midpoint { a, b } => StepData::Midpoint {
+                    a: point(a),
+                    b: point(b),
+                },
+                StepData::Distance { a, b } => StepData::Distance {
+                    a: point(a),
+                    b: point(b),
+                },
+                StepData::Angle { a, vertex, b } => StepData::Angle {
+                    a: point(a),
+                    vertex: point(vertex),
+                    b: point(b),
+                },
+                StepData::DrawInfiniteLine { a, b } => StepData::DrawInfiniteLine {
+                    a: point(a),
+                    b: point(b),
+                },
+                StepData::DrawArrow {
+                    start,
+                    end,
+                    head_size,
+                } => StepData::DrawArrow {
+                    start: point(start),
+                    end: point(end),
+                    head_size: number(head_size),
+                },
+                StepData::DrawImage {
+                    src,
+                    position,
+                    scale,
+                } => StepData::DrawImage {
+                    src: create_rw_signal(cx, src.get()),
+                    position: point(position),
+                    scale: number(scale),
+                },
+                StepData::DrawText {
+                    anchor,
+                    content,
+                    size,
+                    path,
+                } => StepData::DrawText {
+                    anchor: point(anchor),
+                    content: create_rw_signal(cx, content.get()),
+                    size: number(size),
+                    path: create_rw_signal(cx, path.get().map(|r| r.remapped(id_map))),
+                },
+                StepData::Callout {
+                    anchor,
+                    tip,
+                    content,
+                    size,
+                } => StepData::Callout {
+                    anchor: point(anchor),
+                    tip: point(tip),
+                    content: create_rw_signal(cx, content.get()),
+                    size: number(size),
+                },
+                StepData::ScaleBar {
+                    origin,
+                    length,
+                    divisions,
+                    unit,
+                    size,
+                } => StepData::ScaleBar {
+                    origin: point(origin),
+                    length: number(length),
+                    divisions: number(divisions),
+                    unit: create_rw_signal(cx, unit.get()),
+                    size: number(size),
+                },
+                StepData::Axes {
+                    origin,
+                    x_min,
+                    x_max,
+                    y_min,
+                    y_max,
+                    spacing,
+                    size,
+                } => StepData::Axes {
+                    origin: point(origin),
+                    x_min: number(x_min),
+                    x_max: number(x_max),
+                    y_min: number(y_min),
+                    y_max: number(y_max),
+                    spacing: number(spacing),
+                    size: number(size),
+                },
+                StepData::DrawPath { points } => StepData::DrawPath {
+                    points: create_rw_signal(cx, points.get().into_iter().map(point).collect()),
+                },
+                StepData::Spline { points } => StepData::Spline {
+                    points: create_rw_signal(cx, points.get().into_iter().map(point).collect()),
+                },
+                StepData::Centroid { points } => StepData::Centroid {
+                    points: create_rw_signal(cx, points.get().into_iter().map(point).collect()),
+                },
+                StepData::DrawPolygon {
+                    center,
+                    radius,
+                    sides,
+                } => StepData::DrawPolygon {
+                    center: point(center),
+                    radius: number(radius),
+                    sides: number(sides),
+                },
+                StepData::PointGrid {
+                    origin,
+                    col_spacing,
+                    row_spacing,
+                    cols,
+                    rows,
+                } => StepData::PointGrid {
+                    origin: point(origin),
+                    col_spacing: number(col_spacing),
+                    row_spacing: number(row_spacing),
+                    cols: number(cols),
+                    rows: number(rows),
+                },
+                StepData::Annulus {
+                    center,
+                    inner_radius,
+                    outer_radius,
+                } => StepData::Annulus {
+                    center: point(center),
+                    inner_radius: number(inner_radius),
+                    outer_radius: number(outer_radius),
+                },
+                StepData::DrawRect {
+                    origin,
+                    width,
+                    height,
+                } => StepData::DrawRect {
+                    origin: point(origin),
+                    width: number(width),
+                    height: number(height),
+                },
+                StepData::DrawRoundedRect {
+                    origin,
+                    width,
+                    height,
+                    radius,
+                } => StepData::DrawRoundedRect {
+                    origin: point(origin),
+                    width: number(width),
+                    height: number(height),
+                    radius: number(radius),
+                },
+                StepData::DrawArc {
+                    center,
+                    radius,
+                    start_angle,
+                    end_angle,
+                } => StepData::DrawArc {
+                    center: point(center),
+                    radius: number(radius),
+                    start_angle: number(start_angle),
+                    end_angle: number(end_angle),
+                },
+                StepData::DrawSector {
+                    center,
+                    radius,
+                    start_angle,
+                    end_angle,
+                } => StepData::DrawSector {
+                    center: point(center),
+                    radius: number(radius),
+                    start_angle: number(start_angle),
+                    end_angle: number(end_angle),
+                },
+                StepData::Freehand { points, smooth } => StepData::Freehand {
+                    points: create_rw_signal(cx, points.get()),
+                    smooth: create_rw_signal(cx, smooth.get()),
+                },
+                StepData::Clip { shape } => StepData::Clip {
+                    shape: create_rw_signal(cx, shape.get().map(|r| r.remapped(id_map))),
+                },
+                StepData::ShapeBoolean { op, a, b } => StepData::ShapeBoolean {
+                    op: create_rw_signal(cx, op.get()),
+                    a: create_rw_signal(cx, a.get().map(|r| r.remapped(id_map))),
+                    b: create_rw_signal(cx, b.get().map(|r| r.remapped(id_map))),
+                },
+                StepData::BoundingBox { sources } => StepData::BoundingBox {
+                    sources: create_rw_signal(
+                        cx,
+                        sources
+                            .get()
+                            .into_iter()
+                            .map(|s| create_rw_signal(cx, s.get().map(|r| r.remapped(id_map))))
+                            .collect(),
+                    ),
+                },
+                StepData::ParallelOffset { line, distance } => StepData::ParallelOffset {
+                    line: create_rw_signal(cx, line.get().map(|r| r.remapped(id_map))),
+                    distance: number(distance),
+                },
+                StepData::OffsetCurve {
+                    source,
+                    distance,
+                    join,
+                } => StepData::OffsetCurve {
+                    source: create_rw_signal(cx, source.get().map(|r| r.remapped(id_map))),
+                    distance: number(distance),
+                    join: create_rw_signal(cx, join.get()),
+                },
+                StepData::TrimExtend {
+                    line,
+                    boundary,
+                    keep_end,
+                } => StepData::TrimExtend {
+                    line: create_rw_signal(cx, line.get().map(|r| r.remapped(id_map))),
+                    boundary: create_rw_signal(cx, boundary.get().map(|r| r.remapped(id_map))),
+                    keep_end: create_rw_signal(cx, keep_end.get()),
+                },
+                StepData::Projection { point: p, line } => StepData::Projection {
+                    point: point(p),
+                    line: create_rw_signal(cx, line.get().map(|r| r.remapped(id_map))),
+                },
+                StepData::Perpendicular { line, through } => StepData::Perpendicular {
+                    line: create_rw_signal(cx, line.get().map(|r| r.remapped(id_map))),
+                    through: point(through),
+                },
+                StepData::Tangent {
+                    circle,
+                    from,
+                    other_side,
+                } => StepData::Tangent {
+                    circle: create_rw_signal(cx, circle.get().map(|r| r.remapped(id_map))),
+                    from: point(from),
+                    other_side: create_rw_signal(cx, other_side.get()),
+                },
+                StepData::RotateCopy {
+                    source,
+                    center,
+                    angle,
+                } => StepData::RotateCopy {
+                    source: create_rw_signal(cx, source.get().map(|r| r.remapped(id_map))),
+                    center: point(center),
+                    angle: number(angle),
+                },
+                StepData::TranslateCopy { source, dx, dy } => StepData::TranslateCopy {
+                    source: create_rw_signal(cx, source.get().map(|r| r.remapped(id_map))),
+                    dx: number(dx),
+                    dy: number(dy),
+                },
+                StepData::TransformCopy { source, transform } => StepData::TransformCopy {
+                    source: create_rw_signal(cx, source.get().map(|r| r.remapped(id_map))),
+                    transform: create_rw_signal(cx, transform.get()),
+                },
+                StepData::LinearArray {
+                    source,
+                    count,
+                    dx,
+                    dy,
+                } => StepData::LinearArray {
+                    source: create_rw_signal(cx, source.get().map(|r| r.remapped(id_map))),
+                    count: number(count),
+                    dx: number(dx),
+                    dy: number(dy),
+                },
+                StepData::PolarArray {
+                    source,
+                    center,
+                    count,
+                    sweep,
+                } => StepData::PolarArray {
+                    source: create_rw_signal(cx, source.get().map(|r| r.remapped(id_map))),
+                    center: point(center),
+                    count: number(count),
+                    sweep: number(sweep),
+                },
+                StepData::Mirror { source, axis } => StepData::Mirror {
+                    source: create_rw_signal(cx, source.get().map(|r| r.remapped(id_map))),
+                    axis: create_rw_signal(cx, axis.get().map(|r| r.remapped(id_map))),
+                },
+                StepData::PointAlongLine { line, t } => StepData::PointAlongLine {
+                    line: create_rw_signal(cx, line.get().map(|r| r.remapped(id_map))),
+                    t: number(t),
+                },
+                StepData::Intersection { line_a, line_b } => StepData::Intersection {
+                    line_a: create_rw_signal(cx, line_a.get().map(|r| r.remapped(id_map))),
+                    line_b: create_rw_signal(cx, line_b.get().map(|r| r.remapped(id_map))),
+                },
+                StepData::FunctionPlot { f, x_min, x_max } => StepData::FunctionPlot {
+                    f: create_rw_signal(cx, f.get()),
+                    x_min: number(x_min),
+                    x_max: number(x_max),
+                },
+                StepData::ParametricCurve {
+                    x,
+                    y,
+                    t_min,
+                    t_max,
+                    samples,
+                } => StepData::ParametricCurve {
+                    x: create_rw_signal(cx, x.get()),
+                    y: create_rw_signal(cx, y.get()),
+                    t_min: number(t_min),
+                    t_max: number(t_max),
+                    samples: create_rw_signal(cx, samples.get()),
+                },
+            },
+            artboard: create_rw_signal(cx, self.artboard.get()),
+            suppressed: create_rw_signal(cx, self.suppressed.get()),
+            suppress_with: create_rw_signal(cx, self.suppress_with.get()),
+            construction: create_rw_signal(cx, self.construction.get()),
+            trashed: create_rw_signal(cx, false),
+            style: create_rw_signal(
+                cx,
+                self.style.with(|style| StepStyle {
+                    fill: match style.fill.clone() {
+                        Fill::LinearGradient {
+                            start,
+                            end,
+                            from,
+                            to,
+                        } => Fill::LinearGradient {
+                            start: point(start),
+                            end: point(end),
+                            from,
+                            to,
+                        },
+                        Fill::RadialGradient {
+                            center,
+                            radius,
+                            from,
+                            to,
+                        } => Fill::RadialGradient {
+                            center: point(center),
+                            radius: number(radius),
+                            from,
+                            to,
+                        },
+                        Fill::Solid(color) => Fill::Solid(color.duplicate(cx)),
+                        Fill::None => Fill::None,
+                    },
+                    stroke: style.stroke.duplicate(cx),
+                    ..style.clone()
+                }),
+            ),
+            tags: create_rw_signal(cx, self.tags.get()),
+        }
+    }
+
+    /// Moves every fully literal point in this step (style anchors included) through `f`
+    pub fn map_literal_points(&self, f: &dyn Fn(Point) -> Point) {
+        for target in self.infer_targets() {
+            let InferTarget::Point(point) = target else {
+                continue;
+            };
+            let ResolvableTo::T(p) = point.get() else {
+                continue;
+            };
+            if let (ResolvableTo::T(x), ResolvableTo::T(y)) = (p.x.get(), p.y.get()) {
+                let mapped = f(Point {
+                    x: x.get(),
+                    y: y.get(),
+                });
+                x.set(mapped.x);
+                y.set(mapped.y);
+            }
+        }
+
+        if let StepData::Freehand { points, .. } = self.data {
+            points.update(|points| {
+                for p in points.iter_mut() {
+                    *p = f(*p);
+                }
+            });
+        }
+    }
+
+    pub fn translate(&self, delta: Point) {
+        self.transform(&Transform::Translate {
+            dx: delta.x,
+            dy: delta.y,
+        });
+    }
+
+    /// Moves the literal points, lengths and arc angles of this step through `transform`
+    pub fn transform(&self, transform: &Transform) {
+        self.map_literal_points(&|p| transform.apply(p));
+        self.transform_literal_sizes(transform);
+    }
+
+    /// The non-point half of `transform`: literal lengths and arc angles
+    fn transform_literal_sizes(&self, transform: &Transform) {
+        fn update_literal(n: RwSignal<ResolvableTo<NumberSignal>>, f: impl Fn(f64) -> f64) {
+            if let ResolvableTo::T(v) = n.get() {
+                v.update(|v| *v = f(*v));
+            }
+        }
+
+        match (*transform, self.data) {
+            // Lengths and arcs follow the matrix's scale and turn; a reflection reverses arcs
+            (Transform::Matrix { a, b, c, d, .. }, data) => {
+                let det = a * d - b * c;
+                self.transform_literal_sizes(&Transform::Scale {
+                    about: Point::default(),
+                    factor: det.abs().sqrt(),
+                });
+                if let StepData::DrawArc {
+                    start_angle,
+                    end_angle,
+                    ..
+                }
+                | StepData::DrawSector {
+                    start_angle,
+                    end_angle,
+                    ..
+                } = data
+                {
+                    if let (ResolvableTo::T(start), ResolvableTo::T(end)) =
+                        (start_angle.get(), end_angle.get())
+                    {
+                        let turn = |angle: f64| {
+                            let (sin, cos) = angle.sin_cos();
+                            (b * cos + d * sin).atan2(a * cos + c * sin)
+                        };
+                        let sweep = end.get() - start.get();
+                        let new_start = if det < 0.0 {
+                            turn(end.get())
+                        } else {
+                            turn(start.get())
+                        };
+                        start.set(new_start);
+                        end.set(new_start + sweep);
+                    }
+                }
+            }
+            (Transform::Scale { factor, .. }, StepData::DrawRect { width, height, .. }) => {
+                update_literal(width, |w| w * factor);
+                update_literal(height, |h| h * factor);
+            }
+            (
+                Transform::Scale { factor, .. },
+                StepData::DrawRoundedRect {
+                    width,
+                    height,
+                    radius,
+                    ..
+                },
+            ) => {
+                update_literal(width, |w| w * factor);
+                update_literal(height, |h| h * factor);
+                update_literal(radius, |r| r * factor.abs());
+            }
+            (Transform::Scale { factor, .. }, StepData::DrawArc { radius, .. })
+            | (Transform::Scale { factor, .. }, StepData::DrawSector { radius, .. })
+            | (
+                Transform::Scale { factor, .. },
+                StepData::ParallelOffset {
+                    distance: radius, ..
+                },
+            )
+            | (
+                Transform::Scale { factor, .. },
+                StepData::OffsetCurve {
+                    distance: radius, ..
+                },
+            )
+            | (Transform::Scale { factor, .. }, StepData::DrawPolygon { radius, .. })
+            | (Transform::Scale { factor, .. }, StepData::DrawText { size: radius, .. })
+            | (Transform::Scale { factor, .. }, StepData::Callout { size: radius, .. })
+            | (Transform::Scale { factor, .. }, StepData::ScaleBar { size: radius, .. })
+            | (Transform::Scale { factor, .. }, StepData::Axes { size: radius, .. }) => {
+                update_literal(radius, |r| r * factor.abs());
+            }
+            (
+                Transform::Scale { factor, .. },
+                StepData::Annulus {
+                    inner_radius,
+                    outer_radius,
+                    ..
+                },
+            ) => {
+                update_literal(inner_radius, |r| r * factor.abs());
+                update_literal(outer_radius, |r| r * factor.abs());
+            }
+            (
+                Transform::Scale { factor, .. },
+                StepData::PointGrid {
+                    col_spacing,
+                    row_spacing,
+                    ..
+                },
+            ) => {
+                update_literal(col_spacing, |s| s * factor);
+                update_literal(row_spacing, |s| s * factor);
+            }
+            (
+                Transform::Rotate { angle, .. },
+                StepData::DrawArc {
+                    start_angle,
+                    end_angle,
+                    ..
+                }
+                | StepData::DrawSector {
+                    start_angle,
+                    end_angle,
+                    ..
+                },
+            ) => {
+                update_literal(start_angle, |a| a + angle.to_radians());
+                update_literal(end_angle, |a| a + angle.to_radians());
+            }
+            _ => {}
+        }
+    }
+
+    pub fn references(&self) -> Vec<DataRef> {
+        let targets = self.infer_targets();
+        let mut refs: Vec<DataRef> = targets.iter().filter_map(|t| t.data_ref()).collect();
+        refs.extend(targets.iter().flat_map(|t| t.expr_refs()));
+
+        // Whole-step refs, which aren't infer targets
+        match self.data {
+            StepData::Clip { shape } => refs.extend(shape.get()),
+            StepData::BoundingBox { sources } => {
+                refs.extend(sources.get().into_iter().filter_map(|s| s.get()))
+            }
+            StepData::Tangent { circle, .. } => refs.extend(circle.get()),
+            StepData::DrawText { path, .. } => refs.extend(path.get()),
+            StepData::RotateCopy { source, .. }
+            | StepData::TranslateCopy { source, .. }
+            | StepData::LinearArray { source, .. }
+            | StepData::PolarArray { source, .. } => refs.extend(source.get()),
+            StepData::TransformCopy { source, transform } => {
+                refs.extend(source.get());
+                refs.extend(transform.get());
+            }
+            StepData::Perpendicular { line, .. }
+            | StepData::ParallelOffset { line, .. }
+            | StepData::OffsetCurve { source: line, .. }
+            | StepData::PointAlongLine { line, .. }
+            | StepData::Projection { line, .. } => refs.extend(line.get()),
+            StepData::Intersection {
+                line_a: a,
+                line_b: b,
+            }
+            | StepData::TrimExtend {
+                line: a,
+                boundary: b,
+                ..
+            }
+            | StepData::Mirror { source: a, axis: b }
+            | StepData::ShapeBoolean { a, b, .. } => {
+                refs.extend(a.get());
+                refs.extend(b.get());
+            }
+            _ => {}
+        }
+        refs.extend(self.style.with(|s| s.color_refs()));
+        refs.extend(self.suppress_with.get());
+
+        refs
+    }
+
+    /// Whether this step draws transformed copies of another step, so it can be exploded
+    pub fn is_copy(&self) -> bool {
+        matches!(
+            self.data,
+            StepData::Mirror { .. }
+                | StepData::RotateCopy { .. }
+                | StepData::TranslateCopy { .. }
+                | StepData::TransformCopy { .. }
+                | StepData::LinearArray { .. }
+                | StepData::PolarArray { .. }
+        )
+    }
+
+    /// Whether this step is a straight line (segment, ray or infinite)
+    pub fn is_line(&self) -> bool {
+        matches!(
+            self.data,
+            StepData::DrawLine { .. }
+                | StepData::DrawArrow { .. }
+                | StepData::DrawRay { .. }
+                | StepData::DrawInfiniteLine { .. }
+                | StepData::Perpendicular { .. }
+                | StepData::Tangent { .. }
+                | StepData::ParallelOffset { .. }
+                | StepData::TrimExtend { .. }
+        )
+    }
+
+    /// The center and radius of a step's full circle, for steps that have one
+    pub fn circle(&self, cx: Scope) -> Option<(Point, f64)> {
+        match self.data {
+            StepData::DrawArc { center, radius, .. }
+            | StepData::DrawSector { center, radius, .. } => {
+                Some((center.get().resolve(cx), radius.get().resolve(cx)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Two points the line runs through, for line steps
+    pub fn line_points(&self, cx: Scope) -> Option<(Point, Point)> {
+        match self.data {
+            StepData::DrawLine { start: a, end: b }
+            | StepData::DrawArrow {
+                start: a, end: b, ..
+            }
+            | StepData::DrawRay {
+                origin: a,
+                through: b,
+            }
+            | StepData::DrawInfiniteLine { a, b } => {
+                Some((a.get().resolve(cx), b.get().resolve(cx)))
+            }
+            StepData::Perpendicular { line, through } => {
+                let through = through.get().resolve(cx);
+                let (_, normal) = perpendicular_foot(cx, line.get(), through).ok()?;
+                Some((
+                    through,
+                    Point {
+                        x: through.x + normal.x,
+                        y: through.y + normal.y,
+                    },
+                ))
+            }
+            StepData::Tangent {
+                circle,
+                from,
+                other_side,
+            } => {
+                let from = from.get().resolve(cx);
+                let touch = tangent_point(cx, circle.get(), from, other_side.get()).ok()?;
+                Some((from, touch))
+            }
+            StepData::ParallelOffset { line, distance } => {
+                offset_line(cx, line.get(), distance.get().resolve(cx)).ok()
+            }
+            StepData::TrimExtend {
+                line,
+                boundary,
+                keep_end,
+            } => trimmed_line(cx, line.get(), boundary.get(), keep_end.get()).ok(),
+            _ => None,
+        }
+    }
+
+    /// How far a line step runs past its two `line_points`
+    pub fn line_extent(&self, cx: Scope) -> LineExtent {
+        match self.data {
+            StepData::DrawRay { .. } => LineExtent::Ray,
+            StepData::DrawInfiniteLine { .. }
+            | StepData::Perpendicular { .. }
+            | StepData::Tangent { .. } => LineExtent::Infinite,
+            StepData::ParallelOffset { line, .. } => {
+                match line
+                    .get()
+                    .and_then(|r| r.step_id())
+                    .and_then(|id| step_by_id(cx, id))
+                {
+                    Some(line) => line.line_extent(cx),
+                    None => LineExtent::Segment,
+                }
+            }
+            _ => LineExtent::Segment,
+        }
+    }
+
+    /// Why a derived step has no well-defined result right now, e.g. parallel lines
+    pub fn resolve_error(&self, cx: Scope) -> Option<String> {
+        match self.data {
+            StepData::Intersection { line_a, line_b } => {
+                intersection_point(cx, line_a.get(), line_b.get()).err()
+            }
+            StepData::PointAlongLine { line, t } => {
+                point_on_line(cx, line.get(), t.get().resolve(cx)).err()
+            }
+            StepData::Projection { point, line } => {
+                perpendicular_foot(cx, line.get(), point.get().resolve(cx)).err()
+            }
+            StepData::PointGrid { rows, cols, .. } => grid_size(cx, rows, cols).err(),
+            StepData::BoundingBox { sources } => bounding_box(cx, sources).err(),
+            StepData::ShapeBoolean { op, a, b } => {
+                boolean_shape(cx, op.get(), a.get(), b.get()).err()
+            }
+            StepData::ScaleBar { divisions, .. } => array_count(divisions.get().resolve(cx)).err(),
+            StepData::Axes { .. } => axes_ticks(cx, self.data).err(),
+            StepData::Perpendicular { line, through } => {
+                perpendicular_foot(cx, line.get(), through.get().resolve(cx)).err()
+            }
+            StepData::Tangent {
+                circle,
+                from,
+                other_side,
+            } => tangent_point(cx, circle.get(), from.get().resolve(cx), other_side.get()).err(),
+            StepData::ParallelOffset { line, distance } => {
+                offset_line(cx, line.get(), distance.get().resolve(cx)).err()
+            }
+            StepData::OffsetCurve {
+                source,
+                distance,
+                join,
+            } => offset_curve(cx, source.get(), distance.get().resolve(cx), join.get()).err(),
+            StepData::TrimExtend {
+                line,
+                boundary,
+                keep_end,
+            } => trimmed_line(cx, line.get(), boundary.get(), keep_end.get()).err(),
+            StepData::Mirror { source, axis } => mirror_parts(cx, source.get(), axis.get()).err(),
+            StepData::RotateCopy { .. }
+            | StepData::TranslateCopy { .. }
+            | StepData::TransformCopy { .. }
+            | StepData::LinearArray { .. }
+            | StepData::PolarArray { .. } => copy_parts(cx, self.data).err(),
+            _ => None,
+        }
+    }
+
+    /// The open ends of this step as (tip, the point just before it), for line end markers
+    pub fn line_ends(&self, cx: Scope) -> Option<[(Point, Point); 2]> {
+        match self.data {
+            StepData::OffsetCurve { .. } => {
+                let paths = self.paths(cx)?;
+                let (first, last) = (paths.first()?, paths.last()?);
+                if first.1 || last.1 {
+                    return None;
+                }
+                let (first, last) = (&first.0, &last.0);
+                let n = last.len();
+                Some([(first[0], first[1]), (last[n - 1], last[n - 2])])
+            }
+            StepData::DrawLine { start, end } | StepData::DrawArrow { start, end, .. } => {
+                let (start, end) = (start.get().resolve(cx), end.get().resolve(cx));
+                Some([(start, end), (end, start)])
+            }
+            // A spline leaves each end heading for the next point, just like a path
+            StepData::DrawPath { points } | StepData::Spline { points } => {
+                let points: Vec<Point> = points
+                    .get()
+                    .into_iter()
+                    .map(|p| p.get().resolve(cx))
+                    .collect();
+                let n = points.len();
+                (n >= 2).then(|| [(points[0], points[1]), (points[n - 1], points[n - 2])])
+            }
+            StepData::DrawArc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } => {
+                let center = center.get().resolve(cx);
+                let radius = radius.get().resolve(cx);
+                let (start_angle, end_angle) = (
+                    start_angle.get().resolve_in(cx, AngleUnit::Radians),
+                    end_angle.get().resolve_in(cx, AngleUnit::Radians),
+                );
+                // A point a little way along the arc from each end, to get the tangent
+                let nudge = (end_angle - start_angle) / 100.0;
+                Some([
+                    (
+                        arc_point(center, radius, start_angle),
+                        arc_point(center, radius, start_angle + nudge),
+                    ),
+                    (
+                        arc_point(center, radius, end_angle),
+                        arc_point(center, radius, end_angle - nudge),
+                    ),
+                ])
+            }
+            StepData::Freehand { points, .. } => points.with(|points| {
+                let n = points.len();
+                (n >= 2).then(|| [(points[0], points[1]), (points[n - 1], points[n - 2])])
+            }),
+            StepData::FunctionPlot { .. } | StepData::ParametricCurve { .. } => {
+                let runs = self.data.curve_runs(cx)?;
+                let (first, last) = (runs.first()?, runs.last()?);
+                let n = last.len();
+                Some([(first[0], first[1]), (last[n - 1], last[n - 2])])
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether this step draws a closed outline that other steps can use as a region
+    pub fn is_closed_shape(&self) -> bool {
+        matches!(
+            self.data,
+            StepData::Freehand { .. }
+                | StepData::DrawRect { .. }
+                | StepData::DrawRoundedRect { .. }
+                | StepData::DrawPolygon { .. }
+                | StepData::DrawSector { .. }
+                | StepData::Annulus { .. }
+                | StepData::BoundingBox { .. }
+                | StepData::ShapeBoolean { .. }
+        )
+    }
+
+    /// This step's closed outline as a single polygon, with curves flattened
+    pub fn outline(&self, cx: Scope) -> Option<Vec<Point>> {
+        let rect = |origin: Point, w: f64, h: f64| {
+            ["origin", "top_right", "bottom_right", "bottom_left"]
+                .iter()
+                .map(|prop_name| rect_point(origin, w, h, prop_name))
+                .collect::<Vec<_>>()
+        };
+        let tau = std::f64::consts::PI * 2.0;
+
+        let mut outline = match self.data {
+            StepData::DrawRect {
+                origin,
+                width,
+                height,
+            } => rect(
+                origin.get().resolve(cx),
+                width.get().resolve(cx),
+                height.get().resolve(cx),
+            ),
+            StepData::BoundingBox { sources } => {
+                let (min, max) = bounding_box(cx, sources).ok()?;
+                rect(min, max.x - min.x, max.y - min.y)
+            }
+            StepData::DrawRoundedRect {
+                origin,
+                width,
+                height,
+                radius,
+            } => {
+                let origin = origin.get().resolve(cx);
+                let (w, h) = (width.get().resolve(cx), height.get().resolve(cx));
+                let (left, right) = (origin.x.min(origin.x + w), origin.x.max(origin.x + w));
+                let (top, bottom) = (origin.y.min(origin.y + h), origin.y.max(origin.y + h));
+                let r = radius
+                    .get()
+                    .resolve(cx)
+                    .max(0.0)
+                    .min(w.abs() / 2.0)
+                    .min(h.abs() / 2.0);
+                // Corner centers clockwise from the top right, with the angle each starts at
+                [
+                    (right - r, top + r, -tau / 4.0),
+                    (right - r, bottom - r, 0.0),
+                    (left + r, bottom - r, tau / 4.0),
+                    (left + r, top + r, tau / 2.0),
+                ]
+                .into_iter()
+                .flat_map(|(x, y, start)| arc_points(Point { x, y }, r, start, start + tau / 4.0))
+                .collect()
+            }
+            StepData::DrawPolygon {
+                center,
+                radius,
+                sides,
+            } => polygon_vertices(
+                center.get().resolve(cx),
+                radius.get().resolve(cx),
+                polygon_sides(sides.get().resolve(cx)),
+            ),
+            StepData::DrawSector {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } => {
+                let center = center.get().resolve(cx);
+                let radius = radius.get().resolve(cx).abs();
+                let (start, end) = (
+                    start_angle.get().resolve_in(cx, AngleUnit::Radians),
+                    end_angle.get().resolve_in(cx, AngleUnit::Radians),
+                );
+                if (end - start).abs() >= tau {
+                    arc_points(center, radius, 0.0, tau)
+                } else {
+                    let mut points = vec![center];
+                    points.extend(arc_points(center, radius, start, end));
+                    points
+                }
+            }
+            StepData::DrawArc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } => {
+                let sweep = end_angle.get().resolve_in(cx, AngleUnit::Radians)
+                    - start_angle.get().resolve_in(cx, AngleUnit::Radians);
+                if sweep.abs() < tau - 1e-9 {
+                    return None;
+                }
+                arc_points(
+                    center.get().resolve(cx),
+                    radius.get().resolve(cx).abs(),
+                    0.0,
+                    tau,
+                )
+            }
+            StepData::Freehand { points, .. } => points.get(),
+            StepData::ShapeBoolean { op, a, b } => {
+                let mut rings = boolean_shape(cx, op.get(), a.get(), b.get()).ok()?;
+                if rings.len() != 1 {
+                    return None;
+                }
+                rings.pop().unwrap()
+            }
+            _ => return None,
+        };
+
+        // Repeated points (square corners, a closing point) make zero-length edges
+        outline.dedup_by(|p, q| p.distance(*q) < 1e-9);
+        if outline.len() > 1 && outline[0].distance(outline[outline.len() - 1]) < 1e-9 {
+            outline.pop();
+        }
+        (outline.len() >= 3).then_some(outline)
+    }
+
+    /// The polylines this step draws, each with whether it's closed
+    pub fn paths(&self, cx: Scope) -> Option<Vec<(Vec<Point>, bool)>> {
+        match self.data {
+            StepData::Freehand { points, .. } => Some(vec![(points.get(), false)]),
+            StepData::FunctionPlot { .. } | StepData::ParametricCurve { .. } => Some(
+                self.data
+                    .curve_runs(cx)?
+                    .into_iter()
+                    .map(|run| (run, false))
+                    .collect(),
+            ),
+            StepData::ShapeBoolean { op, a, b } => Some(
+                boolean_shape(cx, op.get(), a.get(), b.get())
+                    .ok()?
+                    .into_iter()
+                    .map(|ring| (ring, true))
+                    .collect(),
+            ),
+            StepData::Annulus {
+                center,
+                inner_radius,
+                outer_radius,
+            } => {
+                let center = center.get().resolve(cx);
+                let circle = |r: RwSignal<ResolvableTo<NumberSignal>>| {
+                    let mut points = arc_points(
+                        center,
+                        r.get().resolve(cx).abs(),
+                        0.0,
+                        std::f64::consts::PI * 2.0,
+                    );
+                    points.pop();
+                    (points, true)
+                };
+                Some(vec![circle(outer_radius), circle(inner_radius)])
+            }
+            StepData::OffsetCurve {
+                source,
+                distance,
+                join,
+            } => offset_curve(cx, source.get(), distance.get().resolve(cx), join.get()).ok(),
+            _ if self.is_line() => match self.line_extent(cx) {
+                LineExtent::Segment => {
+                    let (a, b) = self.line_points(cx)?;
+                    Some(vec![(vec![a, b], false)])
+                }
+                LineExtent::Ray | LineExtent::Infinite => None,
+            },
+            StepData::DrawArc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } if self.outline(cx).is_none() => Some(vec![(
+                arc_points(
+                    center.get().resolve(cx),
+                    radius.get().resolve(cx).abs(),
+                    start_angle.get().resolve_in(cx, AngleUnit::Radians),
+                    end_angle.get().resolve_in(cx, AngleUnit::Radians),
+                ),
+                false,
+            )]),
+            _ => self.outline(cx).map(|outline| vec![(outline, true)]),
+        }
+    }
+
+    /// The (top left, bottom right) corners of the box around this step's snap points
+    pub fn bounds(&self, cx: Scope) -> Option<(Point, Point)> {
+        let mut points: Vec<Point> = self
+            .snap_points(cx)
+            .iter()
+            .map(|r| ResolveToPoint::resolve(r, cx))
+            .collect();
+        let circle = match self.data {
+            StepData::Annulus {
+                center,
+                outer_radius,
+                ..
+            } => Some((center.get().resolve(cx), outer_radius.get().resolve(cx))),
+            _ => self.circle(cx),
+        };
+        if let Some((center, radius)) = circle {
+            let radius = radius.abs();
+            points.push(Point {
+                x: center.x - radius,
+                y: center.y - radius,
+            });
+            points.push(Point {
+                x: center.x + radius,
+                y: center.y + radius,
+            });
+        }
+
+        let mut points = points
+            .into_iter()
+            .filter(|p| p.x.is_finite() && p.y.is_finite());
+        let first = points.next()?;
+        Some(points.fold((first, first), |(min, max), p| {
+            (
+                Point {
+                    x: min.x.min(p.x),
+                    y: min.y.min(p.y),
+                },
+                Point {
+                    x: max.x.max(p.x),
+                    y: max.y.max(p.y),
+                },
+            )
+        }))
+    }
+
+    /// How filling or clipping to this step's closed shape decides what's inside
+    pub fn fill_rule(&self) -> web_sys::CanvasWindingRule {
+        match self.data {
+            StepData::Annulus { .. } | StepData::ShapeBoolean { .. } => {
+                web_sys::CanvasWindingRule::Evenodd
+            }
+            _ => web_sys::CanvasWindingRule::Nonzero,
+        }
+    }
+}
+
+/// The id of a suppressed step that `step` depends on, directly or not
+pub fn suppressed_source(steps: &[Step], step: &Step) -> Option<usize> {
+    fn visit(steps: &[Step], step: &Step, visited: &mut Vec<usize>) -> Option<usize> {
+        for id in step.references().iter().filter_map(|r| r.step_id()) {
+            if visited.contains(&id) {
+                continue;
+            }
+            visited.push(id);
+
+            let Some(source) = steps.iter().find(|s| s.id == id) else {
+                continue;
+            };
+            if source.is_suppressed() {
+                return Some(id);
+            }
+            if let Some(id) = visit(steps, source, visited) {
+                return Some(id);
+            }
+        }
+        None
+    }
+
+    visit(steps, step, &mut vec![step.id])
+}
+
+/// Why `step` can't be resolved, shown on its card instead of drawing it
+pub fn step_error(steps: &[Step], step: &Step) -> Option<String> {
+    let mut visited = vec![step.id];
+    let mut pending = vec![*step];
+    while let Some(current) = pending.pop() {
+        for r in current.references() {
+            // Data stands on its own, and missing data resolves to NaN
+            if r.data_id().is_some() {
+                continue;
+            }
+            let Some(id) = r.step_id() else {
+                return Some(format!("{} isn't a valid reference", r.desc()));
+            };
+            if visited.contains(&id) {
+                if depends_on(steps, id, id) {
+                    return Some(format!("step[{}] is part of a reference cycle", id));
+                }
+                continue;
+            }
+            let Some(source) = steps.iter().find(|s| s.id == id) else {
+                return Some(format!("{} refers to a missing step", r.desc()));
+            };
+            visited.push(id);
+            pending.push(*source);
+        }
+    }
+    None
+}
+
+/// Something wrong with the document, as found by `validate`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    /// The step the problem belongs to, if any, for jumping to it
+    pub step_id: Option<usize>,
+    pub message: String,
+}
+
+/// Every problem in the document's untrashed steps and data
+pub fn validate(cx: Scope, steps: &[Step], datas: &[Data]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    for (i, step) in steps.iter().enumerate() {
+        if steps[..i].iter().any(|s| s.id == step.id) {
+            problems.push(Problem {
+                step_id: Some(step.id),
+                message: format!("Duplicate step id {}", step.id),
+            });
+        }
+    }
+    for (i, data) in datas.iter().enumerate() {
+        if datas[..i].iter().any(|d| d.id == data.id) {
+            problems.push(Problem {
+                step_id: None,
+                message: format!("Duplicate data id {}", data.id),
+            });
+        }
+    }
+
+    for step in steps.iter().filter(|s| !s.trashed.get()) {
+        let mut problem = |message: String| {
+            problems.push(Problem {
+                step_id: Some(step.id),
+                message,
+            })
+        };
+
+        if let Some(error) = step_error(steps, step) {
+            problem(error);
+            continue;
+        }
+        if !is_resolvable(steps, step) {
+            continue;
+        }
+        if let Some(error) = step.resolve_error(cx) {
+            problem(error);
+            continue;
+        }
+
+        let expr_error = step.infer_targets().iter().find_map(|t| match t {
+            InferTarget::Number(n) => match n.get() {
+                ResolvableTo::Expr(src) => expr_value(cx, &src).err(),
+                _ => None,
+            },
+            _ => None,
+        });
+        if let Some(error) = expr_error {
+            problem(format!("step[{}]: {}", step.id, error));
+            continue;
+        }
+
+        let literal_not_finite = step.infer_targets().iter().any(|t| match t {
+            InferTarget::Number(n) => match n.get() {
+                ResolvableTo::T(v) => !v.get().is_finite(),
+                ResolvableTo::Ref(_) | ResolvableTo::Expr(_) => false,
+            },
+            _ => false,
+        });
+        let point_not_finite = step.snap_points(cx).iter().any(|r| {
+            let p = ResolveToPoint::resolve(r, cx);
+            !p.x.is_finite() || !p.y.is_finite()
+        });
+        if literal_not_finite || point_not_finite {
+            problem(format!(
+                "step[{}] has values which aren't finite numbers",
+                step.id
+            ));
+        }
+    }
+
+    problems
+}
+
+/// Whether `step` isn't suppressed, doesn't depend on anything that is, and has no broken refs
+pub fn is_resolvable(steps: &[Step], step: &Step) -> bool {
+    !step.is_suppressed()
+        && suppressed_source(steps, step).is_none()
+        && step_error(steps, step).is_none()
+}
+
+/// Ids of the steps and data which reference something
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Dependents {
+    pub steps: Vec<usize>,
+    pub datas: Vec<usize>,
+}
+
+impl Dependents {
+    fn of(steps: &[Step], datas: &[Data], refers: impl Fn(&DataRef) -> bool) -> Self {
+        Dependents {
+            steps: steps
+                .iter()
+                .filter(|s| s.references().iter().any(&refers))
+                .map(|s| s.id)
+                .collect(),
+            datas: datas
+                .iter()
+                .filter(|d| d.references().iter().any(&refers))
+                .map(|d| d.id)
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty() && self.datas.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len() + self.datas.len()
+    }
+}
+
+/// The steps and data which directly reference `step_id`.
+pub fn step_dependents(steps: &[Step], datas: &[Data], step_id: usize) -> Dependents {
+    let mut dependents = Dependents::of(steps, datas, |r| r.step_id() == Some(step_id));
+    dependents.steps.retain(|id| *id != step_id);
+    dependents
+}
+
+/// The steps and data which reference data `data_id`
+pub fn data_users(steps: &[Step], datas: &[Data], data_id: usize) -> Dependents {
+    let mut users = Dependents::of(steps, datas, |r| r.data_id() == Some(data_id));
+    users.datas.retain(|id| *id != data_id);
+    users
+}
+
+/// Every slot, in steps, data and watches, holding a ref which passes `filter`
+fn slots_referencing(cx: Scope, filter: impl Fn(&DataRef) -> bool) -> Vec<(InferTarget, DataRef)> {
+    let step_targets = use_context::<RwSignal<Vec<Step>>>(cx)
+        .unwrap()
+        .with(|steps| {
+            steps
+                .iter()
+                .flat_map(|s| s.infer_targets())
+                .collect::<Vec<_>>()
+        });
+    let data_targets = use_context::<RwSignal<Vec<Data>>>(cx)
+        .unwrap()
+        .with(|datas| {
+            datas
+                .iter()
+                .flat_map(|d| d.infer_targets())
+                .collect::<Vec<_>>()
+        });
+    let watch_targets = use_context::<RwSignal<Vec<Watch>>>(cx)
+        .unwrap()
+        .with(|watches| {
+            watches
+                .iter()
+                .flat_map(|w| w.infer_targets())
+                .collect::<Vec<_>>()
+        });
+    step_targets
+        .into_iter()
+        .chain(data_targets)
+        .chain(watch_targets)
+        .filter_map(|t| t.data_ref().map(|r| (t, r)))
+        .filter(|(_, r)| filter(r))
+        .collect()
+}
+
+fn slots_referencing_step(cx: Scope, step_id: usize) -> Vec<(InferTarget, DataRef)> {
+    // A step's refs into itself would be cycles, so these are all from elsewhere
+    slots_referencing(cx, |r| r.step_id() == Some(step_id))
+}
+
+/// Whether `step_id` references `target_id`, directly or through other steps
+pub fn depends_on(steps: &[Step], step_id: usize, target_id: usize) -> bool {
+    let mut visited = vec![step_id];
+    let mut pending = vec![step_id];
+    while let Some(id) = pending.pop() {
+        let Some(step) = steps.iter().find(|s| s.id == id) else {
+            continue;
+        };
+        for dep in step.references().iter().filter_map(|r| r.step_id()) {
+            if dep == target_id {
+                return true;
+            }
+            if !visited.contains(&dep) {
+                visited.push(dep);
+                pending.push(dep);
+            }
+        }
+    }
+    false
+}
+
+/// Whether resolving `r` goes through step `step_id` or data `data_id`
+fn ref_reaches(
+    steps: &[Step],
+    datas: &[Data],
+    r: &DataRef,
+    step_id: Option<usize>,
+    data_id: Option<usize>,
+) -> bool {
+    let mut visited = Vec::new();
+    let mut pending = vec![r.clone()];
+    while let Some(r) = pending.pop() {
+        if (r.step_id().is_some() && r.step_id() == step_id)
+            || (r.data_id().is_some() && r.data_id() == data_id)
+        {
+            return true;
+        }
+        let next = match (r.step_id(), r.data_id()) {
+            (Some(id), _) => steps.iter().find(|s| s.id == id).map(|s| s.references()),
+            (_, Some(id)) => datas.iter().find(|d| d.id == id).map(|d| d.references()),
+            _ => None,
+        };
+        for r in next.unwrap_or_default() {
+            let key = (r.step_id(), r.data_id());
+            if !visited.contains(&key) {
+                visited.push(key);
+                pending.push(r);
+            }
+        }
+    }
+    false
+}
+
+/// Whether pointing `slot` at `candidate` leaves the steps and data free of cycles
+pub fn binding_allowed(
+    steps: &[Step],
+    datas: &[Data],
+    slot: &InferTarget,
+    candidate: &DataRef,
+) -> bool {
+    let slot = &match *slot {
+        InferTarget::Eyedropper { target, .. } => InferTarget::Number(target),
+        slot => slot,
+    };
+    let owner_step = steps
+        .iter()
+        .find(|s| s.infer_targets().contains(slot))
+        .map(|s| s.id);
+    let owner_data = datas
+        .iter()
+        .find(|d| d.infer_targets().contains(slot))
+        .map(|d| d.id);
+    if owner_step.is_none() && owner_data.is_none() {
+        return true;
+    }
+    !ref_reaches(steps, datas, candidate, owner_step, owner_data)
+}
+
+/// A binding applied while its candidate is hovered, remembering the slot's old value
+#[derive(Clone, Debug)]
+pub enum BindingPreview {
+    Number {
+        slot: RwSignal<ResolvableTo<NumberSignal>>,
+        previous: ResolvableTo<NumberSignal>,
+        candidate: DataRef,
+    },
+    Point {
+        slot: RwSignal<ResolvableTo<PointSignal>>,
+        previous: ResolvableTo<PointSignal>,
+        candidate: DataRef,
+    },
+}
+
+/// Previews binding `target` to `candidate`, replacing any other preview
+pub fn start_binding_preview(cx: Scope, target: InferTarget, candidate: DataRef) {
+    let preview = use_context::<RwSignal<Option<BindingPreview>>>(cx).unwrap();
+    let already = preview.with(|p| match (p, target) {
+        (
+            Some(BindingPreview::Number {
+                slot, candidate: c, ..
+            }),
+            InferTarget::Number(t),
+        ) => *slot == t && *c == candidate,
+        (
+            Some(BindingPreview::Point {
+                slot, candidate: c, ..
+            }),
+            InferTarget::Point(t),
+        ) => *slot == t && *c == candidate,
+        _ => false,
+    });
+    if already {
+        return;
+    }
+
+    end_binding_preview(cx);
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+    let allowed = use_context::<RwSignal<Vec<Step>>>(cx)
+        .unwrap()
+        .with(|steps| datas.with(|datas| binding_allowed(steps, datas, &target, &candidate)));
+    if !allowed {
+        return;
+    }
+
+    match target {
+        InferTarget::Number(slot) => {
+            preview.set(Some(BindingPreview::Number {
+                slot,
+                previous: slot.get(),
+                candidate: candidate.clone(),
+            }));
+            slot.set(ResolvableTo::Ref(candidate));
+        }
+        InferTarget::Point(slot) => {
+            preview.set(Some(BindingPreview::Point {
+                slot,
+                previous: slot.get(),
+                candidate: candidate.clone(),
+            }));
+            slot.set(ResolvableTo::Ref(candidate));
+        }
+        InferTarget::Redirect(_) | InferTarget::Eyedropper { .. } => {}
+    }
+}
+
+/// Puts the previewed slot back the way it was
+pub fn end_binding_preview(cx: Scope) {
+    let preview = use_context::<RwSignal<Option<BindingPreview>>>(cx).unwrap();
+    let Some(p) = preview.get() else {
+        return;
+    };
+    preview.set(None);
+    match p {
+        BindingPreview::Number { slot, previous, .. } => slot.set(previous),
+        BindingPreview::Point { slot, previous, .. } => slot.set(previous),
+    }
+}
+
+/// Keeps the previewed binding for good
+pub fn commit_binding_preview(cx: Scope) {
+    use_context::<RwSignal<Option<BindingPreview>>>(cx)
+        .unwrap()
+        .set(None);
+}
+
+/// Bakes the current value into every reference into `step_id`, expressions included
+pub fn bake_step_refs(cx: Scope, step_id: usize) {
+    bake_expr_names(cx, DataRefPathEl::Step, step_id);
+    for (target, r) in slots_referencing_step(cx, step_id) {
+        match target {
+            InferTarget::Number(n) => {
+                let value = ResolveToNumber::resolve(&r, cx);
+                n.set(ResolvableTo::T(create_rw_signal(cx, value)));
+            }
+            InferTarget::Point(p) => {
+                let value = ResolveToPoint::resolve(&r, cx);
+                p.set(ResolvableTo::T(PointSignal::literal(cx, value)));
+            }
+            InferTarget::Redirect(_) | InferTarget::Eyedropper { .. } => unreachable!(),
+        }
+    }
+}
+
+/// Same as `bake_step_refs`, for data `data_id`
+pub fn bake_data_refs(cx: Scope, data_id: usize) {
+    bake_expr_names(cx, DataRefPathEl::Data, data_id);
+    let is_data = |c: &ResolvableTo<ColorSignal>| matches!(c, ResolvableTo::Ref(r) if r.data_id() == Some(data_id));
+    for step in use_context::<RwSignal<Vec<Step>>>(cx).unwrap().get() {
+        // The step stays suppressed or not as it is now
+        if step.suppress_with.get().and_then(|r| r.data_id()) == Some(data_id) {
+            step.suppress_with.set(None);
+        }
+        let style = step.style.get();
+        if is_data(&style.stroke) {
+            let color = style.stroke.resolve_color(cx);
+            step.style
+                .update(|s| s.stroke = ResolvableTo::literal(cx, &color));
+        }
+        if let Fill::Solid(fill) = &style.fill {
+            if is_data(fill) {
+                let color = fill.resolve_color(cx);
+                step.style
+                    .update(|s| s.fill = Fill::Solid(ResolvableTo::literal(cx, &color)));
+            }
+        }
+    }
+    for (target, r) in slots_referencing(cx, |r| r.data_id() == Some(data_id)) {
+        match target {
+            InferTarget::Number(n) => {
+                let value = ResolveToNumber::resolve(&r, cx);
+                n.set(ResolvableTo::T(create_rw_signal(cx, value)));
+            }
+            InferTarget::Point(p) => {
+                let value = ResolveToPoint::resolve(&r, cx);
+                p.set(ResolvableTo::T(PointSignal::literal(cx, value)));
+            }
+            InferTarget::Redirect(_) | InferTarget::Eyedropper { .. } => unreachable!(),
+        }
+    }
+}
+
+/// Points refs into `step_id` at `replacement` where they can follow, baking in the rest
+pub fn redirect_step_refs(cx: Scope, step_id: usize, replacement: &DataRef) {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+    for (target, r) in slots_referencing_step(cx, step_id) {
+        let redirected = match (target, r.0.last()) {
+            (InferTarget::Number(_), Some(DataRefPathEl::PropName(coord @ ("x" | "y")))) => {
+                let mut path = replacement.0.clone();
+                path.push(DataRefPathEl::PropName(*coord));
+                Some(DataRef(path))
+            }
+            (InferTarget::Point(_), _) => Some(replacement.clone()),
+            _ => None,
+        }
+        .filter(|to| {
+            steps.with(|steps| datas.with(|datas| binding_allowed(steps, datas, &target, to)))
+        });
+        match (target, redirected) {
+            (InferTarget::Number(n), Some(to)) => n.set(ResolvableTo::Ref(to)),
+            (InferTarget::Number(n), None) => {
+                let value = ResolveToNumber::resolve(&r, cx);
+                n.set(ResolvableTo::T(create_rw_signal(cx, value)));
+            }
+            (InferTarget::Point(p), Some(to)) => p.set(ResolvableTo::Ref(to)),
+            (InferTarget::Point(p), None) => {
+                let value = ResolveToPoint::resolve(&r, cx);
+                p.set(ResolvableTo::T(PointSignal::literal(cx, value)));
+            }
+            (InferTarget::Redirect(_) | InferTarget::Eyedropper { .. }, _) => unreachable!(),
+        }
+    }
+}
+
+/// The point behind a coordinate ref like `step[3].end.x`, or `r` itself
+fn referenced_point(r: &DataRef) -> DataRef {
+    match r.0.split_last() {
+        Some((DataRefPathEl::PropName("x" | "y"), point)) => DataRef(point.to_vec()),
+        _ => r.clone(),
+    }
+}
+
+/// The point refs in use anywhere in the document, sorted by description
+pub fn point_refs_in_use(cx: Scope) -> Vec<DataRef> {
+    let mut refs: Vec<DataRef> = Vec::new();
+    for (target, r) in slots_referencing(cx, |_| true) {
+        let r = match target {
+            InferTarget::Point(_) => r,
+            InferTarget::Number(_) if referenced_point(&r) != r => referenced_point(&r),
+            _ => continue,
+        };
+        if !refs.contains(&r) {
+            refs.push(r);
+        }
+    }
+    refs.sort_by_key(|r| r.desc());
+    refs
+}
+
+/// Every slot referencing the point `from` or one of its coordinates
+pub fn slots_referencing_point(cx: Scope, from: &DataRef) -> Vec<InferTarget> {
+    slots_referencing(cx, |r| referenced_point(r) == *from)
+        .into_iter()
+        .map(|(t, _)| t)
+        .collect()
+}
+
+/// Points every reference to `from` at `to` where no cycle results, returning how many
+pub fn replace_point_refs(cx: Scope, from: &DataRef, to: &DataRef) -> usize {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+    let slots: Vec<InferTarget> = slots_referencing_point(cx, from)
+        .into_iter()
+        .filter(|t| steps.with(|steps| datas.with(|datas| binding_allowed(steps, datas, t, to))))
+        .collect();
+
+    for slot in slots.iter() {
+        match slot {
+            InferTarget::Point(p) => p.set(ResolvableTo::Ref(to.clone())),
+            InferTarget::Number(n) => {
+                let ResolvableTo::Ref(r) = n.get() else {
+                    continue;
+                };
+                let Some(coord) = r.0.last() else {
+                    continue;
+                };
+                let mut path = to.0.clone();
+                path.push(*coord);
+                n.set(ResolvableTo::Ref(DataRef(path)));
+            }
+            InferTarget::Redirect(_) | InferTarget::Eyedropper { .. } => unreachable!(),
+        }
+    }
+    slots.len()
+}
+
+/// Step ids with each after its references, plus those caught in cycles in their own order
+pub fn topological_order(steps: &[Step]) -> (Vec<usize>, Vec<usize>) {
+    let deps: Vec<(usize, Vec<usize>)> = steps
+        .iter()
+        .map(|s| {
+            let deps = s
+                .references()
+                .iter()
+                .filter_map(|r| r.step_id())
+                .filter(|id| steps.iter().any(|s| s.id == *id))
+                .collect();
+            (s.id, deps)
+        })
+        .collect();
+
+    let mut ordered: Vec<usize> = Vec::new();
+    let mut remaining = deps;
+    while let Some(i) = remaining
+        .iter()
+        .position(|(_, deps)| deps.iter().all(|d| ordered.contains(d)))
+    {
+        ordered.push(remaining.remove(i).0);
+    }
+
+    (ordered, remaining.into_iter().map(|(id, _)| id).collect())
+}
+
+pub fn next_step_id(steps: &[Step]) -> usize {
+    steps.iter().map(|s| s.id + 1).max().unwrap_or(0)
+}
+
+pub fn next_data_id(datas: &[Data]) -> usize {
+    datas.iter().map(|d| d.id + 1).max().unwrap_or(0)
+}
+
+/// Where the add-step buttons put new steps
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NewStepPlacement {
+    Origin,
+    #[default]
+    ViewCenter,
+    LastPointer,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NewStepDefaults {
+    pub placement: NewStepPlacement,
+    /// Scales the lengths and offsets new steps start with
+    pub size: f64,
+    /// Go straight into picking each point of a new step on the canvas, one after another
+    pub place_by_clicking: bool,
+}
+
+impl Default for NewStepDefaults {
+    fn default() -> Self {
+        Self {
+            placement: NewStepPlacement::default(),
+            size: 1.0,
+            place_by_clicking: true,
+        }
+    }
+}
+
+/// The points of a new step still waiting to be placed, after the one being inferred
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PendingPlacement(pub Vec<RwSignal<ResolvableTo<PointSignal>>>);
+
+/// Starts inferring the next point waiting to be placed, if any
+pub fn advance_placement(cx: Scope) {
+    let pending = use_context::<RwSignal<PendingPlacement>>(cx).unwrap();
+    let next = pending.with(|p| p.0.first().copied());
+    pending.update(|p| {
+        if !p.0.is_empty() {
+            p.0.remove(0);
+        }
+    });
+    use_context::<RwSignal<Option<InferTarget>>>(cx)
+        .unwrap()
+        .set(next.map(InferTarget::Point));
+}
+
+/// The world position the pointer was last over the canvas at, if it has been yet
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LastPointer(pub Option<Point>);
+
+/// Where a new step goes by `NewStepDefaults`, rounded to the grid when snapping
+fn new_step_anchor(cx: Scope) -> Point {
+    let placement = use_context::<RwSignal<NewStepDefaults>>(cx)
+        .unwrap()
+        .with(|d| d.placement);
+    let view_center = || {
+        let (min, max) = use_context::<RwSignal<ViewTransform>>(cx)
+            .unwrap()
+            .with(|vt| vt.visible_world_rect());
+        Point {
+            x: (min.x + max.x) / 2.0,
+            y: (min.y + max.y) / 2.0,
+        }
+    };
+    let anchor = match placement {
+        NewStepPlacement::Origin => return Point::default(),
+        NewStepPlacement::ViewCenter => view_center(),
+        NewStepPlacement::LastPointer => use_context::<RwSignal<LastPointer>>(cx)
+            .unwrap()
+            .get()
+            .0
+            .unwrap_or_else(view_center),
+    };
+    use_context::<RwSignal<SnapSettings>>(cx)
+        .unwrap()
+        .with(|snap| snap.snap_to_grid(anchor))
+}
+
+/// Appends a step built around the origin, placed and scaled by `NewStepDefaults`
+pub fn push_new_step(cx: Scope, data: StepData) -> usize {
+    let size = use_context::<RwSignal<NewStepDefaults>>(cx)
+        .unwrap()
+        .with(|d| d.size);
+    let anchor = new_step_anchor(cx);
+
+    let id = push_step(cx, data);
+    let step = find_step(cx, id);
+    if size != 1.0 {
+        step.transform(&Transform::Scale {
+            about: Point::default(),
+            factor: size,
+        });
+    }
+    step.translate(anchor);
+
+    let place_by_clicking = use_context::<RwSignal<NewStepDefaults>>(cx)
+        .unwrap()
+        .with(|d| d.place_by_clicking);
+    if place_by_clicking {
+        let points = step
+            .infer_targets()
+            .into_iter()
+            .filter_map(|t| match t {
+                InferTarget::Point(p) => Some(p),
+                _ => None,
+            })
+            .collect();
+        use_context::<RwSignal<PendingPlacement>>(cx)
+            .unwrap()
+            .set(PendingPlacement(points));
+        advance_placement(cx);
+    }
+    id
+}
+
+/// A construction distance step for the compass's spread and a circle whose radius follows it
+pub fn push_compass_steps(cx: Scope, transfer: bool) {
+    let spread_id = push_new_step(
+        cx,
+        StepData::Distance {
+            a: create_rw_signal(
+                cx,
+                ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+            ),
+            b: create_rw_signal(
+                cx,
+                ResolvableTo::T(PointSignal::literal(cx, Point { x: 2.0, y: 0.0 })),
+            ),
+        },
+    );
+    let spread = find_step(cx, spread_id);
+    spread.construction.set(true);
+
+    let center = create_rw_signal(
+        cx,
+        if transfer {
+            ResolvableTo::T(PointSignal::literal(cx, new_step_anchor(cx)))
+        } else {
+            ResolvableTo::Ref(spread.prop_ref("a"))
+        },
+    );
+    push_step(
+        cx,
+        StepData::DrawArc {
+            center,
+            radius: create_rw_signal(cx, ResolvableTo::Ref(spread.prop_ref("value"))),
+            start_angle: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+            end_angle: create_rw_signal(
+                cx,
+                ResolvableTo::T(create_rw_signal(cx, std::f64::consts::TAU)),
+            ),
+        },
+    );
+
+    // The new center is placed after the spread's two points
+    let placing = use_context::<RwSignal<Option<InferTarget>>>(cx)
+        .unwrap()
+        .with(|t| t.is_some());
+    if transfer && placing {
+        use_context::<RwSignal<PendingPlacement>>(cx)
+            .unwrap()
+            .update(|p| p.0.push(center));
+    }
+}
+
+/// Appends a new step, drawn on the active artboard, and returns its id
+pub fn push_step(cx: Scope, data: StepData) -> usize {
+    let artboard = use_context::<RwSignal<DocumentSettings>>(cx)
+        .unwrap()
+        .with(|s| s.active_artboard);
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+
+    let id = steps.with(|s| next_step_id(s));
+    steps.update(|s| {
+        s.push(Step {
+            id,
+            data,
+            artboard: create_rw_signal(cx, Some(artboard)),
+            suppressed: create_rw_signal(cx, false),
+            suppress_with: create_rw_signal(cx, None),
+            construction: create_rw_signal(cx, false),
+            trashed: create_rw_signal(cx, false),
+            style: create_rw_signal(cx, StepStyle::new(cx)),
+            tags: create_rw_signal(cx, Vec::new()),
+        })
+    });
+    id
+}
+
+/// What an expression in `var` comes to at each of `at`, like "x=0 → 1, x=10 → 3.5"
+pub fn expr_preview(src: &str, var: &str, at: &[f64]) -> Result<String, String> {
+    let expr = expr::parse(src)?;
+    let round = |v: f64| (v * 1e4).round() / 1e4;
+    let values = at
+        .iter()
+        .map(|&v| {
+            let value = expr.eval(&|name| (name == var).then_some(v), AngleUnit::Radians)?;
+            Ok(format!("{}={} → {}", var, round(v), round(value)))
+        })
+        .collect::<Result<Vec<String>, String>>()?;
+    Ok(values.join(", "))
+}
+
+/// Evaluates `x(i)`, `y(i)` for `i` in `0..count`, with trig functions in radians
+pub fn point_series(x: &str, y: &str, count: usize) -> Result<Vec<Point>, String> {
+    let x = expr::parse(x).map_err(|e| format!("x: {}", e))?;
+    let y = expr::parse(y).map_err(|e| format!("y: {}", e))?;
+
+    (0..count)
+        .map(|i| {
+            let vars = |name: &str| (name == "i").then_some(i as f64);
+            Ok(Point {
+                x: x.eval(&vars, AngleUnit::Radians)
+                    .map_err(|e| format!("x: {}", e))?,
+                y: y.eval(&vars, AngleUnit::Radians)
+                    .map_err(|e| format!("y: {}", e))?,
+            })
+        })
+        .collect()
+}
+
+/// Appends copies of `step_ids`, with refs between them remapped onto the copies
+pub fn duplicate_steps(cx: Scope, step_ids: &[usize]) -> Vec<usize> {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+
+    let originals: Vec<Step> = steps.with(|steps| {
+        steps
+            .iter()
+            .filter(|s| step_ids.contains(&s.id))
+            .cloned()
+            .collect()
+    });
+
+    let first_id = steps.with(|steps| next_step_id(steps));
+    let id_map: HashMap<usize, usize> = originals
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.id, first_id + i))
+        .collect();
+
+    let copies: Vec<Step> = originals
+        .iter()
+        .map(|s| s.duplicate(cx, id_map[&s.id], &id_map))
+        .collect();
+    let copy_ids = copies.iter().map(|s| s.id).collect();
+
+    steps.update(|s| s.extend(copies));
+    copy_ids
+}
+
+/// Replaces a copy step with literal copies of its source, returning their ids
+pub fn explode_step(cx: Scope, step_id: usize) -> Result<Vec<usize>, String> {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let step = step_by_id(cx, step_id).ok_or_else(|| format!("step[{}] is gone", step_id))?;
+    let (source, transforms) = match step.data {
+        StepData::Mirror { source, axis } => {
+            let (source, (a, b)) = mirror_parts(cx, source.get(), axis.get())?;
+            (source, vec![Transform::reflection(a, b)])
+        }
+        data => copy_parts(cx, data)?,
+    };
+    // Copy steps turn their source as a whole, but literal copies of these would stay upright
+    let turns = transforms
+        .iter()
+        .any(|t| !matches!(t, Transform::Translate { .. } | Transform::Scale { .. }));
+    if turns && source.data.keeps_upright() {
+        return Err(format!(
+            "step[{}] can't be turned on its own, so its copies would come out upright",
+            source.id
+        ));
+    }
+
+    // Fails here rather than part way through the copies
+    literal_geometry(cx, &source)?;
+
+    let mut ids = Vec::new();
+    for transform in transforms {
+        let id = steps.with(|steps| next_step_id(steps));
+        let mut copy = source.duplicate(cx, id, &HashMap::new());
+        if let Some(data) = literal_geometry(cx, &source)? {
+            copy.data = data;
+        }
+        for target in copy.infer_targets() {
+            match target {
+                InferTarget::Number(n) if !matches!(n.get(), ResolvableTo::T(_)) => {
+                    let value = n.get().resolve(cx);
+                    n.set(ResolvableTo::T(create_rw_signal(cx, value)));
+                }
+                InferTarget::Point(p) if !matches!(p.get(), ResolvableTo::T(_)) => {
+                    let value = p.get().resolve(cx);
+                    p.set(ResolvableTo::T(PointSignal::literal(cx, value)));
+                }
+                _ => {}
+            }
+        }
+        copy.transform(&transform);
+        steps.update(|steps| steps.push(copy));
+        ids.push(id);
+    }
+
+    bake_step_refs(cx, step_id);
+    remove_step(cx, step_id);
+    Ok(ids)
+}
+
+/// A plain line or point standing in for a step built from whole-step refs
+fn literal_geometry(cx: Scope, step: &Step) -> Result<Option<StepData>, String> {
+    let literal = |p: Point| create_rw_signal(cx, ResolvableTo::T(PointSignal::literal(cx, p)));
+    let finite = |p: Point| p.x.is_finite() && p.y.is_finite();
+    let unresolved = || format!("step[{}] doesn't resolve, so it can't be copied", step.id);
+    match step.data {
+        StepData::Perpendicular { .. }
+        | StepData::Tangent { .. }
+        | StepData::ParallelOffset { .. }
+        | StepData::TrimExtend { .. } => {
+            let (a, b) = step
+                .line_points(cx)
+                .filter(|(a, b)| finite(*a) && finite(*b))
+                .ok_or_else(unresolved)?;
+            Ok(Some(match step.line_extent(cx) {
+                LineExtent::Segment => StepData::DrawLine {
+                    start: literal(a),
+                    end: literal(b),
+                },
+                LineExtent::Ray => StepData::DrawRay {
+                    origin: literal(a),
+                    through: literal(b),
+                },
+                LineExtent::Infinite => StepData::DrawInfiniteLine {
+                    a: literal(a),
+                    b: literal(b),
+                },
+            }))
+        }
+        StepData::Intersection { .. }
+        | StepData::Projection { .. }
+        | StepData::PointAlongLine { .. } => {
+            let r = DataRef(vec![DataRefPathEl::Step, DataRefPathEl::WithId(step.id)]);
+            let p = Some(ResolveToPoint::resolve(&r, cx))
+                .filter(|p| finite(*p))
+                .ok_or_else(unresolved)?;
+            Ok(Some(StepData::DrawPoint {
+                point: literal(p),
+                marker: create_rw_signal(cx, PointMarker::default()),
+                size: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+            }))
+        }
+        StepData::Clip { .. }
+        | StepData::BoundingBox { .. }
+        | StepData::ShapeBoolean { .. }
+        | StepData::OffsetCurve { .. }
+        | StepData::Mirror { .. }
+        | StepData::RotateCopy { .. }
+        | StepData::TranslateCopy { .. }
+        | StepData::TransformCopy { .. }
+        | StepData::LinearArray { .. }
+        | StepData::PolarArray { .. } => Err(format!(
+            "step[{}] is built from other steps, so it can't be copied literally",
+            step.id
+        )),
+        StepData::DrawText { path, .. } if path.get().is_some() => Err(format!(
+            "step[{}] runs along another step, so it can't be copied literally",
+            step.id
+        )),
+        _ => Ok(None),
+    }
+}
+
+/// How far the copies in `pairs` (original id, copy id) sit from their originals
+pub fn placement_offset(cx: Scope, pairs: &[(usize, usize)]) -> Option<Point> {
+    pairs.iter().find_map(|(original, copy)| {
+        let original =
+            ResolveToPoint::resolve(step_by_id(cx, *original)?.snap_points(cx).first()?, cx);
+        let copy = ResolveToPoint::resolve(step_by_id(cx, *copy)?.snap_points(cx).first()?, cx);
+        Some(Point {
+            x: copy.x - original.x,
+            y: copy.y - original.y,
+        })
+    })
+}
+
+/// Moves a step to the trash
+fn remove_step(cx: Scope, step_id: usize) {
+    if let Some(step) = step_by_id(cx, step_id) {
+        step.trashed.set(true);
+    }
+    use_context::<RwSignal<Selection>>(cx)
+        .unwrap()
+        .update(|sel| sel.steps.retain(|id| *id != step_id));
+}
+
+/// Destroys everything in the trash, baking in whatever still references it first
+fn empty_trash(cx: Scope) {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let trashed: Vec<usize> = steps.with(|steps| {
+        steps
+            .iter()
+            .filter(|s| s.trashed.get())
+            .map(|s| s.id)
+            .collect()
+    });
+    for id in trashed.iter() {
+        bake_step_refs(cx, *id);
+    }
+    steps.update(|s| s.retain(|s| !trashed.contains(&s.id)));
+
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+    let trashed: Vec<usize> = datas.with(|datas| {
+        datas
+            .iter()
+            .filter(|d| d.trashed.get())
+            .map(|d| d.id)
+            .collect()
+    });
+    for id in trashed.iter() {
+        bake_data_refs(cx, *id);
+    }
+    datas.update(|d| d.retain(|d| !d.trashed.get()));
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Step {
+    pub id: usize,
+    pub data: StepData,
+    /// The artboard this step is drawn on, or None to draw it on every artboard
+    pub artboard: RwSignal<Option<usize>>,
+    /// Suppressed steps aren't drawn or snapped to, and steps depending on them can't resolve
+    pub suppressed: RwSignal<bool>,
+    /// Bool data `suppressed` follows, suppressed while it's off
+    pub suppress_with: RwSignal<Option<DataRef>>,
+    /// Drawn dashed and grey and left out of printing, but otherwise like any other step
+    pub construction: RwSignal<bool>,
+    /// Deleted steps sit in the trash until it's emptied. They resolve as if suppressed.
+    pub trashed: RwSignal<bool>,
+    pub style: RwSignal<StepStyle>,
+    /// Labels for styling steps in bulk, see `DocumentSettings::tag_presets`
+    pub tags: RwSignal<Vec<String>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fill {
+    None,
+    Solid(ResolvableTo<ColorSignal>),
+    LinearGradient {
+        start: RwSignal<ResolvableTo<PointSignal>>,
+        end: RwSignal<ResolvableTo<PointSignal>>,
+        from: String,
+        to: String,
+    },
+    RadialGradient {
+        center: RwSignal<ResolvableTo<PointSignal>>,
+        radius: RwSignal<ResolvableTo<NumberSignal>>,
+        from: String,
+        to: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+impl LineCap {
+    pub const ALL: [LineCap; 3] = [LineCap::Butt, LineCap::Round, LineCap::Square];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+impl LineJoin {
+    pub const ALL: [LineJoin; 3] = [LineJoin::Miter, LineJoin::Round, LineJoin::Bevel];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineDash {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl LineDash {
+    pub const ALL: [LineDash; 3] = [LineDash::Solid, LineDash::Dashed, LineDash::Dotted];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineDash::Solid => "solid",
+            LineDash::Dashed => "dashed",
+            LineDash::Dotted => "dotted",
+        }
+    }
+
+    /// Alternating dash and gap lengths in canvas pixels, in proportion to `line_width`
+    pub fn pattern(&self, line_width: f64) -> Vec<f64> {
+        match self {
+            LineDash::Solid => Vec::new(),
+            LineDash::Dashed => vec![line_width * 3.0, line_width * 2.0],
+            LineDash::Dotted => vec![line_width, line_width * 2.0],
+        }
+    }
+}
+
+/// Canvas shadow settings. With no offset this works as a glow.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shadow {
+    /// In canvas pixels (canvas shadows ignore the current transform)
+    pub blur: f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+    pub color: String,
+}
+
+impl Default for Shadow {
+    fn default() -> Self {
+        Self {
+            blur: 16.0,
+            offset_x: 8.0,
+            offset_y: 8.0,
+            color: "#64748b".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineEndMarker {
+    #[default]
+    None,
+    Arrow,
+    Dot,
+    Bar,
+}
+
+impl LineEndMarker {
+    pub const ALL: [LineEndMarker; 4] = [
+        LineEndMarker::None,
+        LineEndMarker::Arrow,
+        LineEndMarker::Dot,
+        LineEndMarker::Bar,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEndMarker::None => "none",
+            LineEndMarker::Arrow => "arrow",
+            LineEndMarker::Dot => "dot",
+            LineEndMarker::Bar => "bar",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepStyle {
+    /// Literal, or a ref to color data so steps can share it
+    pub stroke: ResolvableTo<ColorSignal>,
+    /// In canvas pixels, so strokes keep their weight regardless of scale
+    pub line_width: f64,
+    pub line_cap: LineCap,
+    pub line_join: LineJoin,
+    pub dash: LineDash,
+    /// A document `StylePreset` overriding this style's stroke, width, dash and fill
+    pub preset: Option<usize>,
+    /// Only drawn for steps with open ends (lines, strokes)
+    pub start_marker: LineEndMarker,
+    pub end_marker: LineEndMarker,
+    /// In world units
+    pub end_marker_size: f64,
+    /// Only drawn for closed shapes
+    pub fill: Fill,
+    pub shadow: Option<Shadow>,
+}
+
+impl StepStyle {
+    pub fn new(cx: Scope) -> Self {
+        Self {
+            stroke: ResolvableTo::literal(cx, "#000000"),
+            line_width: 4.0,
+            line_cap: LineCap::default(),
+            line_join: LineJoin::default(),
+            dash: LineDash::default(),
+            preset: None,
+            start_marker: LineEndMarker::default(),
+            end_marker: LineEndMarker::default(),
+            end_marker_size: 2.0,
+            fill: Fill::None,
+            shadow: None,
+        }
+    }
+
+    /// This style with its preset (if it still exists) applied
+    pub fn resolved(&self, presets: &[StylePreset]) -> StepStyle {
+        let mut style = self.clone();
+        if let Some(preset) = presets.iter().find(|p| Some(p.id) == self.preset) {
+            style.stroke = ResolvableTo::T(preset.stroke);
+            style.line_width = preset.line_width;
+            style.dash = preset.dash;
+            if let Some(fill) = preset.fill {
+                style.fill = Fill::Solid(ResolvableTo::T(fill));
+            }
+        }
+        style
+    }
+
+    /// The refs to color data in this style
+    pub fn color_refs(&self) -> Vec<DataRef> {
+        let mut refs = Vec::new();
+        if let ResolvableTo::Ref(r) = &self.stroke {
+            refs.push(r.clone());
+        }
+        if let Fill::Solid(ResolvableTo::Ref(r)) = &self.fill {
+            refs.push(r.clone());
+        }
+        refs
+    }
+}
+
+/// A named document-wide style steps can use by reference
+#[derive(Clone, Debug, PartialEq)]
+pub struct StylePreset {
+    pub id: usize,
+    pub name: String,
+    pub stroke: ColorSignal,
+    /// In canvas pixels, like `StepStyle::line_width`
+    pub line_width: f64,
+    pub dash: LineDash,
+    /// A solid fill for closed shapes, or None to leave each step's own fill alone
+    pub fill: Option<ColorSignal>,
+}
+
+/// A step's style as drawn, with its own preset or else its first tag's applied
+fn step_style(cx: Scope, step: &Step) -> StepStyle {
+    let settings = use_context::<RwSignal<DocumentSettings>>(cx).unwrap();
+    settings.with(|settings| {
+        let mut style = step.style.get();
+        if style.preset.is_none() {
+            style.preset = step
+                .tags
+                .with(|tags| tags.iter().find_map(|tag| settings.tag_preset(tag)));
+        }
+        style.resolved(&settings.style_presets)
+    })
+}
+
+/// Tags typed as a comma-separated list, trimmed and without repeats
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selection {
+    pub steps: Vec<usize>,
+}
+
+impl Selection {
+    pub fn toggle_step(&mut self, step_id: usize) {
+        if self.steps.contains(&step_id) {
+            self.steps.retain(|id| *id != step_id);
+        } else {
+            self.steps.push(step_id);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transform {
+    Translate {
+        dx: f64,
+        dy: f64,
+    },
+    /// `angle` in degrees, clockwise on screen
+    Rotate {
+        about: Point,
+        angle: f64,
+    },
+    Scale {
+        about: Point,
+        factor: f64,
+    },
+    /// Any affine transform in the canvas's `transform()` order
+    Matrix {
+        a: f64,
+        b: f64,
+        c: f64,
+        d: f64,
+        e: f64,
+        f: f64,
+    },
+}
+
+impl Transform {
+    /// Mirroring across the infinite line through `a` and `b`, which must be apart
+    pub fn reflection(a: Point, b: Point) -> Self {
+        let length = a.distance(b);
+        let (ux, uy) = ((b.x - a.x) / length, (b.y - a.y) / length);
+        let (m11, m12, m22) = (ux * ux - uy * uy, 2.0 * ux * uy, uy * uy - ux * ux);
+        Transform::Matrix {
+            a: m11,
+            b: m12,
+            c: m12,
+            d: m22,
+            e: a.x - (m11 * a.x + m12 * a.y),
+            f: a.y - (m12 * a.x + m22 * a.y),
+        }
+    }
+
+    /// Scaling by `factor` and turning by `angle` radians about `about`, then moving by `(dx, dy)`
+    pub fn similarity(about: Point, factor: f64, angle: f64, dx: f64, dy: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let (a, b, c, d) = (factor * cos, factor * sin, -factor * sin, factor * cos);
+        Transform::Matrix {
+            a,
+            b,
+            c,
+            d,
+            e: about.x - (a * about.x + c * about.y) + dx,
+            f: about.y - (b * about.x + d * about.y) + dy,
+        }
+    }
+
+    pub fn apply(&self, p: Point) -> Point {
+        match *self {
+            Transform::Translate { dx, dy } => Point {
+                x: p.x + dx,
+                y: p.y + dy,
+            },
+            Transform::Rotate { about, angle } => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let (x, y) = (p.x - about.x, p.y - about.y);
+                Point {
+                    x: about.x + x * cos - y * sin,
+                    y: about.y + x * sin + y * cos,
+                }
+            }
+            Transform::Scale { about, factor } => Point {
+                x: about.x + (p.x - about.x) * factor,
+                y: about.y + (p.y - about.y) * factor,
+            },
+            Transform::Matrix { a, b, c, d, e, f } => Point {
+                x: a * p.x + c * p.y + e,
+                y: b * p.x + d * p.y + f,
+            },
+        }
+    }
+}
+
+/// Slots in `step_ids` referencing something outside of them, which a transform won't move
+pub fn external_ref_slots(cx: Scope, step_ids: &[usize]) -> Vec<(InferTarget, DataRef)> {
+    use_context::<RwSignal<Vec<Step>>>(cx)
+        .unwrap()
+        .with(|steps| {
+            steps
+                .iter()
+                .filter(|s| step_ids.contains(&s.id))
+                .flat_map(|s| s.infer_targets())
+                .filter_map(|t| t.data_ref().map(|r| (t, r)))
+                .filter(|(_, r)| r.step_id().map_or(true, |id| !step_ids.contains(&id)))
+                .collect()
+        })
+}
+
+/// Applies `transform` to `step_ids`, with `bake` first baking in refs to anything else
+pub fn transform_steps(cx: Scope, step_ids: &[usize], transform: &Transform, bake: bool) {
+    if bake {
+        for (target, r) in external_ref_slots(cx, step_ids) {
+            match target {
+                InferTarget::Number(n) => {
+                    let value = ResolveToNumber::resolve(&r, cx);
+                    n.set(ResolvableTo::T(create_rw_signal(cx, value)));
+                }
+                InferTarget::Point(p) => {
+                    let value = ResolveToPoint::resolve(&r, cx);
+                    p.set(ResolvableTo::T(PointSignal::literal(cx, value)));
+                }
+                InferTarget::Redirect(_) | InferTarget::Eyedropper { .. } => unreachable!(),
+            }
+        }
+    }
+
+    for step in step_ids.iter().filter_map(|id| step_by_id(cx, *id)) {
+        step.transform(transform);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum DataData {
+    Number(RwSignal<f64>),
+    /// A number kept within `bounds`, shown as a range slider when it has both ends
+    Slider {
+        value: RwSignal<f64>,
+        bounds: RwSignal<SliderBounds>,
+    },
+    /// Like a step's point, either literal coordinates or a ref into the geometry
+    Point(RwSignal<ResolvableTo<PointSignal>>),
+    /// In radians, whichever unit it's shown in
+    Angle(RwSignal<f64>),
+    /// Any CSS color, shared by the step styles referencing it
+    Color(ColorSignal),
+    /// A toggle. Refs to it as a number come out 1 or 0.
+    Bool(RwSignal<bool>),
+    /// A direction and length; number refs pick out `dx`, `dy` or `length`
+    Vector {
+        dx: RwSignal<f64>,
+        dy: RwSignal<f64>,
+    },
+    /// Points referenced by position like `data[3][2]`
+    PointList(RwSignal<Vec<PointSignal>>),
+    /// A scale and turn (in radians) about `(about_x, about_y)` followed by a move
+    Transform {
+        dx: RwSignal<f64>,
+        dy: RwSignal<f64>,
+        angle: RwSignal<f64>,
+        scale: RwSignal<f64>,
+        about_x: RwSignal<f64>,
+        about_y: RwSignal<f64>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SliderBounds {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Values go up in steps of this from `min` (or from 0 without one)
+    pub step: Option<f64>,
+}
+
+impl SliderBounds {
+    /// The nearest allowed value to `value`
+    pub fn clamp(&self, value: f64) -> f64 {
+        let mut value = value;
+        if let Some(step) = self.step.filter(|s| *s > 0.0) {
+            let from = self.min.unwrap_or(0.0);
+            value = from + ((value - from) / step).round() * step;
+        }
+        if let Some(max) = self.max {
+            value = value.min(max);
+        }
+        if let Some(min) = self.min {
+            value = value.max(min);
+        }
+        value
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Data {
+    pub id: usize,
+    pub data: DataData,
+    /// Deleted data sits in the trash until it's emptied
+    pub trashed: RwSignal<bool>,
+}
+
+impl Data {
+    /// Every resolvable slot in this data, like `Step::infer_targets`
+    pub fn infer_targets(&self) -> Vec<InferTarget> {
+        match self.data {
+            DataData::Point(point) => {
+                let mut targets = vec![InferTarget::Point(point)];
+                if let ResolvableTo::T(p) = point.get() {
+                    targets.push(InferTarget::Number(p.x));
+                    targets.push(InferTarget::Number(p.y));
+                }
+                targets
+            }
+            DataData::PointList(points) => points.with(|points| {
+                points
+                    .iter()
+                    .flat_map(|p| [InferTarget::Number(p.x), InferTarget::Number(p.y)])
+                    .collect()
+            }),
+            DataData::Number(_)
+            | DataData::Slider { .. }
+            | DataData::Angle(_)
+            | DataData::Color(_)
+            | DataData::Bool(_)
+            | DataData::Vector { .. }
+            | DataData::Transform { .. } => Vec::new(),
+        }
+    }
+
+    pub fn references(&self) -> Vec<DataRef> {
+        let targets = self.infer_targets();
+        targets
+            .iter()
+            .filter_map(|t| t.data_ref())
+            .chain(targets.iter().flat_map(|t| t.expr_refs()))
+            .collect()
+    }
+}
+
+/// What a watch keeps an eye on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WatchKind {
+    /// Picked like any number slot: a step's number prop, number data, or the eyedropper
+    Number(RwSignal<ResolvableTo<NumberSignal>>),
+    /// Picked on the canvas like any point slot
+    Point(RwSignal<ResolvableTo<PointSignal>>),
+    /// An expression over the other watches, named `w<id>` (`w<id>_x` and `w<id>_y` for points)
+    Expr(RwSignal<String>),
+}
+
+/// A value shown live in the watch panel, which nothing can refer to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Watch {
+    pub id: usize,
+    pub kind: WatchKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WatchValue {
+    Number(f64),
+    Point(Point),
+}
+
+impl WatchValue {
+    pub fn desc(&self) -> String {
+        let round = |v: f64| (v * 1e4).round() / 1e4;
+        match self {
+            WatchValue::Number(n) => round(*n).to_string(),
+            WatchValue::Point(p) => format!("({}, {})", round(p.x), round(p.y)),
+        }
+    }
+}
+
+impl Watch {
+    /// The watch's slot, so it's kept up to date when what it refers to is deleted
+    pub fn infer_targets(&self) -> Vec<InferTarget> {
+        match self.kind {
+            WatchKind::Number(n) => vec![InferTarget::Number(n)],
+            WatchKind::Point(p) => vec![InferTarget::Point(p)],
+            WatchKind::Expr(_) => Vec::new(),
+        }
+    }
+}
+
+/// Why resolving `r` can't be trusted, if it can't: a missing or broken step
+fn watched_ref_error(cx: Scope, r: &DataRef) -> Option<String> {
+    let id = r.step_id()?;
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    steps.with(|steps| match steps.iter().find(|s| s.id == id) {
+        None => Some(format!("{} refers to a missing step", r.desc())),
+        Some(step) => step_error(steps, step).or_else(|| step.resolve_error(cx)),
+    })
+}
+
+/// The current value of watch `id`, with `visiting` catching expression watch cycles
+pub fn watch_value(
+    cx: Scope,
+    watches: &[Watch],
+    id: usize,
+    visiting: &mut Vec<usize>,
+) -> Result<WatchValue, String> {
+    let watch = watches
+        .iter()
+        .find(|w| w.id == id)
+        .ok_or_else(|| format!("There's no watch w{}", id))?;
+    match watch.kind {
+        WatchKind::Number(n) => {
+            if let ResolvableTo::Ref(r) = n.get() {
+                if let Some(error) = watched_ref_error(cx, &r) {
+                    return Err(error);
+                }
+            }
+            Ok(WatchValue::Number(n.get().resolve(cx)))
+        }
+        WatchKind::Point(p) => {
+            if let ResolvableTo::Ref(r) = p.get() {
+                if let Some(error) = watched_ref_error(cx, &r) {
+                    return Err(error);
+                }
+            }
+            Ok(WatchValue::Point(p.get().resolve(cx)))
+        }
+        WatchKind::Expr(src) => {
+            if visiting.contains(&id) {
+                return Err(format!("w{} refers back to itself", id));
+            }
+            let expr = expr::parse(&src.get())?;
+            visiting.push(id);
+            // Lookup errors are kept aside, since `eval` only hears whether a name exists
+            let error = std::cell::RefCell::new(None);
+            let unit = angle_unit(cx);
+            let value = expr.eval(
+                &|name: &str| {
+                    let (watched, coord) = match name.split_once('_') {
+                        Some((watched, coord @ ("x" | "y"))) => (watched, Some(coord)),
+                        _ => (name, None),
+                    };
+                    let watched = watched.strip_prefix('w')?.parse::<usize>().ok()?;
+                    let value = watch_value(cx, watches, watched, &mut visiting.clone());
+                    match (value, coord) {
+                        (Ok(WatchValue::Number(n)), None) => Some(n),
+                        (Ok(WatchValue::Point(p)), Some("x")) => Some(p.x),
+                        (Ok(WatchValue::Point(p)), Some(_)) => Some(p.y),
+                        (Ok(_), _) => None,
+                        (Err(e), _) => {
+                            error.borrow_mut().get_or_insert(e);
+                            Some(f64::NAN)
+                        }
+                    }
+                },
+                unit,
+            );
+            visiting.pop();
+            match error.into_inner() {
+                Some(error) => Err(error),
+                None => value.map(WatchValue::Number),
+            }
+        }
+    }
+}
+
+/// Adds a watch of the given kind, starting out blank
+pub fn add_watch(cx: Scope, kind: &str) {
+    let watches = use_context::<RwSignal<Vec<Watch>>>(cx).unwrap();
+    let kind = match kind {
+        "point" => WatchKind::Point(create_rw_signal(
+            cx,
+            ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+        )),
+        "expr" => WatchKind::Expr(create_rw_signal(cx, String::new())),
+        _ => WatchKind::Number(create_rw_signal(
+            cx,
+            ResolvableTo::T(create_rw_signal(cx, 0.0)),
+        )),
+    };
+    watches.update(|w| {
+        let id = w.iter().map(|w| w.id + 1).max().unwrap_or(0);
+        w.push(Watch { id, kind });
+    });
+}
+
+/// A named set of values for a `ParameterTable`'s parameters, like one size of a design
+#[derive(Clone, Debug, PartialEq)]
+pub struct Configuration {
+    pub name: String,
+    /// One per parameter, in the same order. Angles are in radians, bools 1 or 0.
+    pub values: Vec<f64>,
+}
+
+/// Chosen data parameters (the rows) against saved configurations (the columns)
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterTable {
+    /// Ids of number, angle or bool data
+    pub params: Vec<usize>,
+    pub configurations: Vec<Configuration>,
+    /// The configuration the data was last switched to
+    pub active: Option<usize>,
+    /// What each configuration's export is called (see `export_filename`)
+    pub filename_template: String,
+}
+
+impl Default for ParameterTable {
+    fn default() -> Self {
+        Self {
+            params: Vec::new(),
+            configurations: Vec::new(),
+            active: None,
+            filename_template: "drawing-{name}.png".to_string(),
+        }
+    }
+}
+
+/// A parameter's value as a number, if it's data a table can hold
+fn parameter_value(cx: Scope, data_id: usize) -> Option<f64> {
+    match find_data(cx, data_id)?.data {
+        DataData::Number(n) | DataData::Angle(n) | DataData::Slider { value: n, .. } => {
+            Some(n.get())
+        }
+        DataData::Bool(b) => Some(if b.get() { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+fn set_parameter_value(cx: Scope, data_id: usize, value: f64) {
+    match find_data(cx, data_id).map(|d| d.data) {
+        Some(DataData::Number(n) | DataData::Angle(n)) => n.set(value),
+        Some(DataData::Slider { value: n, bounds }) => n.set(bounds.get().clamp(value)),
+        Some(DataData::Bool(b)) => b.set(value != 0.0),
+        _ => {}
+    }
+}
+
+/// Adds data `data_id` as a row, with its current value in every configuration
+pub fn add_parameter(cx: Scope, data_id: usize) {
+    let Some(value) = parameter_value(cx, data_id) else {
+        return;
+    };
+    use_context::<RwSignal<ParameterTable>>(cx)
+        .unwrap()
+        .update(|t| {
+            if t.params.contains(&data_id) {
+                return;
+            }
+            t.params.push(data_id);
+            for c in t.configurations.iter_mut() {
+                c.values.push(value);
+            }
+        });
+}
+
+/// Saves the parameters' current values as a new, active configuration
+pub fn add_configuration(cx: Scope) {
+    let table = use_context::<RwSignal<ParameterTable>>(cx).unwrap();
+    let values = table.with(|t| {
+        t.params
+            .iter()
+            .map(|id| parameter_value(cx, *id).unwrap_or(0.0))
+            .collect()
+    });
+    table.update(|t| {
+        t.configurations.push(Configuration {
+            name: format!("Config {}", t.configurations.len() + 1),
+            values,
+        });
+        t.active = Some(t.configurations.len() - 1);
+    });
+}
+
+/// Switches every parameter to configuration `index`'s values, keeping edits in the old one
+pub fn activate_configuration(cx: Scope, index: usize) {
+    let table = use_context::<RwSignal<ParameterTable>>(cx).unwrap();
+    let current: Vec<Option<f64>> =
+        table.with(|t| t.params.iter().map(|id| parameter_value(cx, *id)).collect());
+    table.update(|t| {
+        if let Some(active) = t.active.and_then(|a| t.configurations.get_mut(a)) {
+            for (value, current) in active.values.iter_mut().zip(&current) {
+                if let Some(current) = current {
+                    *value = *current;
+                }
+            }
+        }
+        t.active = Some(index);
+    });
+    table.with(|t| {
+        if let Some(c) = t.configurations.get(index) {
+            for (id, value) in t.params.iter().zip(&c.values) {
+                set_parameter_value(cx, *id, *value);
+            }
+        }
+    });
+}
+
+#[derive(Copy, Clone, Default)]
+struct DragData {
+    initial_value: f64,
+    start: f64,
+}
+
+#[component]
+pub fn DraggableNumView(cx: Scope, d: RwSignal<f64>) -> impl IntoView {
+    let (d, set_d) = d.split();
+
+    let (drag_data, set_drag_data) = create_signal(cx, DragData::default());
+
+    let mousemove_callback = move |e: web_sys::MouseEvent| {
+        let delta = drag_data().start - e.y() as f64;
+        set_d(drag_data().initial_value + delta);
+    };
+    let mousemove_closure =
+        wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(mousemove_callback).into_js_value();
+    let mousemove_closure_clone = mousemove_closure.clone();
+
+    let mouseup_callback = move |_e: web_sys::MouseEvent| {
+        document()
+            .remove_event_listener_with_callback(
+                "mousemove",
+                mousemove_closure.as_ref().unchecked_ref(),
+            )
+            .unwrap();
+    };
+    let mouseup_closure = wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(mouseup_callback);
+
+    let mousedown_callback = move |e: web_sys::MouseEvent| {
+        set_drag_data.update(|dd| {
+            dd.initial_value = d();
+            dd.start = e.y() as f64;
+        });
+
+        document()
+            .add_event_listener_with_callback(
+                "mousemove",
+                mousemove_closure_clone.as_ref().unchecked_ref(),
+            )
+            .unwrap();
+
+        document()
+            .add_event_listener_with_callback("mouseup", mouseup_closure.as_ref().unchecked_ref())
+            .unwrap();
+    };
+
+    view! { cx,
+        <div
+            on:mousedown=mousedown_callback
+            style="user-select: none"
+        >
+            {d}
+        </div>
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum InferTarget {
+    Number(RwSignal<ResolvableTo<NumberSignal>>),
+    Point(RwSignal<ResolvableTo<PointSignal>>),
+    /// Pick a snap point to take over every reference into the given (deleted) step
+    Redirect(usize),
+    /// Picks a number off the canvas: a snap point's `coord` ("x"/"y"), or a line's length
+    Eyedropper {
+        target: RwSignal<ResolvableTo<NumberSignal>>,
+        coord: Option<&'static str>,
+    },
+}
+
+impl InferTarget {
+    pub fn data_ref(&self) -> Option<DataRef> {
+        match self {
+            InferTarget::Number(n) => match n.get() {
+                ResolvableTo::Ref(r) => Some(r),
+                ResolvableTo::T(_) | ResolvableTo::Expr(_) => None,
+            },
+            InferTarget::Point(p) => match p.get() {
+                ResolvableTo::Ref(r) => Some(r),
+                ResolvableTo::T(_) | ResolvableTo::Expr(_) => None,
+            },
+            InferTarget::Redirect(_) | InferTarget::Eyedropper { .. } => None,
+        }
+    }
+
+    /// The steps and data the slot's expression mentions, if it holds one
+    pub fn expr_refs(&self) -> Vec<DataRef> {
+        match self {
+            InferTarget::Number(n) => match n.get() {
+                ResolvableTo::Expr(src) => expr_mentions(&src),
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// An angle field shown in the document's angle unit, kept in `stored`
+#[component]
+fn ResolvableToAngleView(
+    cx: Scope,
+    n: RwSignal<ResolvableTo<AngleSignal>>,
+    stored: AngleUnit,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+
+    move || match (n.get(), context_infer_target.get()) {
+        (ResolvableTo::T(t), None) => {
+            let shown = move || shown_value(angle_unit(cx).convert(t.get(), stored));
+            view! { cx,
+                <div class="flex flex-row">
+                    <input
+                        type="number"
+                        step="any"
+                        class="w-20"
+                        prop:value=shown
+                        on:change=move |ev| {
+                            if let Ok(value) = event_target_value(&ev).parse::<f64>() {
+                                t.set(stored.convert(value, angle_unit(cx)));
+                            }
+                        }
+                    />
+                    <button class="border-2 border-gray-800" on:click=move |_| {
+                        context_infer_target.set(Some(InferTarget::Number(n)));
+                    }>"I"</button>
+                    <button class="border-2 border-gray-800" on:click=move |_| {
+                        context_infer_target.set(Some(InferTarget::Eyedropper { target: n, coord: None }));
+                    }>"E"</button>
+                    // Expressions are written in the document's unit, so it starts off there
+                    <button class="border-2 border-gray-800" on:click=move |_| {
+                        let value = angle_unit(cx).convert(t.get(), stored);
+                        n.set(ResolvableTo::Expr(value.to_string()));
+                    }>"ƒ"</button>
+                </div>
+            }
+            .into_view(cx)
+        }
+        _ => view! { cx, <ResolvableToNumberView n data_ref_path angle=stored /> }.into_view(cx),
+    }
+}
+
+#[component]
+fn ResolvableToNumberView(
+    cx: Scope,
+    n: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+    /// The unit an angle field keeps its literal value in
+    #[prop(optional)]
+    angle: Option<AngleUnit>,
+) -> impl IntoView {
+    move || {
+        let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+
+        if let Some(InferTarget::Eyedropper { target, .. }) = context_infer_target.get() {
+            if n == target {
+                return view! { cx,
+                    <div class="flex flex-row">
+                        <p>"pick on canvas..."</p>
+                        <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
+                            context_infer_target.set(None);
+                        }>
+                            "C"
+                        </button>
+                    </div>
+                }
+                .into_view(cx);
+            }
+        }
+
+        if let Some(InferTarget::Number(it)) = context_infer_target.get() {
+            if n == it {
+                return view! { cx,
+                    <div class="flex flex-row">
+                        <p>"..."</p>
+                        <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
+                            context_infer_target.set(None);
+                        }>
+                            "C"
+                        </button>
+                    </div>
+                }
+                .into_view(cx);
+            } else {
+                return view! { cx,
+                    <div class="flex flex-row">
+                        <button
+                            class="border-2 border-gray-800 mt-4"
+                            on:mouseenter=move |_| {
+                                start_binding_preview(cx, InferTarget::Number(it), DataRef(data_ref_path.get()));
+                            }
+                            on:mouseleave=move |_| end_binding_preview(cx)
+                            on:click=move |_| {
+                                commit_binding_preview(cx);
+                                it.set(ResolvableTo::Ref(DataRef(data_ref_path.get())));
+                                context_infer_target.set(None);
+                            }
+                        >
+                            "O"
+                        </button>
+                    </div>
+                }
+                .into_view(cx);
+            }
+        }
+
+        match n.get() {
+            ResolvableTo::T(t) => view! { cx,
+                <div class="flex flex-row">
+                    <DraggableNumView d=t />
+                    <button class="border-2 border-gray-800" on:click=move |_| {
+                        context_infer_target.set(Some(InferTarget::Number(n)));
+                    }>"I"</button>
+                    <button class="border-2 border-gray-800" on:click=move |_| {
+                        let coord = match data_ref_path.get().last() {
+                            Some(DataRefPathEl::PropName(c @ ("x" | "y"))) => Some(*c),
+                            _ => None,
+                        };
+                        context_infer_target.set(Some(InferTarget::Eyedropper { target: n, coord }));
+                    }>"E"</button>
+                    // Starts the expression off at the current value
+                    <button class="border-2 border-gray-800" on:click=move |_| {
+                        n.set(ResolvableTo::Expr(t.get().to_string()));
+                    }>"ƒ"</button>
+                </div>
+            }
+            .into_view(cx),
+            ResolvableTo::Ref(r) => view! { cx,
+                <div class="flex flex-col">
+                    <p><RefBreadcrumbs r /></p>
+                    // <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
+                    //     n.set(ResolvableTo::T(create_signal(cx, 0.0)));
+                    // }>
+                    //     "Cancel Infer"
+                    // </button>
+                </div>
+            }
+            .into_view(cx),
+            ResolvableTo::Expr(src) => {
+                let value = {
+                    let src = src.clone();
+                    move || expr_value(cx, &src)
+                };
+                // Why the last expression typed wasn't taken, if it wasn't
+                let rejected = create_rw_signal(cx, None::<String>);
+                view! { cx,
+                    <div class="flex flex-col">
+                        <div class="flex flex-row">
+                            <input
+                                type="text"
+                                class="w-40"
+                                prop:value=move || src.clone()
+                                on:change=move |ev| {
+                                    let src = event_target_value(&ev);
+                                    match expr_cycle(cx, n, &src) {
+                                        Some(e) => rejected.set(Some(e)),
+                                        None => n.set(ResolvableTo::Expr(src)),
+                                    }
+                                }
+                            />
+                            // Keeps the value it has now, as a plain number
+                            <button class="border-2 border-gray-800" on:click=move |_| {
+                                let value = match angle {
+                                    Some(stored) => n.get().resolve_in(cx, stored),
+                                    None => n.get().resolve(cx),
+                                };
+                                n.set(ResolvableTo::T(create_rw_signal(cx, value)));
+                            }>"#"</button>
+                        </div>
+                        {move || match rejected.get().map_or_else(&value, Err) {
+                            Ok(v) => view! { cx,
+                                <p class="text-sm text-gray-600">{format!("= {}", (v * 1e4).round() / 1e4)}</p>
+                            },
+                            Err(e) => view! { cx, <p class="text-sm text-red-700">{e}</p> },
+                        }}
+                    </div>
+                }
+                .into_view(cx)
+            }
+        }
+    }
+}
+
+#[component]
+fn InnerStepViewDrawPoint(
+    cx: Scope,
+    sig: RwSignal<ResolvableTo<PointSignal>>,
+    point: PointSignal,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    move || {
+        let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+        if let Some(InferTarget::Point(it)) = context_infer_target.get() {
+            if sig == it {
+                return view! { cx,
+                    <div class="flex flex-col">
+                        <p>"..."</p>
+                        <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
+                            context_infer_target.set(None);
+                        }>
+                            "Cancel Infer"
+                        </button>
+                    </div>
+                }
+                .into_view(cx);
+            }
+        }
+
+        let mut x_path = data_ref_path.get();
+        x_path.push(DataRefPathEl::PropName("x"));
+        let x_path = store_value(cx, x_path);
+
+        let mut y_path = data_ref_path.get();
+        y_path.push(DataRefPathEl::PropName("y"));
+        let y_path = store_value(cx, y_path);
+
+        view! { cx,
+            <div class="flex flex-col">
+                <p>"Draw Point"</p>
+                <div class="flex flex-row">
+                    <p>"x: "</p>
+                    <ResolvableToNumberView n={point.x} data_ref_path=x_path />
+                    <p class="ml-3">"y: "</p>
+                    <ResolvableToNumberView n={point.y} data_ref_path=y_path />
+                </div>
+                <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
+                    context_infer_target.set(Some(InferTarget::Point(sig)));
+                }>
+                    "Infer"
+                </button>
+            </div>
+        }
+        .into_view(cx)
+    }
+}
+
+#[component]
+fn InnerStepViewResolveableToPoint(
+    cx: Scope,
+    point: RwSignal<ResolvableTo<PointSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    move || match point() {
+        ResolvableTo::T(p) => view! { cx,
+            <InnerStepViewDrawPoint sig=point point=p data_ref_path=data_ref_path />
+        }
+        .into_view(cx),
+        ResolvableTo::Ref(dr) => {
+            let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+            view! { cx,
+                <div><RefBreadcrumbs r=dr /></div>
+                <button class="border-2 border-gray-800 mt-4" on:click=move |_| {
+                    context_infer_target.set(Some(InferTarget::Point(point)));
+                }>
+                    "Infer"
+                </button>
+            }
+        }
+        .into_view(cx),
+        ResolvableTo::Expr(_) => unreachable!("Only number fields hold expressions"),
+    }
+}
+
+/// A number prop computed by its step, bindable like any other number
+#[component]
+fn DerivedNumberView(
+    cx: Scope,
+    step_id: usize,
+    /// The unit the value comes out in if it's an angle, to show it in the document's
+    angle: Option<AngleUnit>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+
+    let value = move || {
+        let resolvable = steps.with(|steps| {
+            steps
+                .iter()
+                .find(|s| s.id == step_id)
+                .map_or(false, |s| is_resolvable(steps, s))
+        });
+        resolvable
+            .then(|| ResolveToNumber::resolve(&DataRef(data_ref_path.get()), cx))
+            .map(|v| match angle {
+                Some(stored) => angle_unit(cx).convert(v, stored),
+                None => v,
+            })
+    };
+
+    view! { cx,
+        <div class="flex flex-row items-center">
+            <p>{move || value().map_or("-".to_string(), |v| format!("{:.3}", v))}</p>
+            {move || match context_infer_target.get() {
+                Some(InferTarget::Number(it)) => Some(view! { cx,
+                    <button
+                        class="border-2 border-gray-800 ml-2"
+                        on:mouseenter=move |_| {
+                            start_binding_preview(cx, InferTarget::Number(it), DataRef(data_ref_path.get()));
+                        }
+                        on:mouseleave=move |_| end_binding_preview(cx)
+                        on:click=move |_| {
+                            commit_binding_preview(cx);
+                            it.set(ResolvableTo::Ref(DataRef(data_ref_path.get())));
+                            context_infer_target.set(None);
+                        }
+                    >
+                        "O"
+                    </button>
+                }),
+                _ => None,
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewAngle(
+    cx: Scope,
+    step_id: usize,
+    a: RwSignal<ResolvableTo<PointSignal>>,
+    vertex: RwSignal<ResolvableTo<PointSignal>>,
+    b: RwSignal<ResolvableTo<PointSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let prop_path = move |prop_name: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::PropName(prop_name));
+        store_value(cx, path)
+    };
+    let a_path = prop_path("a");
+    let vertex_path = prop_path("vertex");
+    let b_path = prop_path("b");
+    let value_path = prop_path("value");
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Angle"</p>
+
+            <p>"from: "</p>
+            <InnerStepViewResolveableToPoint point={a} data_ref_path=a_path />
+
+            <p>"at: "</p>
+            <InnerStepViewResolveableToPoint point={vertex} data_ref_path=vertex_path />
+
+            <p>"to: "</p>
+            <InnerStepViewResolveableToPoint point={b} data_ref_path=b_path />
+
+            <p>{move || format!("value ({}): ", angle_unit(cx).name())}</p>
+            <DerivedNumberView step_id angle=Some(AngleUnit::Degrees) data_ref_path=value_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewDistance(
+    cx: Scope,
+    step_id: usize,
+    a: RwSignal<ResolvableTo<PointSignal>>,
+    b: RwSignal<ResolvableTo<PointSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut value_path = data_ref_path.get();
+    value_path.push(DataRefPathEl::PropName("value"));
+    let value_path = store_value(cx, value_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <InnerStepViewTwoPoints
+                title="Distance"
+                names={["a", "b"]}
+                points={[a, b]}
+                data_ref_path
+            />
+
+            <p>"value: "</p>
+            <DerivedNumberView step_id angle=None data_ref_path=value_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewDrawLine(
+    cx: Scope,
+    start: RwSignal<ResolvableTo<PointSignal>>,
+    end: RwSignal<ResolvableTo<PointSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut start_path = data_ref_path.get();
+    start_path.push(DataRefPathEl::PropName("start"));
+    let start_path = store_value(cx, start_path);
+
+    let mut end_path = data_ref_path.get();
+    end_path.push(DataRefPathEl::PropName("start"));
+    let end_path = store_value(cx, end_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Draw Line"</p>
+
+            <p>"start: "</p>
+            <InnerStepViewResolveableToPoint point={start} data_ref_path=start_path />
+
+            <p>"end: "</p>
+            <InnerStepViewResolveableToPoint point={end} data_ref_path=end_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewDrawImage(
+    cx: Scope,
+    src: RwSignal<String>,
+    position: RwSignal<ResolvableTo<PointSignal>>,
+    scale: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut position_path = data_ref_path.get();
+    position_path.push(DataRefPathEl::PropName("position"));
+    let position_path = store_value(cx, position_path);
+
+    let mut scale_path = data_ref_path.get();
+    scale_path.push(DataRefPathEl::PropName("scale"));
+    let scale_path = store_value(cx, scale_path);
+
+    let on_file = move |ev: web_sys::Event| {
+        let input = ev
+            .target()
+            .unwrap()
+            .unchecked_into::<web_sys::HtmlInputElement>();
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+
+        let reader = web_sys::FileReader::new().unwrap();
+        let reader_clone = reader.clone();
+        let onload = wasm_bindgen::prelude::Closure::<dyn Fn()>::new(move || {
+            if let Some(data_url) = reader_clone.result().ok().and_then(|r| r.as_string()) {
+                src.set(data_url);
+            }
+        })
+        .into_js_value();
+        reader.set_onload(Some(onload.as_ref().unchecked_ref()));
+        reader.read_as_data_url(&file).unwrap();
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Draw Image"</p>
+            <input
+                type="text"
+                placeholder="Image URL"
+                // Data URLs are too long to be worth showing
+                prop:value=move || src.with(|s| if s.starts_with("data:") { String::new() } else { s.clone() })
+                on:change=move |ev| src.set(event_target_value(&ev))
+            />
+            <input type="file" accept="image/*" on:change=on_file />
+
+            <p>"position: "</p>
+            <InnerStepViewResolveableToPoint point={position} data_ref_path=position_path />
+
+            <p>"scale: "</p>
+            <ResolvableToNumberView n=scale data_ref_path=scale_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewDrawText(
+    cx: Scope,
+    step_id: usize,
+    anchor: RwSignal<ResolvableTo<PointSignal>>,
+    content: RwSignal<String>,
+    size: RwSignal<ResolvableTo<NumberSignal>>,
+    path: RwSignal<Option<DataRef>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut anchor_path = data_ref_path.get();
+    anchor_path.push(DataRefPathEl::PropName("anchor"));
+    let anchor_path = store_value(cx, anchor_path);
+
+    let mut size_path = data_ref_path.get();
+    size_path.push(DataRefPathEl::PropName("size"));
+    let size_path = store_value(cx, size_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Draw Text"</p>
+            <input
+                type="text"
+                prop:value=move || content.get()
+                on:input=move |ev| content.set(event_target_value(&ev))
+            />
+
+            <p>"anchor: "</p>
+            <InnerStepViewResolveableToPoint point={anchor} data_ref_path=anchor_path />
+
+            <p>"size: "</p>
+            <ResolvableToNumberView n=size data_ref_path=size_path />
+
+            <p>"along: "</p>
+            <PathSelect step_id source=path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewScaleBar(
+    cx: Scope,
+    origin: RwSignal<ResolvableTo<PointSignal>>,
+    length: RwSignal<ResolvableTo<NumberSignal>>,
+    divisions: RwSignal<ResolvableTo<NumberSignal>>,
+    unit: RwSignal<String>,
+    size: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let prop_path = move |prop_name: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::PropName(prop_name));
+        store_value(cx, path)
+    };
+    let origin_path = prop_path("origin");
+    let length_path = prop_path("length");
+    let divisions_path = prop_path("divisions");
+    let size_path = prop_path("size");
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Scale Bar"</p>
+
+            <p>"origin: "</p>
+            <InnerStepViewResolveableToPoint point={origin} data_ref_path=origin_path />
+
+            <p>"length: "</p>
+            <ResolvableToNumberView n=length data_ref_path=length_path />
+
+            <p>"unit: "</p>
+            <input
+                type="text"
+                prop:value=move || unit.get()
+                on:input=move |ev| unit.set(event_target_value(&ev))
+            />
+
+            <p>"divisions: "</p>
+            <ResolvableToNumberView n=divisions data_ref_path=divisions_path />
+
+            <p>"size: "</p>
+            <ResolvableToNumberView n=size data_ref_path=size_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewAxes(
+    cx: Scope,
+    origin: RwSignal<ResolvableTo<PointSignal>>,
+    x_min: RwSignal<ResolvableTo<NumberSignal>>,
+    x_max: RwSignal<ResolvableTo<NumberSignal>>,
+    y_min: RwSignal<ResolvableTo<NumberSignal>>,
+    y_max: RwSignal<ResolvableTo<NumberSignal>>,
+    spacing: RwSignal<ResolvableTo<NumberSignal>>,
+    size: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let prop_path = move |prop_name: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::PropName(prop_name));
+        store_value(cx, path)
+    };
+    let origin_path = prop_path("origin");
+    let x_min_path = prop_path("x_min");
+    let x_max_path = prop_path("x_max");
+    let y_min_path = prop_path("y_min");
+    let y_max_path = prop_path("y_max");
+    let spacing_path = prop_path("spacing");
+    let size_path = prop_path("size");
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Axes"</p>
+
+            <p>"origin: "</p>
+            <InnerStepViewResolveableToPoint point={origin} data_ref_path=origin_path />
+
+            <p>"x from: "</p>
+            <ResolvableToNumberView n=x_min data_ref_path=x_min_path />
+            <p>"x to: "</p>
+            <ResolvableToNumberView n=x_max data_ref_path=x_max_path />
+
+            <p>"y from: "</p>
+            <ResolvableToNumberView n=y_min data_ref_path=y_min_path />
+            <p>"y to: "</p>
+            <ResolvableToNumberView n=y_max data_ref_path=y_max_path />
+
+            <p>"tick spacing: "</p>
+            <ResolvableToNumberView n=spacing data_ref_path=spacing_path />
+
+            <p>"size: "</p>
+            <ResolvableToNumberView n=size data_ref_path=size_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewCallout(
+    cx: Scope,
+    anchor: RwSignal<ResolvableTo<PointSignal>>,
+    tip: RwSignal<ResolvableTo<PointSignal>>,
+    content: RwSignal<String>,
+    size: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let prop_path = move |prop_name: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::PropName(prop_name));
+        store_value(cx, path)
+    };
+    let anchor_path = prop_path("anchor");
+    let tip_path = prop_path("tip");
+    let size_path = prop_path("size");
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Callout"</p>
+            <input
+                type="text"
+                prop:value=move || content.get()
+                on:input=move |ev| content.set(event_target_value(&ev))
+            />
+
+            <p>"label at: "</p>
+            <InnerStepViewResolveableToPoint point={anchor} data_ref_path=anchor_path />
+
+            <p>"pointing to: "</p>
+            <InnerStepViewResolveableToPoint point={tip} data_ref_path=tip_path />
+
+            <p>"size: "</p>
+            <ResolvableToNumberView n=size data_ref_path=size_path />
+        </div>
+    }
+}
+
+/// Shared by paths and splines, which only differ in how they're drawn
+#[component]
+fn InnerStepViewDrawPath(
+    cx: Scope,
+    title: &'static str,
+    points: RwSignal<Vec<RwSignal<ResolvableTo<PointSignal>>>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let add_vertex = move |_| {
+        // Continue from wherever the path currently ends
+        let last = points
+            .with(|p| p.last().copied())
+            .map(|p| p.get().resolve(cx))
+            .unwrap_or_default();
+        points.update(|p| {
+            p.push(create_rw_signal(
+                cx,
+                ResolvableTo::T(PointSignal::literal(cx, last)),
+            ))
+        });
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>{title}</p>
+            {move || {
+                let count = points.with(|p| p.len());
+                points
+                    .get()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, point)| {
+                        let mut path = data_ref_path.get();
+                        path.push(DataRefPathEl::PropName("points"));
+                        path.push(DataRefPathEl::Index(i));
+                        let path = store_value(cx, path);
+
+                        view! { cx,
+                            <div class="flex flex-row justify-between mt-2">
+                                <p>{format!("points[{}]: ", i)}</p>
+                                <button
+                                    class="border-2 border-gray-800 px-1 disabled:opacity-50"
+                                    prop:disabled={count <= 2}
+                                    on:click=move |_| points.update(|p| {
+                                        p.remove(i);
+                                    })
+                                >
+                                    "Remove"
+                                </button>
+                            </div>
+                            <InnerStepViewResolveableToPoint point data_ref_path=path />
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            }}
+            <button class="border-2 border-gray-800 mt-2" on:click=add_vertex>"+ Vertex"</button>
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewCentroid(
+    cx: Scope,
+    points: RwSignal<Vec<RwSignal<ResolvableTo<PointSignal>>>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+    // New points start at the current centroid and go straight to being inferred
+    let add_point = move |_| {
+        let resolved: Vec<Point> = points.with(|p| p.iter().map(|p| p.get().resolve(cx)).collect());
+        let start = if resolved.is_empty() {
+            Point::default()
+        } else {
+            centroid(&resolved)
+        };
+        let point = create_rw_signal(cx, ResolvableTo::T(PointSignal::literal(cx, start)));
+        points.update(|p| p.push(point));
+        context_infer_target.set(Some(InferTarget::Point(point)));
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Centroid"</p>
+            {move || {
+                let count = points.with(|p| p.len());
+                points
+                    .get()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, point)| {
+                        let mut path = data_ref_path.get();
+                        path.push(DataRefPathEl::PropName("points"));
+                        path.push(DataRefPathEl::Index(i));
+                        let path = store_value(cx, path);
+
+                        view! { cx,
+                            <div class="flex flex-row justify-between mt-2">
+                                <p>{format!("points[{}]: ", i)}</p>
+                                <button
+                                    class="border-2 border-gray-800 px-1 disabled:opacity-50"
+                                    prop:disabled={count <= 1}
+                                    on:click=move |_| points.update(|p| {
+                                        p.remove(i);
+                                    })
+                                >
+                                    "Remove"
+                                </button>
+                            </div>
+                            <InnerStepViewResolveableToPoint point data_ref_path=path />
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            }}
+            <button class="border-2 border-gray-800 mt-2" on:click=add_point>"+ Point"</button>
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewDrawPolygon(
+    cx: Scope,
+    center: RwSignal<ResolvableTo<PointSignal>>,
+    radius: RwSignal<ResolvableTo<NumberSignal>>,
+    sides: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let prop_path = move |prop_name: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::PropName(prop_name));
+        store_value(cx, path)
+    };
+    let center_path = prop_path("center");
+    let radius_path = prop_path("radius");
+    let sides_path = prop_path("sides");
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Draw Polygon"</p>
+
+            <p>"center: "</p>
+            <InnerStepViewResolveableToPoint point={center} data_ref_path=center_path />
+
+            <p>"radius: "</p>
+            <ResolvableToNumberView n=radius data_ref_path=radius_path />
+
+            <p>"sides: "</p>
+            <ResolvableToNumberView n=sides data_ref_path=sides_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewPointGrid(
+    cx: Scope,
+    origin: RwSignal<ResolvableTo<PointSignal>>,
+    col_spacing: RwSignal<ResolvableTo<NumberSignal>>,
+    row_spacing: RwSignal<ResolvableTo<NumberSignal>>,
+    cols: RwSignal<ResolvableTo<NumberSignal>>,
+    rows: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let prop_path = move |prop_name: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::PropName(prop_name));
+        store_value(cx, path)
+    };
+    let origin_path = prop_path("origin");
+    let col_spacing_path = prop_path("col_spacing");
+    let row_spacing_path = prop_path("row_spacing");
+    let cols_path = prop_path("cols");
+    let rows_path = prop_path("rows");
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Point Grid"</p>
+
+            <p>"origin: "</p>
+            <InnerStepViewResolveableToPoint point={origin} data_ref_path=origin_path />
+
+            <p>"columns: "</p>
+            <ResolvableToNumberView n=cols data_ref_path=cols_path />
+
+            <p>"column spacing: "</p>
+            <ResolvableToNumberView n=col_spacing data_ref_path=col_spacing_path />
+
+            <p>"rows: "</p>
+            <ResolvableToNumberView n=rows data_ref_path=rows_path />
+
+            <p>"row spacing: "</p>
+            <ResolvableToNumberView n=row_spacing data_ref_path=row_spacing_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewAnnulus(
+    cx: Scope,
+    center: RwSignal<ResolvableTo<PointSignal>>,
+    inner_radius: RwSignal<ResolvableTo<NumberSignal>>,
+    outer_radius: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let prop_path = move |prop_name: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::PropName(prop_name));
+        store_value(cx, path)
+    };
+    let center_path = prop_path("center");
+    let inner_radius_path = prop_path("inner_radius");
+    let outer_radius_path = prop_path("outer_radius");
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Annulus"</p>
+
+            <p>"center: "</p>
+            <InnerStepViewResolveableToPoint point={center} data_ref_path=center_path />
+
+            <p>"inner radius: "</p>
+            <ResolvableToNumberView n=inner_radius data_ref_path=inner_radius_path />
+
+            <p>"outer radius: "</p>
+            <ResolvableToNumberView n=outer_radius data_ref_path=outer_radius_path />
+        </div>
+    }
+}
+
+/// Editor for steps defined by just two points
+#[component]
+fn InnerStepViewTwoPoints(
+    cx: Scope,
+    title: &'static str,
+    names: [&'static str; 2],
+    points: [RwSignal<ResolvableTo<PointSignal>>; 2],
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let point_views = names
+        .into_iter()
+        .zip(points)
+        .map(|(name, point)| {
+            let mut path = data_ref_path.get();
+            path.push(DataRefPathEl::PropName(name));
+            let path = store_value(cx, path);
+            view! { cx,
+                <p>{name} ": "</p>
+                <InnerStepViewResolveableToPoint point data_ref_path=path />
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>{title}</p>
+            {point_views}
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewDrawArrow(
+    cx: Scope,
+    start: RwSignal<ResolvableTo<PointSignal>>,
+    end: RwSignal<ResolvableTo<PointSignal>>,
+    head_size: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let prop_path = move |prop_name: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::PropName(prop_name));
+        store_value(cx, path)
+    };
+    let start_path = prop_path("start");
+    let end_path = prop_path("end");
+    let head_size_path = prop_path("head_size");
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Draw Arrow"</p>
+
+            <p>"start: "</p>
+            <InnerStepViewResolveableToPoint point={start} data_ref_path=start_path />
+
+            <p>"end: "</p>
+            <InnerStepViewResolveableToPoint point={end} data_ref_path=end_path />
+
+            <p>"head size: "</p>
+            <ResolvableToNumberView n=head_size data_ref_path=head_size_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewDrawRect(
+    cx: Scope,
+    origin: RwSignal<ResolvableTo<PointSignal>>,
+    width: RwSignal<ResolvableTo<NumberSignal>>,
+    height: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let prop_path = move |prop_name: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::PropName(prop_name));
+        store_value(cx, path)
+    };
+    let origin_path = prop_path("origin");
+    let width_path = prop_path("width");
+    let height_path = prop_path("height");
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Draw Rect"</p>
+
+            <p>"origin: "</p>
+            <InnerStepViewResolveableToPoint point={origin} data_ref_path=origin_path />
+
+            <p>"width: "</p>
+            <ResolvableToNumberView n=width data_ref_path=width_path />
+
+            <p>"height: "</p>
+            <ResolvableToNumberView n=height data_ref_path=height_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewDrawRoundedRect(
+    cx: Scope,
+    origin: RwSignal<ResolvableTo<PointSignal>>,
+    width: RwSignal<ResolvableTo<NumberSignal>>,
+    height: RwSignal<ResolvableTo<NumberSignal>>,
+    radius: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let prop_path = move |prop_name: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::PropName(prop_name));
+        store_value(cx, path)
+    };
+    let origin_path = prop_path("origin");
+    let width_path = prop_path("width");
+    let height_path = prop_path("height");
+    let radius_path = prop_path("radius");
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Draw Rounded Rect"</p>
+
+            <p>"origin: "</p>
+            <InnerStepViewResolveableToPoint point={origin} data_ref_path=origin_path />
+
+            <p>"width: "</p>
+            <ResolvableToNumberView n=width data_ref_path=width_path />
+
+            <p>"height: "</p>
+            <ResolvableToNumberView n=height data_ref_path=height_path />
+
+            <p>"corner radius: "</p>
+            <ResolvableToNumberView n=radius data_ref_path=radius_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewDrawArc(
+    cx: Scope,
+    title: &'static str,
+    center: RwSignal<ResolvableTo<PointSignal>>,
+    radius: RwSignal<ResolvableTo<NumberSignal>>,
+    start_angle: RwSignal<ResolvableTo<AngleSignal>>,
+    end_angle: RwSignal<ResolvableTo<AngleSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let prop_path = move |prop_name: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::PropName(prop_name));
+        store_value(cx, path)
+    };
+    let center_path = prop_path("center");
+    let radius_path = prop_path("radius");
+    let start_angle_path = prop_path("start_angle");
+    let end_angle_path = prop_path("end_angle");
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>{title}</p>
+
+            <p>"center: "</p>
+            <InnerStepViewResolveableToPoint point={center} data_ref_path=center_path />
+
+            <p>"radius: "</p>
+            <ResolvableToNumberView n=radius data_ref_path=radius_path />
+
+            <p>{move || format!("start angle ({}): ", angle_unit(cx).name())}</p>
+            <ResolvableToAngleView n=start_angle stored=AngleUnit::Radians data_ref_path=start_angle_path />
+
+            <p>{move || format!("end angle ({}): ", angle_unit(cx).name())}</p>
+            <ResolvableToAngleView n=end_angle stored=AngleUnit::Radians data_ref_path=end_angle_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewFreehand(
+    cx: Scope,
+    step_id: usize,
+    points: RwSignal<Vec<Point>>,
+    smooth: RwSignal<bool>,
+) -> impl IntoView {
+    let tolerance = create_rw_signal(cx, SKETCH_TOLERANCE * 2.0);
+
+    // Swaps the stroke for a spline in place, so it keeps its id and style
+    let convert_to_spline = move |_| {
+        let spline = StepData::Spline {
+            points: create_rw_signal(
+                cx,
+                points
+                    .with(|p| fit_spline(p))
+                    .into_iter()
+                    .map(|p| create_rw_signal(cx, ResolvableTo::T(PointSignal::literal(cx, p))))
+                    .collect(),
+            ),
+        };
+        use_context::<RwSignal<Vec<Step>>>(cx)
+            .unwrap()
+            .update(|steps| {
+                if let Some(step) = steps.iter_mut().find(|s| s.id == step_id) {
+                    step.data = spline;
+                }
+            });
+    };
+
+    let simplify = move |_| {
+        points.update(|p| *p = simplify_polyline(p, tolerance.get()));
+    };
+    // Drops every other sample, keeping both ends
+    let decimate = move |_| {
+        points.update(|p| {
+            if p.len() <= 2 {
+                return;
+            }
+            let last = p[p.len() - 1];
+            let mut kept: Vec<Point> = p.iter().step_by(2).copied().collect();
+            if kept.last() != Some(&last) {
+                kept.push(last);
+            }
+            *p = kept;
+        });
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Freehand"</p>
+            <p>{move || points.with(|p| p.len())} " points"</p>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || smooth.get()
+                    on:change=move |_| smooth.update(|s| *s = !*s)
+                />
+                " Smooth"
+            </label>
+            <div class="flex flex-row mt-2">
+                <button class="border-2 border-gray-800 px-1" on:click=simplify>"Simplify"</button>
+                <input
+                    type="number"
+                    step="0.05"
+                    class="w-16 ml-2"
+                    prop:value=move || tolerance.get().to_string()
+                    on:change=move |ev| {
+                        if let Ok(v) = event_target_value(&ev).parse::<f64>() {
+                            if v > 0.0 {
+                                tolerance.set(v);
+                            }
+                        }
+                    }
+                />
+            </div>
+            <button class="border-2 border-gray-800 mt-2" on:click=decimate>"Decimate"</button>
+            <button class="border-2 border-gray-800 mt-2" prop:disabled=move || points.with(|p| p.len() < 2) on:click=convert_to_spline>
+                "Convert to spline"
+            </button>
+        </div>
+    }
+}
+
+/// Text input for an expression in `var`, previewing the draft's value at each of `at`
+#[component]
+fn ExprInput(
+    cx: Scope,
+    label: &'static str,
+    value: RwSignal<String>,
+    var: &'static str,
+    at: Rc<dyn Fn() -> Vec<f64>>,
+) -> impl IntoView {
+    let draft = create_rw_signal(cx, String::new());
+    create_effect(cx, move |_| draft.set(value.get()));
+    let preview = move || draft.with(|d| expr_preview(d, var, &at()));
+
+    view! { cx,
+        <div class="flex flex-col">
+            <label class="flex flex-row">
+                {label}
+                <input
+                    type="text"
+                    class="ml-2 grow min-w-0"
+                    prop:value=move || value.get()
+                    on:input=move |ev| draft.set(event_target_value(&ev))
+                    on:change=move |ev| value.set(event_target_value(&ev))
+                />
+            </label>
+            {move || match preview() {
+                Ok(values) => view! { cx, <p class="text-sm text-gray-600">{values}</p> },
+                Err(e) => view! { cx, <p class="text-sm text-red-700">{e}</p> },
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewFunctionPlot(
+    cx: Scope,
+    f: RwSignal<String>,
+    x_min: RwSignal<ResolvableTo<NumberSignal>>,
+    x_max: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut x_min_path = data_ref_path.get();
+    x_min_path.push(DataRefPathEl::PropName("x_min"));
+    let x_min_path = store_value(cx, x_min_path);
+
+    let mut x_max_path = data_ref_path.get();
+    x_max_path.push(DataRefPathEl::PropName("x_max"));
+    let x_max_path = store_value(cx, x_max_path);
+
+    let range: Rc<dyn Fn() -> Vec<f64>> =
+        Rc::new(move || vec![x_min.get().resolve(cx), x_max.get().resolve(cx)]);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Function Plot"</p>
+            <ExprInput label="y = " value=f var="x" at=range />
+
+            <p>"x from: "</p>
+            <ResolvableToNumberView n=x_min data_ref_path=x_min_path />
+            <p>"to: "</p>
+            <ResolvableToNumberView n=x_max data_ref_path=x_max_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewParametricCurve(
+    cx: Scope,
+    x: RwSignal<String>,
+    y: RwSignal<String>,
+    t_min: RwSignal<ResolvableTo<NumberSignal>>,
+    t_max: RwSignal<ResolvableTo<NumberSignal>>,
+    samples: RwSignal<usize>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut t_min_path = data_ref_path.get();
+    t_min_path.push(DataRefPathEl::PropName("t_min"));
+    let t_min_path = store_value(cx, t_min_path);
+
+    let mut t_max_path = data_ref_path.get();
+    t_max_path.push(DataRefPathEl::PropName("t_max"));
+    let t_max_path = store_value(cx, t_max_path);
+
+    let expr_field = move |label: &'static str, value: RwSignal<String>| {
+        let range: Rc<dyn Fn() -> Vec<f64>> =
+            Rc::new(move || vec![t_min.get().resolve(cx), t_max.get().resolve(cx)]);
+        view! { cx, <ExprInput label value var="t" at=range /> }
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Parametric Curve"</p>
+            {expr_field("x(t) = ", x)}
+            {expr_field("y(t) = ", y)}
+
+            <p>"t from: "</p>
+            <ResolvableToNumberView n=t_min data_ref_path=t_min_path />
+            <p>"to: "</p>
+            <ResolvableToNumberView n=t_max data_ref_path=t_max_path />
+
+            <label class="flex flex-row mt-2">
+                "samples: "
+                <input
+                    type="number"
+                    class="w-20 ml-2"
+                    prop:value=move || samples.get().to_string()
+                    on:change=move |ev| {
+                        if let Ok(n) = event_target_value(&ev).parse::<usize>() {
+                            samples.set(n.max(2));
+                        }
+                    }
+                />
+            </label>
+        </div>
+    }
+}
+
+/// Picks a step with a single closed outline that doesn't depend on `step_id`
+#[component]
+fn ShapeSelect(cx: Scope, step_id: usize, shape: RwSignal<Option<DataRef>>) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let selected_id = move || shape.get().and_then(|r| r.step_id());
+
+    view! { cx,
+        <select on:change=move |ev| {
+            shape.set(event_target_value(&ev).parse::<usize>().ok().map(|id| {
+                DataRef(vec![DataRefPathEl::Step, DataRefPathEl::WithId(id)])
+            }));
+        }>
+            <option value="none" selected=move || selected_id().is_none()>"(none)"</option>
+            {move || steps.with(|steps| {
+                steps
+                    .iter()
+                    .filter(|s| {
+                        s.id != step_id
+                            && !depends_on(steps, s.id, step_id)
+                            && s.outline(cx).is_some()
+                    })
+                    .map(|s| {
+                        let id = s.id;
+                        view! { cx,
+                            <option value=id.to_string() selected=move || selected_id() == Some(id)>
+                                "Step #" {id}
+                            </option>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })}
+        </select>
+    }
+}
+
+#[component]
+fn InnerStepViewShapeBoolean(
+    cx: Scope,
+    step_id: usize,
+    op: RwSignal<BoolOp>,
+    a: RwSignal<Option<DataRef>>,
+    b: RwSignal<Option<DataRef>>,
+) -> impl IntoView {
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Shape Boolean"</p>
+
+            <select on:change=move |ev| {
+                let value = event_target_value(&ev);
+                if let Some(o) = BoolOp::ALL.into_iter().find(|o| o.as_str() == value) {
+                    op.set(o);
+                }
+            }>
+                {BoolOp::ALL
+                    .into_iter()
+                    .map(|o| view! { cx,
+                        <option value=o.as_str() selected=move || op.get() == o>{o.as_str()}</option>
+                    })
+                    .collect::<Vec<_>>()}
+            </select>
+
+            <p>"a: "</p>
+            <ShapeSelect step_id shape=a />
+
+            <p>"b: "</p>
+            <ShapeSelect step_id shape=b />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewClip(cx: Scope, step_id: usize, shape: RwSignal<Option<DataRef>>) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let selected_id = move || shape.get().and_then(|r| r.step_id());
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Clip to Shape"</p>
+            <select on:change=move |ev| {
+                shape.set(event_target_value(&ev).parse::<usize>().ok().map(|id| {
+                    DataRef(vec![DataRefPathEl::Step, DataRefPathEl::WithId(id)])
+                }));
+            }>
+                <option value="none" selected=move || selected_id().is_none()>"(none)"</option>
+                {move || steps.with(|steps| {
+                    steps
+                        .iter()
+                        .filter(|s| s.id != step_id && !s.trashed.get() && s.is_closed_shape())
+                        .map(|s| {
+                            let id = s.id;
+                            view! { cx,
+                                <option value=id.to_string() selected=move || selected_id() == Some(id)>
+                                    "Step #" {id}
+                                </option>
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                })}
+            </select>
+        </div>
+    }
+}
+
+/// Picks a line step (other than `step_id` and anything depending on it) to derive from
+#[component]
+fn LineSelect(cx: Scope, step_id: usize, line: RwSignal<Option<DataRef>>) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let selected_id = move || line.get().and_then(|r| r.step_id());
+
+    view! { cx,
+        <select on:change=move |ev| {
+            line.set(event_target_value(&ev).parse::<usize>().ok().map(|id| {
+                DataRef(vec![DataRefPathEl::Step, DataRefPathEl::WithId(id)])
+            }));
+        }>
+            <option value="none" selected=move || selected_id().is_none()>"(none)"</option>
+            {move || steps.with(|steps| {
+                steps
+                    .iter()
+                    .filter(|s| s.id != step_id && s.is_line() && !depends_on(steps, s.id, step_id))
+                    .map(|s| {
+                        let id = s.id;
+                        view! { cx,
+                            <option value=id.to_string() selected=move || selected_id() == Some(id)>
+                                "Step #" {id}
+                            </option>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })}
+        </select>
+    }
+}
+
+#[component]
+fn CircleSelect(cx: Scope, step_id: usize, circle: RwSignal<Option<DataRef>>) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let selected_id = move || circle.get().and_then(|r| r.step_id());
+
+    view! { cx,
+        <select on:change=move |ev| {
+            circle.set(event_target_value(&ev).parse::<usize>().ok().map(|id| {
+                DataRef(vec![DataRefPathEl::Step, DataRefPathEl::WithId(id)])
+            }));
+        }>
+            <option value="none" selected=move || selected_id().is_none()>"(none)"</option>
+            {move || steps.with(|steps| {
+                steps
+                    .iter()
+                    .filter(|s| {
+                        s.id != step_id
+                            && matches!(s.data, StepData::DrawArc { .. } | StepData::DrawSector { .. })
+                            && !depends_on(steps, s.id, step_id)
+                    })
+                    .map(|s| {
+                        let id = s.id;
+                        view! { cx,
+                            <option value=id.to_string() selected=move || selected_id() == Some(id)>
+                                "Step #" {id}
+                            </option>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })}
+        </select>
+    }
+}
+
+#[component]
+fn InnerStepViewTangent(
+    cx: Scope,
+    step_id: usize,
+    circle: RwSignal<Option<DataRef>>,
+    from: RwSignal<ResolvableTo<PointSignal>>,
+    other_side: RwSignal<bool>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut from_path = data_ref_path.get();
+    from_path.push(DataRefPathEl::PropName("from"));
+    let from_path = store_value(cx, from_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Tangent"</p>
+
+            <p>"to circle: "</p>
+            <CircleSelect step_id circle />
+
+            <p>"from: "</p>
+            <InnerStepViewResolveableToPoint point={from} data_ref_path=from_path />
+
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || other_side.get()
+                    on:change=move |_| other_side.update(|s| *s = !*s)
+                />
+                " Other side"
+            </label>
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewPerpendicular(
+    cx: Scope,
+    step_id: usize,
+    line: RwSignal<Option<DataRef>>,
+    through: RwSignal<ResolvableTo<PointSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut through_path = data_ref_path.get();
+    through_path.push(DataRefPathEl::PropName("through"));
+    let through_path = store_value(cx, through_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Perpendicular"</p>
+
+            <p>"to line: "</p>
+            <LineSelect step_id line />
+
+            <p>"through: "</p>
+            <InnerStepViewResolveableToPoint point={through} data_ref_path=through_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewParallelOffset(
+    cx: Scope,
+    step_id: usize,
+    line: RwSignal<Option<DataRef>>,
+    distance: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut distance_path = data_ref_path.get();
+    distance_path.push(DataRefPathEl::PropName("distance"));
+    let distance_path = store_value(cx, distance_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Parallel Offset"</p>
+
+            <p>"of line: "</p>
+            <LineSelect step_id line />
+
+            <p>"distance: "</p>
+            <ResolvableToNumberView n=distance data_ref_path=distance_path />
+        </div>
+    }
+}
+
+/// Picks a step made of paths that doesn't depend on `step_id`
+#[component]
+fn PathSelect(cx: Scope, step_id: usize, source: RwSignal<Option<DataRef>>) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let selected_id = move || source.get().and_then(|r| r.step_id());
+
+    view! { cx,
+        <select on:change=move |ev| {
+            source.set(event_target_value(&ev).parse::<usize>().ok().map(|id| {
+                DataRef(vec![DataRefPathEl::Step, DataRefPathEl::WithId(id)])
+            }));
+        }>
+            <option value="none" selected=move || selected_id().is_none()>"(none)"</option>
+            {move || steps.with(|steps| {
+                steps
+                    .iter()
+                    .filter(|s| {
+                        s.id != step_id
+                            && !depends_on(steps, s.id, step_id)
+                            && s.paths(cx).is_some()
+                    })
+                    .map(|s| {
+                        let id = s.id;
+                        view! { cx,
+                            <option value=id.to_string() selected=move || selected_id() == Some(id)>
+                                "Step #" {id}
+                            </option>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })}
+        </select>
+    }
+}
+
+#[component]
+fn InnerStepViewOffsetCurve(
+    cx: Scope,
+    step_id: usize,
+    source: RwSignal<Option<DataRef>>,
+    distance: RwSignal<ResolvableTo<NumberSignal>>,
+    join: RwSignal<LineJoin>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut distance_path = data_ref_path.get();
+    distance_path.push(DataRefPathEl::PropName("distance"));
+    let distance_path = store_value(cx, distance_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Offset Curve"</p>
+
+            <p>"of: "</p>
+            <PathSelect step_id source />
+
+            <p>"distance: "</p>
+            <ResolvableToNumberView n=distance data_ref_path=distance_path />
+
+            <label class="flex flex-row justify-between">
+                "corners"
+                <select on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    if let Some(j) = LineJoin::ALL.into_iter().find(|j| j.as_str() == value) {
+                        join.set(j);
+                    }
+                }>
+                    {LineJoin::ALL
+                        .into_iter()
+                        .map(|j| view! { cx,
+                            <option value=j.as_str() selected=move || join.get() == j>{j.as_str()}</option>
+                        })
+                        .collect::<Vec<_>>()}
+                </select>
+            </label>
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewTrimExtend(
+    cx: Scope,
+    step_id: usize,
+    line: RwSignal<Option<DataRef>>,
+    boundary: RwSignal<Option<DataRef>>,
+    keep_end: RwSignal<bool>,
+) -> impl IntoView {
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Trim / Extend"</p>
+
+            <p>"line: "</p>
+            <LineSelect step_id line />
+
+            <p>"to meet: "</p>
+            <LineSelect step_id line=boundary />
+
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || keep_end.get()
+                    on:change=move |_| keep_end.update(|k| *k = !*k)
+                />
+                " Keep the other end"
+            </label>
+        </div>
+    }
+}
+
+/// Picks a step (other than `step_id` and anything depending on it) to copy or mirror
+#[component]
+fn SourceSelect(cx: Scope, step_id: usize, source: RwSignal<Option<DataRef>>) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let source_id = move || source.get().and_then(|r| r.step_id());
+
+    view! { cx,
+        <select on:change=move |ev| {
+            source.set(event_target_value(&ev).parse::<usize>().ok().map(|id| {
+                DataRef(vec![DataRefPathEl::Step, DataRefPathEl::WithId(id)])
+            }));
+        }>
+            <option value="none" selected=move || source_id().is_none()>"(none)"</option>
+            {move || steps.with(|steps| {
+                steps
+                    .iter()
+                    .filter(|s| {
+                        s.id != step_id
+                            && !matches!(s.data, StepData::Clip { .. })
+                            && !depends_on(steps, s.id, step_id)
+                    })
+                    .map(|s| {
+                        let id = s.id;
+                        view! { cx,
+                            <option value=id.to_string() selected=move || source_id() == Some(id)>
+                                "Step #" {id}
+                            </option>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })}
+        </select>
+    }
+}
+
+#[component]
+fn InnerStepViewBoundingBox(
+    cx: Scope,
+    step_id: usize,
+    sources: RwSignal<Vec<RwSignal<Option<DataRef>>>>,
+) -> impl IntoView {
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Bounding Box"</p>
+            {move || {
+                let count = sources.with(|s| s.len());
+                sources
+                    .get()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, source)| {
+                        view! { cx,
+                            <div class="flex flex-row justify-between mt-2">
+                                <SourceSelect step_id source />
+                                <button
+                                    class="border-2 border-gray-800 px-1 disabled:opacity-50"
+                                    prop:disabled={count <= 1}
+                                    on:click=move |_| sources.update(|s| {
+                                        s.remove(i);
+                                    })
+                                >
+                                    "Remove"
+                                </button>
+                            </div>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            }}
+            <button
+                class="border-2 border-gray-800 mt-2"
+                on:click=move |_| sources.update(|s| s.push(create_rw_signal(cx, None)))
+            >
+                "+ Step"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewMirror(
+    cx: Scope,
+    step_id: usize,
+    source: RwSignal<Option<DataRef>>,
+    axis: RwSignal<Option<DataRef>>,
+) -> impl IntoView {
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Mirror"</p>
+
+            <p>"step: "</p>
+            <SourceSelect step_id source />
+
+            <p>"across line: "</p>
+            <LineSelect step_id line=axis />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewRotateCopy(
+    cx: Scope,
+    step_id: usize,
+    source: RwSignal<Option<DataRef>>,
+    center: RwSignal<ResolvableTo<PointSignal>>,
+    angle: RwSignal<ResolvableTo<AngleSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut center_path = data_ref_path.get();
+    center_path.push(DataRefPathEl::PropName("center"));
+    let center_path = store_value(cx, center_path);
+
+    let mut angle_path = data_ref_path.get();
+    angle_path.push(DataRefPathEl::PropName("angle"));
+    let angle_path = store_value(cx, angle_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Rotate Copy"</p>
+
+            <p>"step: "</p>
+            <SourceSelect step_id source />
+
+            <p>"center: "</p>
+            <InnerStepViewResolveableToPoint point={center} data_ref_path=center_path />
+
+            <p>{move || format!("angle ({}): ", angle_unit(cx).name())}</p>
+            <ResolvableToAngleView n=angle stored=AngleUnit::Degrees data_ref_path=angle_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewTranslateCopy(
+    cx: Scope,
+    step_id: usize,
+    source: RwSignal<Option<DataRef>>,
+    dx: RwSignal<ResolvableTo<NumberSignal>>,
+    dy: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut dx_path = data_ref_path.get();
+    dx_path.push(DataRefPathEl::PropName("dx"));
+    let dx_path = store_value(cx, dx_path);
+
+    let mut dy_path = data_ref_path.get();
+    dy_path.push(DataRefPathEl::PropName("dy"));
+    let dy_path = store_value(cx, dy_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Translate Copy"</p>
+
+            <p>"step: "</p>
+            <SourceSelect step_id source />
+
+            <p>"dx: "</p>
+            <ResolvableToNumberView n=dx data_ref_path=dx_path />
+
+            <p>"dy: "</p>
+            <ResolvableToNumberView n=dy data_ref_path=dy_path />
+
+            <p>"by vector: "</p>
+            <VectorSelect dx dy />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewTransformCopy(
+    cx: Scope,
+    step_id: usize,
+    source: RwSignal<Option<DataRef>>,
+    transform: RwSignal<Option<DataRef>>,
+) -> impl IntoView {
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+    let transforms = move || {
+        datas.with(|datas| {
+            datas
+                .iter()
+                .filter(|d| !d.trashed.get() && matches!(d.data, DataData::Transform { .. }))
+                .map(|d| d.id)
+                .collect::<Vec<_>>()
+        })
+    };
+    let transform_id = move || transform.get().and_then(|r| r.data_id());
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Transform Copy"</p>
+
+            <p>"step: "</p>
+            <SourceSelect step_id source />
+
+            <p>"transform: "</p>
+            <select on:change=move |ev| {
+                transform.set(event_target_value(&ev).parse::<usize>().ok().map(|id| {
+                    DataRef(vec![DataRefPathEl::Data, DataRefPathEl::WithId(id)])
+                }));
+            }>
+                <option value="none" selected=move || transform_id().is_none()>"(none)"</option>
+                {move || transforms().into_iter().map(|id| view! { cx,
+                    <option value=id.to_string() selected=move || transform_id() == Some(id)>
+                        {format!("Data #{}", id)}
+                    </option>
+                }).collect::<Vec<_>>()}
+            </select>
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewLinearArray(
+    cx: Scope,
+    step_id: usize,
+    source: RwSignal<Option<DataRef>>,
+    count: RwSignal<ResolvableTo<NumberSignal>>,
+    dx: RwSignal<ResolvableTo<NumberSignal>>,
+    dy: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut count_path = data_ref_path.get();
+    count_path.push(DataRefPathEl::PropName("count"));
+    let count_path = store_value(cx, count_path);
+
+    let mut dx_path = data_ref_path.get();
+    dx_path.push(DataRefPathEl::PropName("dx"));
+    let dx_path = store_value(cx, dx_path);
+
+    let mut dy_path = data_ref_path.get();
+    dy_path.push(DataRefPathEl::PropName("dy"));
+    let dy_path = store_value(cx, dy_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Linear Array"</p>
+
+            <p>"step: "</p>
+            <SourceSelect step_id source />
+
+            <p>"count: "</p>
+            <ResolvableToNumberView n=count data_ref_path=count_path />
+
+            <p>"dx: "</p>
+            <ResolvableToNumberView n=dx data_ref_path=dx_path />
+
+            <p>"dy: "</p>
+            <ResolvableToNumberView n=dy data_ref_path=dy_path />
+
+            <p>"by vector: "</p>
+            <VectorSelect dx dy />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewPolarArray(
+    cx: Scope,
+    step_id: usize,
+    source: RwSignal<Option<DataRef>>,
+    center: RwSignal<ResolvableTo<PointSignal>>,
+    count: RwSignal<ResolvableTo<NumberSignal>>,
+    sweep: RwSignal<ResolvableTo<AngleSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut center_path = data_ref_path.get();
+    center_path.push(DataRefPathEl::PropName("center"));
+    let center_path = store_value(cx, center_path);
+
+    let mut count_path = data_ref_path.get();
+    count_path.push(DataRefPathEl::PropName("count"));
+    let count_path = store_value(cx, count_path);
+
+    let mut sweep_path = data_ref_path.get();
+    sweep_path.push(DataRefPathEl::PropName("sweep"));
+    let sweep_path = store_value(cx, sweep_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Polar Array"</p>
+
+            <p>"step: "</p>
+            <SourceSelect step_id source />
+
+            <p>"center: "</p>
+            <InnerStepViewResolveableToPoint point={center} data_ref_path=center_path />
+
+            <p>"count: "</p>
+            <ResolvableToNumberView n=count data_ref_path=count_path />
+
+            <p>{move || format!("sweep ({}): ", angle_unit(cx).name())}</p>
+            <ResolvableToAngleView n=sweep stored=AngleUnit::Degrees data_ref_path=sweep_path />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewIntersection(
+    cx: Scope,
+    step_id: usize,
+    line_a: RwSignal<Option<DataRef>>,
+    line_b: RwSignal<Option<DataRef>>,
+) -> impl IntoView {
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Intersection"</p>
+
+            <p>"line a: "</p>
+            <LineSelect step_id line=line_a />
+
+            <p>"line b: "</p>
+            <LineSelect step_id line=line_b />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewProjection(
+    cx: Scope,
+    step_id: usize,
+    point: RwSignal<ResolvableTo<PointSignal>>,
+    line: RwSignal<Option<DataRef>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut point_path = data_ref_path.get();
+    point_path.push(DataRefPathEl::PropName("point"));
+    let point_path = store_value(cx, point_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Projection"</p>
+
+            <p>"of point: "</p>
+            <InnerStepViewResolveableToPoint point data_ref_path=point_path />
+
+            <p>"onto line: "</p>
+            <LineSelect step_id line />
+        </div>
+    }
+}
+
+#[component]
+fn InnerStepViewPointAlongLine(
+    cx: Scope,
+    step_id: usize,
+    line: RwSignal<Option<DataRef>>,
+    t: RwSignal<ResolvableTo<NumberSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let mut t_path = data_ref_path.get();
+    t_path.push(DataRefPathEl::PropName("t"));
+    let t_path = store_value(cx, t_path);
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Point Along Line"</p>
+
+            <p>"line: "</p>
+            <LineSelect step_id line />
+
+            <p>"t (0 to 1): "</p>
+            <ResolvableToNumberView n=t data_ref_path=t_path />
+        </div>
+    }
+}
+
+#[component]
+fn ColorInput(cx: Scope, value: Signal<String>, set: SignalSetter<String>) -> impl IntoView {
+    view! { cx,
+        <input
+            type="color"
+            class="w-8 h-6"
+            prop:value=move || value.get()
+            on:input=move |ev| set.set(event_target_value(&ev))
+        />
+    }
+}
+
+/// A color picker, or a pick of color data to share with other steps
+#[component]
+fn ResolvableColorView(
+    cx: Scope,
+    value: Signal<ResolvableTo<ColorSignal>>,
+    set: SignalSetter<ResolvableTo<ColorSignal>>,
+) -> impl IntoView {
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+    let color_datas = move || {
+        datas.with(|datas| {
+            datas
+                .iter()
+                .filter(|d| !d.trashed.get() && matches!(d.data, DataData::Color(_)))
+                .map(|d| d.id)
+                .collect::<Vec<_>>()
+        })
+    };
+    let data_id = move || match value.get() {
+        ResolvableTo::Ref(r) => r.data_id(),
+        ResolvableTo::T(_) | ResolvableTo::Expr(_) => None,
+    };
+
+    view! { cx,
+        <div class="flex flex-row">
+            {move || match value.get() {
+                ResolvableTo::T(c) => {
+                    let (value, set) = create_slice(cx, c, |c| c.clone(), |c, v| *c = v);
+                    view! { cx, <ColorInput value set /> }.into_view(cx)
+                }
+                shared => view! { cx,
+                    <div
+                        class="w-8 h-6 border border-gray-400"
+                        style=move || format!("background-color: {}", shared.resolve_color(cx))
+                    ></div>
+                }
+                .into_view(cx),
+            }}
+            <select class="text-sm ml-1" on:change=move |ev| {
+                match event_target_value(&ev).parse::<usize>() {
+                    Ok(id) => set.set(ResolvableTo::Ref(DataRef(vec![
+                        DataRefPathEl::Data,
+                        DataRefPathEl::WithId(id),
+                    ]))),
+                    // Keeps the color it had, just no longer shared
+                    Err(_) => set.set(ResolvableTo::literal(cx, &value.get().resolve_color(cx))),
+                }
+            }>
+                <option value="own" selected=move || data_id().is_none()>"(own)"</option>
+                {move || color_datas().into_iter().map(|id| view! { cx,
+                    <option value=id.to_string() selected=move || data_id() == Some(id)>
+                        {format!("Data #{}", id)}
+                    </option>
+                }).collect::<Vec<_>>()}
+            </select>
+        </div>
+    }
+}
+
+#[component]
+fn FillView(cx: Scope, step: Step) -> impl IntoView {
+    let style = step.style;
+    let step_path = |prop: &'static str| {
+        store_value(
+            cx,
+            vec![
+                DataRefPathEl::Step,
+                DataRefPathEl::WithId(step.id),
+                DataRefPathEl::PropName(prop),
+            ],
+        )
+    };
+
+    let set_fill_kind = move |kind: String| {
+        // New gradients span the step's first and last snap points
+        let anchors = step.snap_points(cx);
+        let anchor = |r: Option<&DataRef>| {
+            let p = r
+                .map(|r| ResolveToPoint::resolve(r, cx))
+                .unwrap_or_default();
+            create_rw_signal(cx, ResolvableTo::T(PointSignal::literal(cx, p)))
+        };
+
+        let fill = match kind.as_str() {
+            "solid" => Fill::Solid(ResolvableTo::literal(cx, "#93c5fd")),
+            "linear" => Fill::LinearGradient {
+                start: anchor(anchors.first()),
+                end: anchor(anchors.last()),
+                from: "#93c5fd".to_string(),
+                to: "#1d4ed8".to_string(),
+            },
+            "radial" => Fill::RadialGradient {
+                center: anchor(anchors.first()),
+                radius: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 10.0))),
+                from: "#93c5fd".to_string(),
+                to: "#1d4ed8".to_string(),
+            },
+            _ => Fill::None,
+        };
+        style.update(|s| s.fill = fill);
+    };
+
+    let (gradient_from, set_gradient_from) = create_slice(
+        cx,
+        style,
+        |s| match &s.fill {
+            Fill::LinearGradient { from, .. } | Fill::RadialGradient { from, .. } => from.clone(),
+            _ => String::new(),
+        },
+        |s, v| {
+            if let Fill::LinearGradient { from, .. } | Fill::RadialGradient { from, .. } =
+                &mut s.fill
+            {
+                *from = v;
+            }
+        },
+    );
+    let (gradient_to, set_gradient_to) = create_slice(
+        cx,
+        style,
+        |s| match &s.fill {
+            Fill::LinearGradient { to, .. } | Fill::RadialGradient { to, .. } => to.clone(),
+            _ => String::new(),
+        },
+        |s, v| {
+            if let Fill::LinearGradient { to, .. } | Fill::RadialGradient { to, .. } = &mut s.fill {
+                *to = v;
+            }
+        },
+    );
+
+    let fill_kind = move || match style.with(|s| s.fill.clone()) {
+        Fill::None => "none",
+        Fill::Solid(_) => "solid",
+        Fill::LinearGradient { .. } => "linear",
+        Fill::RadialGradient { .. } => "radial",
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <label>
+                "fill: "
+                <select on:change=move |ev| set_fill_kind(event_target_value(&ev))>
+                    <option value="none" selected=move || fill_kind() == "none">"None"</option>
+                    <option value="solid" selected=move || fill_kind() == "solid">"Solid"</option>
+                    <option value="linear" selected=move || fill_kind() == "linear">"Linear Gradient"</option>
+                    <option value="radial" selected=move || fill_kind() == "radial">"Radial Gradient"</option>
+                </select>
+            </label>
+            {move || match style.with(|s| s.fill.clone()) {
+                Fill::None => view! { cx, <div></div> }.into_view(cx),
+                Fill::Solid(color) => {
+                    let (value, set) = create_slice(
+                        cx,
+                        style,
+                        move |s| match &s.fill {
+                            Fill::Solid(c) => c.clone(),
+                            _ => color.clone(),
+                        },
+                        |s, v| s.fill = Fill::Solid(v),
+                    );
+                    view! { cx, <ResolvableColorView value set /> }.into_view(cx)
+                }
+                Fill::LinearGradient { start, end, .. } => view! { cx,
+                    <div class="flex flex-col">
+                        <div class="flex flex-row">
+                            <ColorInput value=gradient_from set=set_gradient_from />
+                            <ColorInput value=gradient_to set=set_gradient_to />
+                        </div>
+                        <p>"from: "</p>
+                        <InnerStepViewResolveableToPoint point=start data_ref_path=step_path("fill_start") />
+                        <p>"to: "</p>
+                        <InnerStepViewResolveableToPoint point=end data_ref_path=step_path("fill_end") />
+                    </div>
+                }
+                .into_view(cx),
+                Fill::RadialGradient { center, radius, .. } => view! { cx,
+                    <div class="flex flex-col">
+                        <div class="flex flex-row">
+                            <ColorInput value=gradient_from set=set_gradient_from />
+                            <ColorInput value=gradient_to set=set_gradient_to />
+                        </div>
+                        <p>"center: "</p>
+                        <InnerStepViewResolveableToPoint point=center data_ref_path=step_path("fill_center") />
+                        <div class="flex flex-row">
+                            <p>"radius: "</p>
+                            <ResolvableToNumberView n=radius data_ref_path=step_path("fill_radius") />
+                        </div>
+                    </div>
+                }
+                .into_view(cx),
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn ShadowView(cx: Scope, style: RwSignal<StepStyle>) -> impl IntoView {
+    let (color, set_color) = create_slice(
+        cx,
+        style,
+        |s| {
+            s.shadow
+                .as_ref()
+                .map(|s| s.color.clone())
+                .unwrap_or_default()
+        },
+        |s, v| {
+            if let Some(shadow) = &mut s.shadow {
+                shadow.color = v;
+            }
+        },
+    );
+
+    let number_field = move |label: &'static str,
+                             get: fn(&Shadow) -> f64,
+                             set: fn(&mut Shadow, f64)| {
+        view! { cx,
+            <label>
+                {label}
+                <input
+                    type="number"
+                    class="w-16"
+                    prop:value=move || style.with(|s| s.shadow.as_ref().map(get).unwrap_or_default().to_string())
+                    on:change=move |ev| {
+                        if let Ok(v) = event_target_value(&ev).parse::<f64>() {
+                            style.update(|s| {
+                                if let Some(shadow) = &mut s.shadow {
+                                    set(shadow, v);
+                                }
+                            });
+                        }
+                    }
+                />
+            </label>
+        }
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || style.with(|s| s.shadow.is_some())
+                    on:change=move |_| style.update(|s| {
+                        s.shadow = match s.shadow {
+                            Some(_) => None,
+                            None => Some(Shadow::default()),
+                        };
+                    })
+                />
+                " Shadow"
+            </label>
+            {move || style.with(|s| s.shadow.is_some()).then(|| view! { cx,
+                <div class="flex flex-col ml-3">
+                    {number_field("blur: ", |s| s.blur, |s, v| s.blur = v.max(0.0))}
+                    {number_field("dx: ", |s| s.offset_x, |s, v| s.offset_x = v)}
+                    {number_field("dy: ", |s| s.offset_y, |s, v| s.offset_y = v)}
+                    <ColorInput value=color set=set_color />
+                </div>
+            })}
+        </div>
+    }
+}
+
+#[component]
+fn LineEndMarkersView(cx: Scope, style: RwSignal<StepStyle>) -> impl IntoView {
+    let marker_select = move |get: fn(&StepStyle) -> LineEndMarker,
+                              set: fn(&mut StepStyle, LineEndMarker)| {
+        view! { cx,
+            <select on:change=move |ev| {
+                let value = event_target_value(&ev);
+                if let Some(m) = LineEndMarker::ALL.into_iter().find(|m| m.as_str() == value) {
+                    style.update(|s| set(s, m));
+                }
+            }>
+                {LineEndMarker::ALL.into_iter().map(|m| view! { cx,
+                    <option value=m.as_str() selected=move || style.with(|s| get(s) == m)>{m.as_str()}</option>
+                }).collect::<Vec<_>>()}
+            </select>
+        }
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <label>"start: " {marker_select(|s| s.start_marker, |s, m| s.start_marker = m)}</label>
+            <label>"end: " {marker_select(|s| s.end_marker, |s, m| s.end_marker = m)}</label>
+            <label>
+                "marker size: "
+                <input
+                    type="number"
+                    class="w-16"
+                    prop:value=move || style.with(|s| s.end_marker_size.to_string())
+                    on:change=move |ev| {
+                        if let Ok(v) = event_target_value(&ev).parse::<f64>() {
+                            style.update(|s| s.end_marker_size = v.max(0.0));
+                        }
+                    }
+                />
+            </label>
+        </div>
+    }
+}
+
+#[component]
+fn StepStyleView(cx: Scope, step: Step) -> impl IntoView {
+    let style = step.style;
+    let expanded = create_rw_signal(cx, false);
+    let settings = use_context::<RwSignal<DocumentSettings>>(cx).unwrap();
+
+    let (stroke, set_stroke) = create_slice(cx, style, |s| s.stroke.clone(), |s, v| s.stroke = v);
+
+    view! { cx,
+        <div class="flex flex-col mt-2">
+            <button class="text-left text-sm text-gray-600" on:click=move |_| expanded.update(|e| *e = !*e)>
+                {move || if expanded.get() { "v Style" } else { "> Style" }}
+            </button>
+            {move || expanded.get().then(|| view! { cx,
+                <div class="flex flex-col">
+                    <label>
+                        "preset: "
+                        <select on:change=move |ev| {
+                            let preset = event_target_value(&ev).parse::<usize>().ok();
+                            style.update(|s| s.preset = preset);
+                        }>
+                            <option value="none" selected=move || style.with(|s| s.preset.is_none())>"(none)"</option>
+                            {move || settings.with(|settings| {
+                                settings
+                                    .style_presets
+                                    .iter()
+                                    .map(|p| {
+                                        let id = p.id;
+                                        view! { cx,
+                                            <option value=id.to_string() selected=move || style.with(|s| s.preset == Some(id))>
+                                                {p.name.clone()}
+                                            </option>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>()
+                            })}
+                        </select>
+                    </label>
+                    <label class="flex flex-row">
+                        "stroke: "
+                        <ResolvableColorView value=stroke set=set_stroke />
+                    </label>
+                    <label>
+                        "width: "
+                        <input
+                            type="number"
+                            class="w-16"
+                            prop:value=move || style.with(|s| s.line_width.to_string())
+                            on:change=move |ev| {
+                                if let Ok(w) = event_target_value(&ev).parse::<f64>() {
+                                    style.update(|s| s.line_width = w.max(0.0));
+                                }
+                            }
+                        />
+                    </label>
+                    <label>
+                        "cap: "
+                        <select on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            if let Some(cap) = LineCap::ALL.into_iter().find(|c| c.as_str() == value) {
+                                style.update(|s| s.line_cap = cap);
+                            }
+                        }>
+                            {LineCap::ALL.into_iter().map(|cap| view! { cx,
+                                <option value=cap.as_str() selected=move || style.with(|s| s.line_cap == cap)>
+                                    {cap.as_str()}
+                                </option>
+                            }).collect::<Vec<_>>()}
+                        </select>
+                    </label>
+                    <label>
+                        "join: "
+                        <select on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            if let Some(join) = LineJoin::ALL.into_iter().find(|j| j.as_str() == value) {
+                                style.update(|s| s.line_join = join);
+                            }
+                        }>
+                            {LineJoin::ALL.into_iter().map(|join| view! { cx,
+                                <option value=join.as_str() selected=move || style.with(|s| s.line_join == join)>
+                                    {join.as_str()}
+                                </option>
+                            }).collect::<Vec<_>>()}
+                        </select>
+                    </label>
+                    <label>
+                        "dash: "
+                        <select on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            if let Some(dash) = LineDash::ALL.into_iter().find(|d| d.as_str() == value) {
+                                style.update(|s| s.dash = dash);
+                            }
+                        }>
+                            {LineDash::ALL.into_iter().map(|dash| view! { cx,
+                                <option value=dash.as_str() selected=move || style.with(|s| s.dash == dash)>
+                                    {dash.as_str()}
+                                </option>
+                            }).collect::<Vec<_>>()}
+                        </select>
+                    </label>
+                    {step.line_ends(cx).is_some().then(|| view! { cx, <LineEndMarkersView style /> })}
+                    {step.is_closed_shape().then(|| view! { cx, <FillView step /> })}
+                    <ShadowView style />
+                </div>
+            })}
+        </div>
+    }
+}
+
+#[component]
+pub fn InnerStepView(cx: Scope, step: Step) -> impl IntoView {
+    move || {
+        let data_ref_path = store_value(
+            cx,
+            vec![DataRefPathEl::Step, DataRefPathEl::WithId(step.id)],
+        );
+
+        match step.data {
+            StepData::DrawPoint {
+                point,
+                marker,
+                size,
+            } => {
+                let point_view = match point.get() {
+                    ResolvableTo::T(p) => view! { cx,
+                        <InnerStepViewDrawPoint sig=point point=p data_ref_path />
+                    }
+                    .into_view(cx),
+                    ResolvableTo::Ref(dr) => {
+                        view! { cx,
+                            <div>"TODO"</div>
+                        <div><RefBreadcrumbs r=dr /></div>
+                        }
+                    }
+                    .into_view(cx),
+                    ResolvableTo::Expr(_) => unreachable!("Only number fields hold expressions"),
+                };
+
+                let mut size_path = data_ref_path.get();
+                size_path.push(DataRefPathEl::PropName("size"));
+                let size_path = store_value(cx, size_path);
+
+                view! { cx,
+                    <div class="flex flex-col">
+                        {point_view}
+                        <div class="flex flex-row mt-2">
+                            <select on:change=move |ev| {
+                                let value = event_target_value(&ev);
+                                if let Some(m) = PointMarker::ALL.into_iter().find(|m| m.as_str() == value) {
+                                    marker.set(m);
+                                }
+                            }>
+                                {PointMarker::ALL.into_iter().map(|m| view! { cx,
+                                    <option value=m.as_str() selected=move || marker.get() == m>{m.as_str()}</option>
+                                }).collect::<Vec<_>>()}
+                            </select>
+                            <p class="ml-3">"size: "</p>
+                            <ResolvableToNumberView n=size data_ref_path=size_path />
+                        </div>
+                    </div>
+                }
+                .into_view(cx)
+            }
+            StepData::DrawLine { start, end } => view! { cx,
+                <InnerStepViewDrawLine start end data_ref_path />
+            }
+            .into_view(cx),
+            StepData::DrawRay { origin, through } => view! { cx,
+                <InnerStepViewTwoPoints
+                    title="Draw Ray"
+                    names={["origin", "through"]}
+                    points={[origin, through]}
+                    data_ref_path
+                />
+            }
+            .into_view(cx),
+            StepData::Angle { a, vertex, b } => view! { cx,
+                <InnerStepViewAngle step_id=step.id a vertex b data_ref_path />
+            }
+            .into_view(cx),
+            StepData::Distance { a, b } => view! { cx,
+                <InnerStepViewDistance step_id=step.id a b data_ref_path />
+            }
+            .into_view(cx),
+            StepData::Midpoint { a, b } => view! { cx,
+                <InnerStepViewTwoPoints
+                    title="Midpoint"
+                    names={["a", "b"]}
+                    points={[a, b]}
+                    data_ref_path
+                />
+            }
+            .into_view(cx),
+            StepData::DrawInfiniteLine { a, b } => view! { cx,
+                <InnerStepViewTwoPoints
+                    title="Draw Infinite Line"
+                    names={["a", "b"]}
+                    points={[a, b]}
+                    data_ref_path
+                />
+            }
+            .into_view(cx),
+            StepData::DrawArrow {
+                start,
+                end,
+                head_size,
+            } => view! { cx,
+                <InnerStepViewDrawArrow start end head_size data_ref_path />
+            }
+            .into_view(cx),
+            StepData::Freehand { points, smooth } => view! { cx,
+                <InnerStepViewFreehand step_id=step.id points smooth />
+            }
+            .into_view(cx),
+            StepData::Intersection { line_a, line_b } => view! { cx,
+                <InnerStepViewIntersection step_id=step.id line_a line_b />
+            }
+            .into_view(cx),
+            StepData::Projection { point, line } => view! { cx,
+                <InnerStepViewProjection step_id=step.id point line data_ref_path />
+            }
+            .into_view(cx),
+            StepData::PointAlongLine { line, t } => view! { cx,
+                <InnerStepViewPointAlongLine step_id=step.id line t data_ref_path />
+            }
+            .into_view(cx),
+            StepData::Mirror { source, axis } => view! { cx,
+                <InnerStepViewMirror step_id=step.id source axis />
+            }
+            .into_view(cx),
+            StepData::RotateCopy {
+                source,
+                center,
+                angle,
+            } => view! { cx,
+                <InnerStepViewRotateCopy step_id=step.id source center angle data_ref_path />
+            }
+            .into_view(cx),
+            StepData::TranslateCopy { source, dx, dy } => view! { cx,
+                <InnerStepViewTranslateCopy step_id=step.id source dx dy data_ref_path />
+            }
+            .into_view(cx),
+            StepData::TransformCopy { source, transform } => view! { cx,
+                <InnerStepViewTransformCopy step_id=step.id source transform />
+            }
+            .into_view(cx),
+            StepData::LinearArray {
+                source,
+                count,
+                dx,
+                dy,
+            } => view! { cx,
+                <InnerStepViewLinearArray step_id=step.id source count dx dy data_ref_path />
+            }
+            .into_view(cx),
+            StepData::PolarArray {
+                source,
+                center,
+                count,
+                sweep,
+            } => view! { cx,
+                <InnerStepViewPolarArray step_id=step.id source center count sweep data_ref_path />
+            }
+            .into_view(cx),
+            StepData::ParallelOffset { line, distance } => view! { cx,
+                <InnerStepViewParallelOffset step_id=step.id line distance data_ref_path />
+            }
+            .into_view(cx),
+            StepData::OffsetCurve {
+                source,
+                distance,
+                join,
+            } => view! { cx,
+                <InnerStepViewOffsetCurve step_id=step.id source distance join data_ref_path />
+            }
+            .into_view(cx),
+            StepData::TrimExtend {
+                line,
+                boundary,
+                keep_end,
+            } => view! { cx,
+                <InnerStepViewTrimExtend step_id=step.id line boundary keep_end />
+            }
+            .into_view(cx),
+            StepData::Perpendicular { line, through } => view! { cx,
+                <InnerStepViewPerpendicular step_id=step.id line through data_ref_path />
+            }
+            .into_view(cx),
+            StepData::Tangent {
+                circle,
+                from,
+                other_side,
+            } => view! { cx,
+                <InnerStepViewTangent step_id=step.id circle from other_side data_ref_path />
+            }
+            .into_view(cx),
+            StepData::Clip { shape } => view! { cx,
+                <InnerStepViewClip step_id=step.id shape />
+            }
+            .into_view(cx),
+            StepData::ShapeBoolean { op, a, b } => view! { cx,
+                <InnerStepViewShapeBoolean step_id=step.id op a b />
+            }
+            .into_view(cx),
+            StepData::BoundingBox { sources } => view! { cx,
+                <InnerStepViewBoundingBox step_id=step.id sources />
+            }
+            .into_view(cx),
+            StepData::DrawImage {
+                src,
+                position,
+                scale,
+            } => view! { cx,
+                <InnerStepViewDrawImage src position scale data_ref_path />
+            }
+            .into_view(cx),
+            StepData::DrawText {
+                anchor,
+                content,
+                size,
+                path,
+            } => view! { cx,
+                <InnerStepViewDrawText step_id=step.id anchor content size path data_ref_path />
+            }
+            .into_view(cx),
+            StepData::ScaleBar {
+                origin,
+                length,
+                divisions,
+                unit,
+                size,
+            } => view! { cx,
+                <InnerStepViewScaleBar origin length divisions unit size data_ref_path />
+            }
+            .into_view(cx),
+            StepData::Axes {
+                origin,
+                x_min,
+                x_max,
+                y_min,
+                y_max,
+                spacing,
+                size,
+            } => view! { cx,
+                <InnerStepViewAxes origin x_min x_max y_min y_max spacing size data_ref_path />
+            }
+            .into_view(cx),
+            StepData::Callout {
+                anchor,
+                tip,
+                content,
+                size,
+            } => view! { cx,
+                <InnerStepViewCallout anchor tip content size data_ref_path />
+            }
+            .into_view(cx),
+            StepData::DrawPath { points } => view! { cx,
+                <InnerStepViewDrawPath title="Draw Path" points data_ref_path />
+            }
+            .into_view(cx),
+            StepData::Spline { points } => view! { cx,
+                <InnerStepViewDrawPath title="Spline" points data_ref_path />
+            }
+            .into_view(cx),
+            StepData::Centroid { points } => view! { cx,
+                <InnerStepViewCentroid points data_ref_path />
+            }
+            .into_view(cx),
+            StepData::DrawPolygon {
+                center,
+                radius,
+                sides,
+            } => view! { cx,
+                <InnerStepViewDrawPolygon center radius sides data_ref_path />
+            }
+            .into_view(cx),
+            StepData::PointGrid {
+                origin,
+                col_spacing,
+                row_spacing,
+                cols,
+                rows,
+            } => view! { cx,
+                <InnerStepViewPointGrid origin col_spacing row_spacing cols rows data_ref_path />
+            }
+            .into_view(cx),
+            StepData::Annulus {
+                center,
+                inner_radius,
+                outer_radius,
+            } => view! { cx,
+                <InnerStepViewAnnulus center inner_radius outer_radius data_ref_path />
+            }
+            .into_view(cx),
+            StepData::DrawRect {
+                origin,
+                width,
+                height,
+            } => view! { cx,
+                <InnerStepViewDrawRect origin width height data_ref_path />
+            }
+            .into_view(cx),
+            StepData::DrawRoundedRect {
+                origin,
+                width,
+                height,
+                radius,
+            } => view! { cx,
+                <InnerStepViewDrawRoundedRect origin width height radius data_ref_path />
+            }
+            .into_view(cx),
+            StepData::DrawArc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } => view! { cx,
+                <InnerStepViewDrawArc title="Draw Arc" center radius start_angle end_angle data_ref_path />
+            }
+            .into_view(cx),
+            StepData::DrawSector {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } => view! { cx,
+                <InnerStepViewDrawArc title="Draw Sector" center radius start_angle end_angle data_ref_path />
+            }
+            .into_view(cx),
+            StepData::FunctionPlot { f, x_min, x_max } => view! { cx,
+                <InnerStepViewFunctionPlot f x_min x_max data_ref_path />
+            }
+            .into_view(cx),
+            StepData::ParametricCurve {
+                x,
+                y,
+                t_min,
+                t_max,
+                samples,
+            } => view! { cx,
+                <InnerStepViewParametricCurve x y t_min t_max samples data_ref_path />
+            }
+            .into_view(cx),
+        }
+    }
+}
+
+const THUMBNAIL_WIDTH: f64 = 64.0;
+const THUMBNAIL_HEIGHT: f64 = 48.0;
+
+/// Canvas pixels of empty space around an exported selection
+const EXPORT_MARGIN: f64 = 16.0;
+
+/// `ids` plus every step they reference, directly or through other steps
+pub fn with_dependencies(steps: &[Step], ids: &[usize]) -> Vec<usize> {
+    let mut all = ids.to_vec();
+    let mut pending = ids.to_vec();
+    while let Some(id) = pending.pop() {
+        let Some(step) = steps.iter().find(|s| s.id == id) else {
+            continue;
+        };
+        for r in step.references().iter().filter_map(|r| r.step_id()) {
+            if !all.contains(&r) {
+                all.push(r);
+                pending.push(r);
+            }
+        }
+    }
+    all
+}
+
+/// Downloads a PNG of the selected steps, plus what they reference with `dependencies`
+pub fn export_selection_png(cx: Scope, dependencies: bool) -> Result<(), String> {
+    let selected = use_context::<RwSignal<Selection>>(cx)
+        .unwrap()
+        .with(|s| s.steps.clone());
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let exported: Vec<Step> = steps.with(|steps| {
+        let ids = if dependencies {
+            with_dependencies(steps, &selected)
+        } else {
+            selected
+        };
+        steps
+            .iter()
+            .filter(|s| ids.contains(&s.id) && is_resolvable(steps, s))
+            .copied()
+            .collect()
+    });
+
+    let bounds =
+        steps_bounds(cx, &exported).ok_or_else(|| "Nothing selected can be drawn".to_string())?;
+    let url = render_png(cx, &exported, bounds, EXPORT_MARGIN)?;
+    download(cx, url, "selection.png");
+    Ok(())
+}
+
+/// The box around everything `steps` draw
+fn steps_bounds(cx: Scope, steps: &[Step]) -> Option<(Point, Point)> {
+    steps
+        .iter()
+        .filter_map(|s| s.bounds(cx))
+        .reduce(|(a_min, a_max), (b_min, b_max)| {
+            (
+                Point {
+                    x: a_min.x.min(b_min.x),
+                    y: a_min.y.min(b_min.y),
+                },
+                Point {
+                    x: a_max.x.max(b_max.x),
+                    y: a_max.y.max(b_max.y),
+                },
+            )
+        })
+}
+
+/// `steps` on their own as a PNG data URL, cropped to `(min, max)` plus `margin` pixels
+fn render_png(
+    cx: Scope,
+    steps: &[Step],
+    (min, max): (Point, Point),
+    margin: f64,
+) -> Result<String, String> {
+    // Same scale as on screen, since stroke widths are worked out from it
+    let unpanned = ViewTransform {
+        pan: Point::default(),
+        ..use_context::<RwSignal<ViewTransform>>(cx).unwrap().get()
+    };
+    // The projection can turn the box, so crop to where its corners land
+    let corners = [
+        min,
+        max,
+        Point { x: min.x, y: max.y },
+        Point { x: max.x, y: min.y },
+    ]
+    .map(|c| unpanned.world_to_canvas(c));
+    let (left, top) = (
+        corners.iter().map(|c| c.x).fold(f64::INFINITY, f64::min),
+        corners.iter().map(|c| c.y).fold(f64::INFINITY, f64::min),
+    );
+    let (right, bottom) = (
+        corners
+            .iter()
+            .map(|c| c.x)
+            .fold(f64::NEG_INFINITY, f64::max),
+        corners
+            .iter()
+            .map(|c| c.y)
+            .fold(f64::NEG_INFINITY, f64::max),
+    );
+    let vt = ViewTransform {
+        pan: unpanned.canvas_to_world(Point {
+            x: left - margin,
+            y: top - margin,
+        }),
+        canvas_width: (right - left + margin * 2.0).ceil(),
+        canvas_height: (bottom - top + margin * 2.0).ceil(),
+        // Exports come out at their canvas size, whatever screen they're made on
+        dpr: 1.0,
+        ..unpanned
+    };
+
+    let canvas = view! { cx, <canvas /> };
+    canvas.set_width(vt.canvas_width as u32);
+    canvas.set_height(vt.canvas_height as u32);
+    let context = canvas
+        .get_context("2d")
+        .unwrap()
+        .unwrap()
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .unwrap();
+    vt.apply(&context);
+    draw_steps(cx, &context, steps.iter());
+
+    canvas
+        .to_data_url()
+        .map_err(|_| "The browser wouldn't encode the image".to_string())
+}
+
+/// Has the browser download `url` as `filename`
+fn download(cx: Scope, url: String, filename: &str) {
+    let link = view! { cx, <a href=url download=filename.to_string() /> };
+    link.click();
+}
+
+/// `template` with `{name}` and `{n}` (counting from 1) filled in for one configuration
+pub fn export_filename(template: &str, name: &str, n: usize) -> String {
+    template
+        .replace("{name}", name)
+        .replace("{n}", &n.to_string())
+}
+
+/// Downloads a print of exactly the active artboard for each configuration
+pub fn export_configurations_png(cx: Scope) -> Result<(), String> {
+    let table = use_context::<RwSignal<ParameterTable>>(cx).unwrap();
+    let (names, template, active, params) = table.with(|t| {
+        (
+            t.configurations
+                .iter()
+                .map(|c| c.name.clone())
+                .collect::<Vec<_>>(),
+            t.filename_template.clone(),
+            t.active,
+            t.params.clone(),
+        )
+    });
+    if names.is_empty() {
+        return Err("There are no configurations to export".to_string());
+    }
+    let before: Vec<Option<f64>> = params.iter().map(|id| parameter_value(cx, *id)).collect();
+    let (artboard, extent) =
+        use_context::<RwSignal<DocumentSettings>>(cx)
+            .unwrap()
+            .with(|settings| {
+                let artboard = settings.active();
+                let corner = Point {
+                    x: artboard.origin.x + artboard.width,
+                    y: artboard.origin.y + artboard.height,
+                };
+                (artboard.id, (artboard.origin, corner))
+            });
+
+    let mut result = Ok(());
+    for (i, name) in names.iter().enumerate() {
+        activate_configuration(cx, i);
+        let drawn: Vec<Step> = use_context::<RwSignal<Vec<Step>>>(cx)
+            .unwrap()
+            .with(|steps| {
+                steps
+                    .iter()
+                    .filter(|s| {
+                        s.on_artboard(artboard) && is_resolvable(steps, s) && !s.construction.get()
+                    })
+                    .copied()
+                    .collect()
+            });
+        match render_png(cx, &drawn, extent, 0.0) {
+            Ok(url) => download(cx, url, &export_filename(&template, name, i + 1)),
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+
+    match active {
+        Some(active) => activate_configuration(cx, active),
+        None => {
+            for (id, value) in params.iter().zip(before) {
+                if let Some(value) = value {
+                    set_parameter_value(cx, *id, value);
+                }
+            }
+            table.update(|t| t.active = None);
+        }
+    }
+    result
+}
+
+/// Why the last export failed, for the command palette and the export buttons alike
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExportErrors {
+    pub selection: Option<String>,
+    pub configurations: Option<String>,
+}
+
+/// Export of the selection on its own, for pulling a detail out of a bigger drawing
+#[component]
+fn ExportSelectionView(cx: Scope) -> impl IntoView {
+    let selection = use_context::<RwSignal<Selection>>(cx).unwrap();
+    let dependencies = create_rw_signal(cx, false);
+    let (error, set_error) = create_slice(
+        cx,
+        use_context::<RwSignal<ExportErrors>>(cx).unwrap(),
+        |e| e.selection.clone(),
+        |e, v| e.selection = v,
+    );
+
+    view! { cx,
+        <div class="flex flex-col mb-6 w-[12rem] max-w-[85%] self-center">
+            <button
+                class="bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded disabled:opacity-50"
+                prop:disabled=move || selection.with(|s| s.steps.is_empty())
+                on:click=move |_| set_error.set(export_selection_png(cx, dependencies.get()).err())
+            >
+                "Export Selection (PNG)"
+            </button>
+            <label class="text-sm">
+                <input
+                    type="checkbox"
+                    prop:checked=move || dependencies.get()
+                    on:change=move |_| dependencies.update(|d| *d = !*d)
+                />
+                " with what it references"
+            </label>
+            {move || error.get().map(|e| view! { cx, <p class="text-sm text-red-600">{e}</p> })}
+        </div>
+    }
+}
+
+/// A tiny drawing of `step` in blue on the active artboard, over the steps it's built from
+#[component]
+fn StepThumbnail(cx: Scope, step: Step) -> impl IntoView {
+    let canvas = view! { cx,
+        <canvas class="border border-gray-300 rounded" />
+    };
+    canvas.set_width(THUMBNAIL_WIDTH as u32);
+    canvas.set_height(THUMBNAIL_HEIGHT as u32);
+
+    let context = canvas
+        .get_context("2d")
+        .unwrap()
+        .unwrap()
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .unwrap();
+
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let document_settings = use_context::<RwSignal<DocumentSettings>>(cx).unwrap();
+
+    create_effect(cx, move |_| {
+        context.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0).unwrap();
+        context.clear_rect(0.0, 0.0, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
+
+        // Fit the artboard into the thumbnail, centered
+        let artboard = document_settings.with(|s| s.active().clone());
+        let scale = (THUMBNAIL_WIDTH / artboard.width).min(THUMBNAIL_HEIGHT / artboard.height);
+        context
+            .set_transform(
+                scale,
+                0.0,
+                0.0,
+                scale,
+                (THUMBNAIL_WIDTH - artboard.width * scale) / 2.0 - artboard.origin.x * scale,
+                (THUMBNAIL_HEIGHT - artboard.height * scale) / 2.0 - artboard.origin.y * scale,
+            )
+            .unwrap();
+        context.set_line_width(1.0 / scale);
+
+        steps.with(|steps| {
+            // Only the step and its dependencies, leaving out clips which would clip the rest
+            let shown = with_dependencies(steps, &[step.id]);
+            let drawable = steps.iter().filter(|s| {
+                shown.contains(&s.id)
+                    && s.on_artboard(artboard.id)
+                    && is_resolvable(steps, s)
+                    && !matches!(s.data, StepData::Clip { .. })
+            });
+            for other in drawable {
+                let highlighted = other.id == step.id;
+                context.set_stroke_style(&wasm_bindgen::JsValue::from_str(if highlighted {
+                    "#3b82f6"
+                } else {
+                    "#cbd5e1"
+                }));
+                context.set_line_width(if highlighted { 2.0 } else { 1.0 } / scale);
+                draw_step(cx, &context, other);
+            }
+        });
+    });
+
+    canvas
+}
+
+#[component]
+pub fn StepView(cx: Scope, step: Step) -> impl IntoView {
+    // Whatever still references this step, while the user decides what to do
+    let pending_delete = create_rw_signal(cx, Dependents::default());
+
+    let delete_callback = move |_| {
+        let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+        let dependents = use_context::<RwSignal<Vec<Step>>>(cx)
+            .unwrap()
+            .with(|steps| datas.with(|datas| step_dependents(steps, datas, step.id)));
+        if dependents.is_empty() {
+            remove_step(cx, step.id);
+        } else {
+            pending_delete.set(dependents);
+        }
+    };
+
+    let delete_dialog = move || {
+        let dependents = pending_delete.get();
+        (!dependents.is_empty()).then(|| {
+            let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+            let list = |ids: &[usize], what: &str| {
+                (!ids.is_empty()).then(|| {
+                    let ids = ids
+                        .iter()
+                        .map(|id| format!("#{}", id))
+                        .collect::<Vec<String>>()
+                        .join(", ");
+                    view! { cx, <p>"Referenced by " {what.to_string()} " " {ids}</p> }
+                })
+            };
+
+            view! { cx,
+                <div class="flex flex-col border-t-2 border-gray-800 mt-2 pt-2">
+                    {list(&dependents.steps, "steps")}
+                    {list(&dependents.datas, "data")}
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                        bake_step_refs(cx, step.id);
+                        remove_step(cx, step.id);
+                    }>
+                        "Bake values and delete"
+                    </button>
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                        pending_delete.set(Dependents::default());
+                        context_infer_target.set(Some(InferTarget::Redirect(step.id)));
+                    }>
+                        "Redirect references to..."
+                    </button>
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                        remove_step(cx, step.id);
+                    }>
+                        "Move to trash anyway"
+                    </button>
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                        pending_delete.set(Dependents::default());
+                    }>
+                        "Cancel"
+                    </button>
+                </div>
+            }
+        })
+    };
+
+    let redirecting = move || {
+        let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+        (context_infer_target.get() == Some(InferTarget::Redirect(step.id))).then(|| {
+            view! { cx,
+                <div class="flex flex-col border-t-2 border-gray-800 mt-2 pt-2">
+                    <p>"Pick a replacement snap point on the canvas"</p>
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                        context_infer_target.set(None);
+                    }>
+                        "Cancel"
+                    </button>
+                </div>
+            }
+        })
+    };
+
+    let selection = use_context::<RwSignal<Selection>>(cx).unwrap();
+    let selected = move || selection.with(|sel| sel.steps.contains(&step.id));
+    let flashed_step = use_context::<RwSignal<FlashedStep>>(cx).unwrap();
+    let flashed = move || flashed_step.get() == FlashedStep(Some(step.id));
+
+    let suppressed_source = move || {
+        use_context::<RwSignal<Vec<Step>>>(cx)
+            .unwrap()
+            .with(|steps| suppressed_source(steps, &step))
+    };
+    let error = move || {
+        use_context::<RwSignal<Vec<Step>>>(cx)
+            .unwrap()
+            .with(|steps| step_error(steps, &step))
+    };
+    let resolve_error = move || {
+        use_context::<RwSignal<Vec<Step>>>(cx)
+            .unwrap()
+            .with(|steps| is_resolvable(steps, &step))
+            .then(|| step.resolve_error(cx))
+            .flatten()
+    };
+
+    view! { cx,
+        <div id=format!("step-{}", step.id) class=move || {
+            format!(
+                "p-2 m-1 shadow bg-white w-[90%] rounded-lg relative group {} {}",
+                if flashed() {
+                    "ring-4 ring-amber-400"
+                } else if selected() {
+                    "ring-2 ring-blue-500"
+                } else {
+                    ""
+                },
+                if step.suppressed.get() { "opacity-50" } else { "" },
+            )
+        }>
+            <button
+                class="absolute left-[90%] opacity-0 group-hover:opacity-100 transition-all"
+                on:click=delete_callback>
+                "x"
+            </button>
+            <div class="w-full h-full flex flex-col">
+                <div class="flex flex-row justify-between">
+                    <p class="cursor-pointer" on:click=move |_| selection.update(|sel| sel.toggle_step(step.id))>
+                        "Step #" {step.id}
+                    </p>
+                    <StepArtboardSelect step />
+                </div>
+                <div class="self-center my-1">
+                    <StepThumbnail step />
+                </div>
+                <div class="flex flex-row justify-between">
+                    <label class="text-sm">
+                        <input
+                            type="checkbox"
+                            prop:checked=move || step.suppressed.get()
+                            prop:disabled=move || step.suppress_with.get().is_some()
+                            on:change=move |_| step.suppressed.update(|s| *s = !*s)
+                        />
+                        " Suppress"
+                    </label>
+                    <SuppressWithSelect step />
+                </div>
+                <label class="text-sm">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || step.construction.get()
+                        on:change=move |_| step.construction.update(|c| *c = !*c)
+                    />
+                    " Construction"
+                </label>
+                <label class="text-sm">
+                    "Tags: "
+                    <input
+                        class="w-28"
+                        placeholder="outline, labels"
+                        prop:value=move || step.tags.with(|tags| tags.join(", "))
+                        on:change=move |ev| step.tags.set(parse_tags(&event_target_value(&ev)))
+                    />
+                </label>
+                {move || suppressed_source().map(|id| view! { cx,
+                    <p class="text-sm text-amber-700">"Suppressed source: step[" {id} "]"</p>
+                })}
+                {move || error().map(|e| view! { cx,
+                    <p class="text-sm text-red-700 border border-red-700 rounded p-1">"Not drawn: " {e}</p>
+                })}
+                {move || resolve_error().map(|e| view! { cx,
+                    <p class="text-sm text-red-700">{e}</p>
+                })}
+                <InnerStepView step/>
+                <StepStyleView step/>
+                {delete_dialog}
+                {redirecting}
+            </div>
+        </div>
+    }
+}
+
+/// Refs to the points in `data`, for snapping to: the point itself, or each of a list's
+pub fn data_points(data: &Data) -> Vec<DataRef> {
+    let base = vec![DataRefPathEl::Data, DataRefPathEl::WithId(data.id)];
+    match data.data {
+        DataData::Point(_) => vec![DataRef(base)],
+        DataData::PointList(points) => (0..points.with(|p| p.len()))
+            .map(|i| {
+                let mut path = base.clone();
+                path.push(DataRefPathEl::Index(i));
+                DataRef(path)
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[component]
+fn InnerDataViewPointList(
+    cx: Scope,
+    points: RwSignal<Vec<PointSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let entry_path = move |i: usize, coord: &'static str| {
+        let mut path = data_ref_path.get();
+        path.push(DataRefPathEl::Index(i));
+        path.push(DataRefPathEl::PropName(coord));
+        store_value(cx, path)
+    };
+    let entry_refs = move || {
+        (0..points.with(|p| p.len()))
+            .map(|i| {
+                let mut path = data_ref_path.get();
+                path.push(DataRefPathEl::Index(i));
+                ResolvableTo::Ref(DataRef(path))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Drawn from the points as they are now: points added later aren't picked up
+    let draw_path = move |_| {
+        let points = entry_refs()
+            .into_iter()
+            .map(|r| create_rw_signal(cx, r))
+            .collect();
+        push_step(
+            cx,
+            StepData::DrawPath {
+                points: create_rw_signal(cx, points),
+            },
+        );
+    };
+    let draw_points = move |_| {
+        for r in entry_refs() {
+            push_step(
+                cx,
+                StepData::DrawPoint {
+                    point: create_rw_signal(cx, r),
+                    marker: create_rw_signal(cx, PointMarker::default()),
+                    size: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                },
+            );
+        }
+    };
+    let add_point = move |_| {
+        // One unit along from the last one, so it doesn't land on top of it
+        let last = points.with(|p| p.last().map(|p| ResolvableTo::T(*p).resolve(cx)));
+        let next = last.map_or(Point::default(), |p| Point { x: p.x + 1.0, ..p });
+        points.update(|p| p.push(PointSignal::literal(cx, next)));
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Point List"</p>
+            {move || points.get().into_iter().enumerate().map(|(i, point)| view! { cx,
+                <div class="flex flex-row">
+                    <p>{format!("[{}] x: ", i)}</p>
+                    <ResolvableToNumberView n={point.x} data_ref_path=entry_path(i, "x") />
+                    <p class="ml-3">"y: "</p>
+                    <ResolvableToNumberView n={point.y} data_ref_path=entry_path(i, "y") />
+                    <button class="border-2 border-gray-800 ml-2 px-1" on:click=move |_| {
+                        points.update(|p| {
+                            p.remove(i);
+                        });
+                    }>
+                        "-"
+                    </button>
+                </div>
+            }).collect::<Vec<_>>()}
+            <button class="border-2 border-gray-800 mt-2" on:click=add_point>"+ point"</button>
+            <div class="flex flex-row mt-2">
+                <button class="border-2 border-gray-800 px-1" on:click=draw_path>"Draw as path"</button>
+                <button class="border-2 border-gray-800 px-1 ml-1" on:click=draw_points>"Draw as points"</button>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+pub fn InnerDataViewPoint(
+    cx: Scope,
+    point: RwSignal<ResolvableTo<PointSignal>>,
+    data_ref_path: StoredValue<Vec<DataRefPathEl>>,
+) -> impl IntoView {
+    let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+
+    let mut x_path = data_ref_path.get();
+    x_path.push(DataRefPathEl::PropName("x"));
+    let x_path = store_value(cx, x_path);
+
+    let mut y_path = data_ref_path.get();
+    y_path.push(DataRefPathEl::PropName("y"));
+    let y_path = store_value(cx, y_path);
+
+    move || {
+        if context_infer_target.get() == Some(InferTarget::Point(point)) {
+            return view! { cx,
+                <div class="flex flex-row">
+                    <p>"..."</p>
+                    <button class="border-2 border-gray-800 ml-2" on:click=move |_| {
+                        context_infer_target.set(None);
+                    }>
+                        "Cancel Infer"
+                    </button>
+                </div>
+            }
+            .into_view(cx);
+        }
+
+        match point.get() {
+            ResolvableTo::T(p) => view! { cx,
+                <div class="flex flex-col">
+                    <div class="flex flex-row">
+                        <p>"x: "</p>
+                        <ResolvableToNumberView n={p.x} data_ref_path=x_path />
+                        <p class="ml-3">"y: "</p>
+                        <ResolvableToNumberView n={p.y} data_ref_path=y_path />
+                    </div>
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                        context_infer_target.set(Some(InferTarget::Point(point)));
+                    }>
+                        "Infer"
+                    </button>
+                </div>
+            }
+            .into_view(cx),
+            ResolvableTo::Ref(r) => view! { cx,
+                <div class="flex flex-col">
+                    <RefBreadcrumbs r />
+                    // Keeps the point where it is, just no longer following the ref
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                        let at = point.get().resolve(cx);
+                        point.set(ResolvableTo::T(PointSignal::literal(cx, at)));
+                    }>
+                        "Detach"
+                    </button>
+                </div>
+            }
+            .into_view(cx),
+            ResolvableTo::Expr(_) => unreachable!("Only number fields hold expressions"),
+        }
+    }
+}
+
+#[component]
+fn InnerDataViewVector(cx: Scope, dx: RwSignal<f64>, dy: RwSignal<f64>) -> impl IntoView {
+    let canvas_tool = use_context::<RwSignal<CanvasTool>>(cx).unwrap();
+    let dragging = move || canvas_tool.get() == CanvasTool::DragVector { dx, dy };
+
+    let length = move || dx.get().hypot(dy.get());
+    let angle = move || angle_unit(cx).from_radians(dy.get().atan2(dx.get()));
+    let set_polar = move |length: f64, angle: f64| {
+        let radians = angle_unit(cx).to_radians(angle);
+        dx.set(length * radians.cos());
+        dy.set(length * radians.sin());
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Vector"</p>
+            <div class="flex flex-row">
+                <p>"dx: "</p>
+                <DraggableNumView d=dx />
+                <p class="ml-3">"dy: "</p>
+                <DraggableNumView d=dy />
+            </div>
+            <label class="flex flex-row justify-between">
+                "length: "
+                <input
+                    type="number"
+                    step="any"
+                    class="w-28"
+                    prop:value=move || shown_value(length())
+                    on:change=move |ev| {
+                        if let Ok(value) = event_target_value(&ev).parse::<f64>() {
+                            set_polar(value, angle());
+                        }
+                    }
+                />
+            </label>
+            <label class="flex flex-row justify-between">
+                {move || format!("angle ({}): ", angle_unit(cx).name())}
+                <input
+                    type="number"
+                    step="any"
+                    class="w-28"
+                    prop:value=move || shown_value(angle())
+                    on:change=move |ev| {
+                        if let Ok(value) = event_target_value(&ev).parse::<f64>() {
+                            set_polar(length(), value);
+                        }
+                    }
+                />
+            </label>
+            <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                canvas_tool.set(if dragging() { CanvasTool::Pointer } else { CanvasTool::DragVector { dx, dy } });
+            }>
+                {move || if dragging() { "Cancel drag" } else { "Drag on canvas" }}
+            </button>
+        </div>
+    }
+}
+
+#[component]
+fn InnerDataViewTransform(
+    cx: Scope,
+    dx: RwSignal<f64>,
+    dy: RwSignal<f64>,
+    angle: RwSignal<f64>,
+    scale: RwSignal<f64>,
+    about_x: RwSignal<f64>,
+    about_y: RwSignal<f64>,
+) -> impl IntoView {
+    let shown_angle = move || shown_value(angle_unit(cx).from_radians(angle.get()));
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Transform"</p>
+            <div class="flex flex-row">
+                <p>"dx: "</p>
+                <DraggableNumView d=dx />
+                <p class="ml-3">"dy: "</p>
+                <DraggableNumView d=dy />
+            </div>
+            <label class="flex flex-row justify-between">
+                {move || format!("angle ({}): ", angle_unit(cx).name())}
+                <input
+                    type="number"
+                    step="any"
+                    class="w-28"
+                    prop:value=shown_angle
+                    on:change=move |ev| {
+                        if let Ok(value) = event_target_value(&ev).parse::<f64>() {
+                            angle.set(angle_unit(cx).to_radians(value));
+                        }
+                    }
+                />
+            </label>
+            <div class="flex flex-row">
+                <p>"scale: "</p>
+                <DraggableNumView d=scale />
+            </div>
+            <div class="flex flex-row">
+                <p>"about x: "</p>
+                <DraggableNumView d=about_x />
+                <p class="ml-3">"y: "</p>
+                <DraggableNumView d=about_y />
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn InnerDataViewSlider(
+    cx: Scope,
+    value: RwSignal<f64>,
+    bounds: RwSignal<SliderBounds>,
+) -> impl IntoView {
+    let ranged = move || bounds.with(|b| b.min.is_some() && b.max.is_some());
+    let attr = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+    let set_value = move |ev: web_sys::Event| {
+        if let Ok(v) = event_target_value(&ev).parse::<f64>() {
+            value.set(bounds.get().clamp(v));
+        }
+    };
+    // A blank field lifts that limit. The value's pulled back into range either way.
+    let bound_input = move |label: &'static str,
+                            get: fn(&SliderBounds) -> Option<f64>,
+                            set: fn(&mut SliderBounds, Option<f64>)| {
+        view! { cx,
+            <label class="flex flex-row justify-between">
+                {label}
+                <input
+                    type="number"
+                    step="any"
+                    class="w-20"
+                    prop:value=move || attr(bounds.with(get))
+                    on:change=move |ev| {
+                        let v = event_target_value(&ev).parse::<f64>().ok();
+                        bounds.update(|b| set(b, v));
+                        value.set(bounds.get().clamp(value.get()));
+                    }
+                />
+            </label>
+        }
+    };
+
+    view! { cx,
+        <div class="flex flex-col">
+            <p>"Slider"</p>
+            {move || if ranged() {
+                view! { cx,
+                    <div class="flex flex-row justify-between">
+                        <input
+                            type="range"
+                            class="w-28"
+                            min=move || attr(bounds.with(|b| b.min))
+                            max=move || attr(bounds.with(|b| b.max))
+                            step=move || bounds.with(|b| b.step).map(|s| s.to_string()).unwrap_or_else(|| "any".to_string())
+                            prop:value=move || value.get().to_string()
+                            on:input=set_value
+                        />
+                        <p class="ml-2">{move || ((value.get() * 1e6).round() / 1e6).to_string()}</p>
+                    </div>
+                }
+                .into_view(cx)
+            } else {
+                view! { cx,
+                    <input
+                        type="number"
+                        step="any"
+                        class="w-28"
+                        prop:value=move || value.get().to_string()
+                        on:change=set_value
+                    />
+                }
+                .into_view(cx)
+            }}
+            {bound_input("min: ", |b| b.min, |b, v| b.min = v)}
+            {bound_input("max: ", |b| b.max, |b, v| b.max = v)}
+            {bound_input("step: ", |b| b.step, |b, v| b.step = v.filter(|s| *s > 0.0))}
+        </div>
+    }
+}
+
+/// Points a step's dx and dy at the components of vector data
+#[component]
+fn VectorSelect(
+    cx: Scope,
+    dx: RwSignal<ResolvableTo<NumberSignal>>,
+    dy: RwSignal<ResolvableTo<NumberSignal>>,
+) -> impl IntoView {
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+    let vectors = move || {
+        datas.with(|datas| {
+            datas
+                .iter()
+                .filter(|d| !d.trashed.get() && matches!(d.data, DataData::Vector { .. }))
+                .map(|d| d.id)
+                .collect::<Vec<_>>()
+        })
+    };
+    let component = |id: usize, prop: &'static str| {
+        DataRef(vec![
+            DataRefPathEl::Data,
+            DataRefPathEl::WithId(id),
+            DataRefPathEl::PropName(prop),
+        ])
+    };
+    // Only counts as following a vector while both components do
+    let selected = move || {
+        vectors().into_iter().find(|id| {
+            dx.get() == ResolvableTo::Ref(component(*id, "dx"))
+                && dy.get() == ResolvableTo::Ref(component(*id, "dy"))
+        })
+    };
+
+    view! { cx,
+        <select class="text-sm" on:change=move |ev| {
+            if let Ok(id) = event_target_value(&ev).parse::<usize>() {
+                dx.set(ResolvableTo::Ref(component(id, "dx")));
+                dy.set(ResolvableTo::Ref(component(id, "dy")));
+            }
+        }>
+            <option value="none" selected=move || selected().is_none()>"(none)"</option>
+            {move || vectors().into_iter().map(|id| view! { cx,
+                <option value=id.to_string() selected=move || selected() == Some(id)>
+                    {format!("Data #{}", id)}
+                </option>
+            }).collect::<Vec<_>>()}
+        </select>
+    }
+}
+
+#[component]
+pub fn InnerDataView(cx: Scope, data: Data) -> impl IntoView {
+    move || match data.data {
+        DataData::PointList(points) => view! { cx,
+            <InnerDataViewPointList points data_ref_path={store_value(cx, vec![
+                DataRefPathEl::Data,
+                DataRefPathEl::WithId(data.id),
+            ])} />
+        }
+        .into_view(cx),
+        DataData::Vector { dx, dy } => view! { cx, <InnerDataViewVector dx dy /> }.into_view(cx),
+        DataData::Transform {
+            dx,
+            dy,
+            angle,
+            scale,
+            about_x,
+            about_y,
+        } => view! { cx,
+            <InnerDataViewTransform dx dy angle scale about_x about_y />
+        }
+        .into_view(cx),
+        DataData::Bool(b) => view! { cx,
+            <label class="flex flex-row justify-between">
+                <p>"Bool"</p>
+                <span>
+                    <input
+                        type="checkbox"
+                        prop:checked=move || b.get()
+                        on:change=move |_| b.update(|b| *b = !*b)
+                    />
+                    {move || if b.get() { " on" } else { " off" }}
+                </span>
+            </label>
+        }
+        .into_view(cx),
+        DataData::Color(c) => {
+            let (value, set) = create_slice(cx, c, |c| c.clone(), |c, v| *c = v);
+            view! { cx,
+                <div class="flex flex-row justify-between">
+                    <p>"Color"</p>
+                    <ColorInput value set />
+                </div>
+            }
+            .into_view(cx)
+        }
+        DataData::Number(n) => view! { cx,
+            <div>
+                <p>"Number"</p>
+                <DraggableNumView d={n} />
+            </div>
+        }
+        .into_view(cx),
+        DataData::Slider { value, bounds } => view! { cx,
+            <InnerDataViewSlider value bounds />
+        }
+        .into_view(cx),
+        DataData::Point(p) => view! { cx,
+            <div>
+                <p>"Point"</p>
+                <InnerDataViewPoint point=p data_ref_path={store_value(cx, vec![
+                    DataRefPathEl::Data,
+                    DataRefPathEl::WithId(data.id),
+                ])} />
+            </div>
+        }
+        .into_view(cx),
+        DataData::Angle(radians) => {
+            let shown = move || shown_value(angle_unit(cx).from_radians(radians.get()));
+
+            view! { cx,
+                <div>
+                    <div class="flex flex-row justify-between">
+                        <p>"Angle"</p>
+                        <p class="text-sm">{move || angle_unit(cx).name()}</p>
+                    </div>
+                    <input
+                        type="number"
+                        step="any"
+                        class="w-28"
+                        prop:value=shown
+                        on:change=move |ev| {
+                            if let Ok(value) = event_target_value(&ev).parse::<f64>() {
+                                radians.set(angle_unit(cx).to_radians(value));
+                            }
+                        }
+                    />
+                </div>
+            }
+            .into_view(cx)
+        }
+    }
+}
+
+#[component]
+pub fn DataView(cx: Scope, data: Data) -> impl IntoView {
+    let selection = use_context::<RwSignal<Selection>>(cx).unwrap();
+    let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+    let users = move || {
+        let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+        use_context::<RwSignal<Vec<Step>>>(cx)
+            .unwrap()
+            .with(|steps| datas.with(|datas| data_users(steps, datas, data.id)))
+    };
+    // Users still referencing this data while we wait for the user to decide, as for steps
+    let pending_delete = create_rw_signal(cx, Dependents::default());
+    let delete_callback = move |_| {
+        let users = users();
+        if users.is_empty() {
+            data.trashed.set(true);
+        } else {
+            pending_delete.set(users);
+        }
+    };
+    let delete_dialog = move || {
+        let users = pending_delete.get();
+        (!users.is_empty()).then(|| {
+            let list = |ids: &[usize], what: &str| {
+                (!ids.is_empty()).then(|| {
+                    let ids = ids
+                        .iter()
+                        .map(|id| format!("#{}", id))
+                        .collect::<Vec<String>>()
+                        .join(", ");
+                    view! { cx, <p>"Referenced by " {what.to_string()} " " {ids}</p> }
+                })
+            };
+
+            view! { cx,
+                <div class="flex flex-col border-t-2 border-gray-800 mt-2 pt-2">
+                    {list(&users.steps, "steps")}
+                    {list(&users.datas, "data")}
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                        bake_data_refs(cx, data.id);
+                        pending_delete.set(Dependents::default());
+                        data.trashed.set(true);
+                    }>
+                        "Bake values and delete"
+                    </button>
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                        pending_delete.set(Dependents::default());
+                        data.trashed.set(true);
+                    }>
+                        "Move to trash anyway"
+                    </button>
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                        pending_delete.set(Dependents::default());
+                    }>
+                        "Cancel"
+                    </button>
+                </div>
+            }
+        })
+    };
+
+    let data_ref = move || DataRef(vec![DataRefPathEl::Data, DataRefPathEl::WithId(data.id)]);
+    // The pending infer target, if this data can fill it
+    let fillable = move || {
+        context_infer_target
+            .get()
+            .filter(|target| match (target, data.data) {
+                (
+                    InferTarget::Number(_) | InferTarget::Eyedropper { .. },
+                    DataData::Number(_)
+                    | DataData::Slider { .. }
+                    | DataData::Angle(_)
+                    | DataData::Bool(_)
+                    | DataData::Vector { .. },
+                )
+                | (InferTarget::Point(_), DataData::Point(_) | DataData::PointList(_)) => true,
+                _ => false,
+            })
+            .filter(|target| {
+                let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+                use_context::<RwSignal<Vec<Step>>>(cx)
+                    .unwrap()
+                    .with(|steps| {
+                        datas.with(|datas| binding_allowed(steps, datas, target, &data_ref()))
+                    })
+            })
+    };
+    let use_as_reference = move |target: InferTarget, r: DataRef| {
+        commit_binding_preview(cx);
+        match target {
+            InferTarget::Number(n) | InferTarget::Eyedropper { target: n, .. } => {
+                n.set(ResolvableTo::Ref(r));
+                context_infer_target.set(None);
+            }
+            InferTarget::Point(p) => {
+                p.set(ResolvableTo::Ref(r));
+                advance_placement(cx);
+            }
+            InferTarget::Redirect(_) => {}
+        }
+    };
+    // What the data offers to fill a target with. Vectors offer one of their components.
+    let choices = move || match data.data {
+        DataData::Vector { .. } => ["dx", "dy", "length"]
+            .into_iter()
+            .map(|prop| {
+                let mut path = data_ref().0;
+                path.push(DataRefPathEl::PropName(prop));
+                (format!("Use {}", prop), DataRef(path))
+            })
+            .collect(),
+        DataData::PointList(_) => data_points(&data)
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| (format!("Use [{}]", i), r))
+            .collect(),
+        _ => vec![("Use as reference".to_string(), data_ref())],
+    };
+
+    view! { cx,
+        <div class="p-2 m-1 shadow bg-white w-[90%] rounded-lg relative group">
+            <button
+                class="absolute left-[90%] opacity-0 group-hover:opacity-100 transition-all"
+                on:click=delete_callback>
+                "x"
+            </button>
+            <div class="w-full h-full flex flex-col">
+                <div class="flex flex-row justify-between">
+                    <p>"Data #" {data.id}</p>
+                    <p class=move || if users().is_empty() { "text-sm text-gray-400" } else { "text-sm" }>
+                        {move || match users().len() {
+                            1 => "1 user".to_string(),
+                            n => format!("{} users", n),
+                        }}
+                    </p>
+                </div>
+                <InnerDataView data/>
+                {move || fillable().map(|target| choices().into_iter().map(|(label, r)| {
+                    let r = store_value(cx, r);
+                    view! { cx,
+                        <button
+                            class="border-2 border-gray-800 mt-2"
+                            on:mouseenter=move |_| start_binding_preview(cx, target, r.get())
+                            on:mouseleave=move |_| end_binding_preview(cx)
+                            on:click=move |_| use_as_reference(target, r.get())
+                        >
+                            {label}
+                        </button>
+                    }
+                }).collect::<Vec<_>>())}
+                <button
+                    class="border-2 border-gray-800 mt-2 disabled:opacity-50"
+                    prop:disabled=move || users().steps.is_empty()
+                    on:click=move |_| selection.set(Selection { steps: users().steps })
+                >
+                    "Select users"
+                </button>
+                {delete_dialog}
+            </div>
+        </div>
+    }
+}
+
+/// A named page rectangle in world space, which its exports cover exactly
+#[derive(Clone, Debug, PartialEq)]
+pub struct Artboard {
+    pub id: usize,
+    pub name: String,
+    pub origin: Point,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Artboard {
+    pub fn aspect_ratio(&self) -> f64 {
+        self.width / self.height
+    }
+}
+
+/// Gap between an artboard and the next one added to its right
+const ARTBOARD_SPACING: f64 = 20.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentSettings {
+    pub artboards: Vec<Artboard>,
+    pub active_artboard: usize,
+    /// Canvas pixels per world unit
+    pub scale_factor: f64,
+    pub background: Option<BackgroundImage>,
+    pub style_presets: Vec<StylePreset>,
+    /// Never reused, so a removed preset's steps and tags can't pick up a new one
+    pub next_style_preset_id: usize,
+    /// The style preset (by id) each tag applies to steps without a preset of their own
+    pub tag_presets: Vec<(String, usize)>,
+    /// What angles are entered and shown in, and what trig in expressions uses
+    pub angle_unit: AngleUnit,
+}
+
+/// A reference image to trace over, left out of exports
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackgroundImage {
+    /// Usually a data URL of the uploaded file
+    pub src: String,
+    /// World position of the image's top-left corner
+    pub position: Point,
+    /// World units per image pixel
+    pub scale: f64,
+    pub opacity: f64,
+    /// Locked images can't be dragged around the canvas or edited
+    pub locked: bool,
+}
+
+impl BackgroundImage {
+    pub fn new(src: String) -> Self {
+        Self {
+            src,
+            position: Point::default(),
+            scale: 0.25,
+            opacity: 0.5,
+            locked: false,
+        }
+    }
+}
+
+impl Default for DocumentSettings {
+    fn default() -> Self {
+        Self {
+            artboards: vec![Artboard {
+                id: 0,
+                name: "Page 1".to_string(),
+                origin: Point::default(),
+                width: 300.0,
+                height: 150.0,
+            }],
+            active_artboard: 0,
+            scale_factor: 16.0,
+            background: None,
+            style_presets: Vec::new(),
+            next_style_preset_id: 0,
+            tag_presets: Vec::new(),
+            angle_unit: AngleUnit::default(),
+        }
+    }
+}
+
+impl DocumentSettings {
+    pub fn active(&self) -> &Artboard {
+        self.artboards
+            .iter()
+            .find(|a| a.id == self.active_artboard)
+            .expect("Invalid active artboard")
+    }
+
+    pub fn active_mut(&mut self) -> &mut Artboard {
+        let active = self.active_artboard;
+        self.artboards
+            .iter_mut()
+            .find(|a| a.id == active)
+            .expect("Invalid active artboard")
+    }
+
+    /// Adds a plain black style preset
+    pub fn add_style_preset(&mut self, cx: Scope) {
+        let id = self.next_style_preset_id;
+        self.next_style_preset_id += 1;
+        self.style_presets.push(StylePreset {
+            id,
+            name: format!("Style {}", self.style_presets.len() + 1),
+            stroke: create_rw_signal(cx, "#000000".to_string()),
+            line_width: 4.0,
+            dash: LineDash::default(),
+            fill: None,
+        });
+    }
+
+    /// Removes the style preset `id` along with any tags applying it
+    pub fn remove_style_preset(&mut self, id: usize) {
+        self.style_presets.retain(|p| p.id != id);
+        self.tag_presets.retain(|(_, preset)| *preset != id);
+    }
+
+    pub fn style_preset_mut(&mut self, id: usize) -> Option<&mut StylePreset> {
+        self.style_presets.iter_mut().find(|p| p.id == id)
+    }
+
+    pub fn tag_preset(&self, tag: &str) -> Option<usize> {
+        self.tag_presets
+            .iter()
+            .find(|(t, _)| t == tag)
+            .map(|(_, preset)| *preset)
+    }
+
+    pub fn set_tag_preset(&mut self, tag: &str, preset: Option<usize>) {
+        self.tag_presets.retain(|(t, _)| t != tag);
+        if let Some(preset) = preset {
+            self.tag_presets.push((tag.to_string(), preset));
+        }
+    }
+
+    /// Adds and activates an artboard the size of the active one, right of all the others
+    pub fn add_artboard(&mut self) {
+        let id = self.artboards.iter().map(|a| a.id + 1).max().unwrap_or(0);
+        let right = self
+            .artboards
+            .iter()
+            .map(|a| a.origin.x + a.width)
+            .fold(0.0, f64::max);
+        let (width, height) = (self.active().width, self.active().height);
+
+        self.artboards.push(Artboard {
+            id,
+            name: format!("Page {}", self.artboards.len() + 1),
+            origin: Point {
+                x: right + ARTBOARD_SPACING,
+                y: 0.0,
+            },
+            width,
+            height,
+        });
+        self.active_artboard = id;
+    }
+
+    /// Moves the active artboard `delta` places through the list, wrapping around
+    pub fn cycle_active(&mut self, delta: isize) {
+        let count = self.artboards.len() as isize;
+        let i = self
+            .artboards
+            .iter()
+            .position(|a| a.id == self.active_artboard)
+            .unwrap_or(0) as isize;
+        self.active_artboard = self.artboards[(i + delta).rem_euclid(count) as usize].id;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SnapSettings {
+    /// Round free (non-ref) placements to the nearest grid point
+    pub grid: bool,
+    pub grid_size: f64,
+    pub grid_kind: GridKind,
+    /// Snap to the snap points of existing geometry
+    pub geometry: bool,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            grid: true,
+            grid_size: 1.0,
+            grid_kind: GridKind::default(),
+            geometry: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GridKind {
+    #[default]
+    Square,
+    /// A triangular lattice at 30°, 150° and 90°, so snapping to it doubles as angle snapping
+    Isometric,
+}
+
+const ISO_COS: f64 = 0.866_025_403_784_438_6; // cos 30°
+const ISO_SIN: f64 = 0.5; // sin 30°
+
+impl SnapSettings {
+    /// Lattice coordinates (i, j) of `p`, along (cos 30°, sin 30°) and (cos 30°, -sin 30°)
+    fn iso_lattice_coords(&self, p: Point) -> (f64, f64) {
+        let a = p.x / (ISO_COS * self.grid_size);
+        let b = p.y / (ISO_SIN * self.grid_size);
+        ((a + b) / 2.0, (a - b) / 2.0)
+    }
+
+    fn iso_lattice_point(&self, i: f64, j: f64) -> Point {
+        Point {
+            x: (i + j) * ISO_COS * self.grid_size,
+            y: (i - j) * ISO_SIN * self.grid_size,
+        }
+    }
+
+    pub fn snap_to_grid(&self, p: Point) -> Point {
+        if !self.grid {
+            return p;
+        }
+        match self.grid_kind {
+            GridKind::Square => Point {
+                x: (p.x / self.grid_size).round() * self.grid_size,
+                y: (p.y / self.grid_size).round() * self.grid_size,
+            },
+            GridKind::Isometric => {
+                let (i, j) = self.iso_lattice_coords(p);
+                self.iso_lattice_point(i.round(), j.round())
+            }
+        }
+    }
+
+    /// Draws the grid lines covering `visible` (top left, bottom right, in world space)
+    fn draw_grid(&self, context: &web_sys::CanvasRenderingContext2d, visible: (Point, Point)) {
+        let (min, max) = visible;
+        context.begin_path();
+        match self.grid_kind {
+            GridKind::Square => {
+                let mut x = (min.x / self.grid_size).floor() * self.grid_size;
+                while x <= max.x {
+                    context.move_to(x, min.y);
+                    context.line_to(x, max.y);
+                    x += self.grid_size;
+                }
+                let mut y = (min.y / self.grid_size).floor() * self.grid_size;
+                while y <= max.y {
+                    context.move_to(min.x, y);
+                    context.line_to(max.x, y);
+                    y += self.grid_size;
+                }
+            }
+            GridKind::Isometric => {
+                let corners = [
+                    min,
+                    max,
+                    Point { x: min.x, y: max.y },
+                    Point { x: max.x, y: min.y },
+                ]
+                .map(|c| self.iso_lattice_coords(c));
+                let range = |f: fn(&(f64, f64)) -> f64| {
+                    let lo = corners.iter().map(f).fold(f64::INFINITY, f64::min).floor();
+                    let hi = corners
+                        .iter()
+                        .map(f)
+                        .fold(f64::NEG_INFINITY, f64::max)
+                        .ceil();
+                    (lo as i64, hi as i64)
+                };
+                let (i_lo, i_hi) = range(|c| c.0);
+                let (j_lo, j_hi) = range(|c| c.1);
+
+                // Lines of constant j run along the i axis (30°), and vice versa (150°)
+                for j in j_lo..=j_hi {
+                    let (a, b) = (
+                        self.iso_lattice_point(i_lo as f64, j as f64),
+                        self.iso_lattice_point(i_hi as f64, j as f64),
+                    );
+                    context.move_to(a.x, a.y);
+                    context.line_to(b.x, b.y);
+                }
+                for i in i_lo..=i_hi {
+                    let (a, b) = (
+                        self.iso_lattice_point(i as f64, j_lo as f64),
+                        self.iso_lattice_point(i as f64, j_hi as f64),
+                    );
+                    context.move_to(a.x, a.y);
+                    context.line_to(b.x, b.y);
+                }
+
+                let column = ISO_COS * self.grid_size;
+                let mut x = (min.x / column).floor() * column;
+                while x <= max.x {
+                    context.move_to(x, min.y);
+                    context.line_to(x, max.y);
+                    x += column;
+                }
+            }
+        }
+        context.stroke();
+    }
+}
+
+/// Refs to where line steps cross the grid lines nearest to `near`
+fn grid_crossing_snap_points(steps: &[Step], snap: &SnapSettings, near: Point) -> Vec<DataRef> {
+    let grid_x = (near.x / snap.grid_size).round() * snap.grid_size;
+    let grid_y = (near.y / snap.grid_size).round() * snap.grid_size;
+
+    let mut crossings = Vec::new();
+    for step in steps.iter() {
+        if let StepData::DrawLine { .. }
+        | StepData::DrawArrow { .. }
+        | StepData::DrawRay { .. }
+        | StepData::DrawInfiniteLine { .. }
+        | StepData::Perpendicular { .. }
+        | StepData::Tangent { .. }
+        | StepData::ParallelOffset { .. }
+        | StepData::TrimExtend { .. } = step.data
+        {
+            for (prop, value) in [("cross_x", grid_x), ("cross_y", grid_y)] {
+                crossings.push(DataRef(vec![
+                    DataRefPathEl::Step,
+                    DataRefPathEl::WithId(step.id),
+                    DataRefPathEl::PropName(prop),
+                    DataRefPathEl::Param(value),
+                ]));
+            }
+        }
+    }
+    crossings
+}
+
+/// Maps between world coordinates, canvas pixels and the client pixels mouse events report
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewTransform {
+    pub projection: Projection,
+    /// Canvas pixels per world unit at zoom 1
+    pub scale_factor: f64,
+    pub zoom: f64,
+    /// The world point shown at the canvas's top-left corner
+    pub pan: Point,
+    pub canvas_width: f64,
+    pub canvas_height: f64,
+    /// Backing store pixels per canvas pixel, so drawing stays sharp on HiDPI screens
+    pub dpr: f64,
+}
+
+impl Default for ViewTransform {
+    fn default() -> Self {
+        Self {
+            projection: Projection::default(),
+            scale_factor: 16.0,
+            zoom: 1.0,
+            pan: Point::default(),
+            canvas_width: 0.0,
+            canvas_height: 0.0,
+            dpr: 1.0,
+        }
+    }
+}
+
+/// How the world plane is laid onto the screen
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Projection {
+    #[default]
+    Plan,
+    /// The world x and y axes are drawn at 30° and 150°, so a square grid looks isometric
+    Isometric,
+}
+
+impl Projection {
+    /// The linear part of the projection as [a, b, c, d], like `set_transform` takes it
+    fn matrix(&self) -> [f64; 4] {
+        match self {
+            Projection::Plan => [1.0, 0.0, 0.0, 1.0],
+            Projection::Isometric => [ISO_COS, ISO_SIN, -ISO_COS, ISO_SIN],
+        }
+    }
+}
+
+impl ViewTransform {
+    pub fn pixels_per_unit(&self) -> f64 {
+        self.scale_factor * self.zoom
+    }
+
+    pub fn world_to_canvas(&self, p: Point) -> Point {
+        let [a, b, c, d] = self.projection.matrix();
+        let (x, y) = (p.x - self.pan.x, p.y - self.pan.y);
+        Point {
+            x: (a * x + c * y) * self.pixels_per_unit(),
+            y: (b * x + d * y) * self.pixels_per_unit(),
+        }
+    }
+
+    pub fn canvas_to_world(&self, p: Point) -> Point {
+        let [a, b, c, d] = self.projection.matrix();
+        let det = a * d - b * c;
+        let (x, y) = (p.x / self.pixels_per_unit(), p.y / self.pixels_per_unit());
+        Point {
+            x: (d * x - c * y) / det + self.pan.x,
+            y: (a * y - b * x) / det + self.pan.y,
+        }
+    }
+
+    pub fn client_to_world(&self, client: Point, rect: &web_sys::DomRect) -> Point {
+        self.canvas_to_world(Point {
+            x: (client.x - rect.x()) / rect.width() * self.canvas_width,
+            y: (client.y - rect.y()) / rect.height() * self.canvas_height,
+        })
+    }
+
+    pub fn world_to_client(&self, p: Point, rect: &web_sys::DomRect) -> Point {
+        let p = self.world_to_canvas(p);
+        Point {
+            x: p.x / self.canvas_width * rect.width() + rect.x(),
+            y: p.y / self.canvas_height * rect.height() + rect.y(),
+        }
+    }
+
+    /// The world-space (top left, bottom right) of everything currently visible
+    pub fn visible_world_rect(&self) -> (Point, Point) {
+        let corners = [
+            (0.0, 0.0),
+            (self.canvas_width, 0.0),
+            (0.0, self.canvas_height),
+            (self.canvas_width, self.canvas_height),
+        ]
+        .map(|(x, y)| self.canvas_to_world(Point { x, y }));
+
+        corners.iter().fold(
+            (
+                Point {
+                    x: f64::INFINITY,
+                    y: f64::INFINITY,
+                },
+                Point {
+                    x: f64::NEG_INFINITY,
+                    y: f64::NEG_INFINITY,
+                },
+            ),
+            |(min, max), c| {
+                (
+                    Point {
+                        x: min.x.min(c.x),
+                        y: min.y.min(c.y),
+                    },
+                    Point {
+                        x: max.x.max(c.x),
+                        y: max.y.max(c.y),
+                    },
+                )
+            },
+        )
+    }
+
+    /// Sets up `context` so drawing in world coordinates lands right on the backing store
+    pub fn apply(&self, context: &web_sys::CanvasRenderingContext2d) {
+        let ppu = self.pixels_per_unit() * self.dpr;
+        let [a, b, c, d] = self.projection.matrix().map(|m| m * ppu);
+        let (e, f) = (
+            -(a * self.pan.x + c * self.pan.y),
+            -(b * self.pan.x + d * self.pan.y),
+        );
+        context.set_transform(a, b, c, d, e, f).unwrap();
+    }
+}
+
+/// A Catmull-Rom spline through `points`, drawn as cubic Béziers
+fn draw_spline(context: &web_sys::CanvasRenderingContext2d, points: &[Point]) {
+    if points.len() < 2 {
+        return;
+    }
+
+    context.begin_path();
+    context.move_to(points[0].x, points[0].y);
+    let last = points.len() - 1;
+    for i in 0..last {
+        let (p0, p1) = (points[i.saturating_sub(1)], points[i]);
+        let (p2, p3) = (points[i + 1], points[(i + 2).min(last)]);
+        context.bezier_curve_to(
+            p1.x + (p2.x - p0.x) / 6.0,
+            p1.y + (p2.y - p0.y) / 6.0,
+            p2.x - (p3.x - p1.x) / 6.0,
+            p2.y - (p3.y - p1.y) / 6.0,
+            p2.x,
+            p2.y,
+        );
+    }
+    context.stroke();
+}
+
+fn draw_polyline(context: &web_sys::CanvasRenderingContext2d, points: &[Point], smooth: bool) {
+    if points.len() < 2 {
+        return;
+    }
+
+    context.begin_path();
+    context.move_to(points[0].x, points[0].y);
+    if smooth {
+        // Quadratic curves through the midpoints, using the samples as control points
+        for w in points[1..].windows(2) {
+            let mid = Point {
+                x: (w[0].x + w[1].x) / 2.0,
+                y: (w[0].y + w[1].y) / 2.0,
+            };
+            context.quadratic_curve_to(w[0].x, w[0].y, mid.x, mid.y);
+        }
+        let last = points[points.len() - 1];
+        context.line_to(last.x, last.y);
+    } else {
+        for p in points[1..].iter() {
+            context.line_to(p.x, p.y);
+        }
+    }
+    context.stroke();
+}
+
+/// Adds the closed outline of `step` to the current path, or returns false
+fn trace_closed_shape(cx: Scope, context: &web_sys::CanvasRenderingContext2d, step: &Step) -> bool {
+    match step.data {
+        StepData::ShapeBoolean { op, a, b } => {
+            let Ok(rings) = boolean_shape(cx, op.get(), a.get(), b.get()) else {
+                return false;
+            };
+            for ring in rings {
+                context.move_to(ring[0].x, ring[0].y);
+                for p in ring[1..].iter() {
+                    context.line_to(p.x, p.y);
+                }
+                context.close_path();
+            }
+            true
+        }
+        StepData::BoundingBox { sources } => {
+            let Ok((min, max)) = bounding_box(cx, sources) else {
+                return false;
+            };
+            context.rect(min.x, min.y, max.x - min.x, max.y - min.y);
+            true
+        }
+        StepData::DrawSector {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let center = center.get().resolve(cx);
+            context.move_to(center.x, center.y);
+            context
+                .arc(
+                    center.x,
+                    center.y,
+                    radius.get().resolve(cx).abs(),
+                    start_angle.get().resolve_in(cx, AngleUnit::Radians),
+                    end_angle.get().resolve_in(cx, AngleUnit::Radians),
+                )
+                .unwrap();
+            context.close_path();
+            true
+        }
+        StepData::Annulus {
+            center,
+            inner_radius,
+            outer_radius,
+        } => {
+            let center = center.get().resolve(cx);
+            for radius in [outer_radius, inner_radius] {
+                let radius = radius.get().resolve(cx).abs();
+                context.move_to(center.x + radius, center.y);
+                context
+                    .arc(center.x, center.y, radius, 0.0, std::f64::consts::PI * 2.0)
+                    .unwrap();
+                context.close_path();
+            }
+            true
+        }
+        StepData::DrawPolygon {
+            center,
+            radius,
+            sides,
+        } => {
+            let vertices = polygon_vertices(
+                center.get().resolve(cx),
+                radius.get().resolve(cx),
+                polygon_sides(sides.get().resolve(cx)),
+            );
+            context.move_to(vertices[0].x, vertices[0].y);
+            for v in vertices[1..].iter() {
+                context.line_to(v.x, v.y);
+            }
+            context.close_path();
+            true
+        }
+        StepData::DrawRect {
+            origin,
+            width,
+            height,
+        } => {
+            let origin = origin.get().resolve(cx);
+            context.rect(
+                origin.x,
+                origin.y,
+                width.get().resolve(cx),
+                height.get().resolve(cx),
+            );
+            true
+        }
+        StepData::DrawRoundedRect {
+            origin,
+            width,
+            height,
+            radius,
+        } => {
+            let origin = origin.get().resolve(cx);
+            let (w, h) = (width.get().resolve(cx), height.get().resolve(cx));
+            // Normalised so the corners go clockwise from the top left whatever the signs
+            let (left, right) = (origin.x.min(origin.x + w), origin.x.max(origin.x + w));
+            let (top, bottom) = (origin.y.min(origin.y + h), origin.y.max(origin.y + h));
+            let r = radius
+                .get()
+                .resolve(cx)
+                .max(0.0)
+                .min(w.abs() / 2.0)
+                .min(h.abs() / 2.0);
+
+            context.move_to(left + r, top);
+            context.arc_to(right, top, right, bottom, r).unwrap();
+            context.arc_to(right, bottom, left, bottom, r).unwrap();
+            context.arc_to(left, bottom, left, top, r).unwrap();
+            context.arc_to(left, top, right, top, r).unwrap();
+            context.close_path();
+            true
+        }
+        StepData::Freehand { points, .. } => points.with(|points| {
+            let Some(first) = points.first() else {
+                return false;
+            };
+            context.move_to(first.x, first.y);
+            for p in points[1..].iter() {
+                context.line_to(p.x, p.y);
+            }
+            context.close_path();
+            true
+        }),
+        _ => false,
+    }
+}
+
+fn draw_marker(
+    context: &web_sys::CanvasRenderingContext2d,
+    p: Point,
+    marker: PointMarker,
+    size: f64,
+) {
+    context.begin_path();
+    match marker {
+        PointMarker::Dot | PointMarker::Ring => {
+            context
+                .arc(p.x, p.y, size, 0.0, std::f64::consts::PI * 2.0)
+                .unwrap();
+        }
+        PointMarker::Cross => {
+            context.move_to(p.x - size, p.y - size);
+            context.line_to(p.x + size, p.y + size);
+            context.move_to(p.x - size, p.y + size);
+            context.line_to(p.x + size, p.y - size);
+        }
+        PointMarker::Square => context.rect(p.x - size, p.y - size, size * 2.0, size * 2.0),
+    }
+
+    if marker == PointMarker::Dot {
+        // Dots are filled in the stroke color
+        context.save();
+        context.set_fill_style(&context.stroke_style());
+        context.fill();
+        context.restore();
+    } else {
+        context.stroke();
+    }
+}
+
+/// Draws `marker` at `tip`, oriented as though the line arrives there from `from`
+fn draw_line_end_marker(
+    context: &web_sys::CanvasRenderingContext2d,
+    marker: LineEndMarker,
+    tip: Point,
+    from: Point,
+    size: f64,
+) {
+    let angle = (tip.y - from.y).atan2(tip.x - from.x);
+    let at = |angle: f64, len: f64| Point {
+        x: tip.x + angle.cos() * len,
+        y: tip.y + angle.sin() * len,
+    };
+
+    context.begin_path();
+    match marker {
+        LineEndMarker::None => return,
+        LineEndMarker::Arrow => {
+            let spread = std::f64::consts::PI * 5.0 / 6.0;
+            let (left, right) = (at(angle + spread, size), at(angle - spread, size));
+            context.move_to(tip.x, tip.y);
+            context.line_to(left.x, left.y);
+            context.line_to(right.x, right.y);
+            context.close_path();
+        }
+        LineEndMarker::Dot => {
+            context
+                .arc(tip.x, tip.y, size / 2.0, 0.0, std::f64::consts::PI * 2.0)
+                .unwrap();
+        }
+        LineEndMarker::Bar => {
+            let half_pi = std::f64::consts::FRAC_PI_2;
+            let (left, right) = (
+                at(angle + half_pi, size / 2.0),
+                at(angle - half_pi, size / 2.0),
+            );
+            context.move_to(left.x, left.y);
+            context.line_to(right.x, right.y);
+            context.stroke();
+            return;
+        }
+    }
+
+    context.save();
+    context.set_fill_style(&context.stroke_style());
+    context.fill();
+    context.restore();
+}
+
+/// Draws the line from `a` through `b` (both ways with `both_ways`) past the visible area
+fn draw_extended_line(
+    cx: Scope,
+    context: &web_sys::CanvasRenderingContext2d,
+    a: Point,
+    b: Point,
+    both_ways: bool,
+) {
+    let length = a.distance(b);
+    if length == 0.0 {
+        return;
+    }
+
+    let (min, max) = use_context::<RwSignal<ViewTransform>>(cx)
+        .unwrap()
+        .with(|vt| vt.visible_world_rect());
+    let reach = [
+        min,
+        max,
+        Point { x: min.x, y: max.y },
+        Point { x: max.x, y: min.y },
+    ]
+    .iter()
+    .map(|corner| corner.distance(a))
+    .fold(0.0, f64::max)
+        * 2.0;
+    let along = |t: f64| Point {
+        x: a.x + (b.x - a.x) / length * t,
+        y: a.y + (b.y - a.y) / length * t,
+    };
+
+    let from = if both_ways { along(-reach) } else { a };
+    let to = along(reach);
+    context.begin_path();
+    context.move_to(from.x, from.y);
+    context.line_to(to.x, to.y);
+    context.stroke();
+}
+
+fn draw_step(cx: Scope, context: &web_sys::CanvasRenderingContext2d, step: &Step) {
+    match step.data {
+        StepData::DrawPoint {
+            point,
+            marker,
+            size,
+        } => draw_marker(
+            context,
+            point.get().resolve(cx),
+            marker.get(),
+            size.get().resolve(cx),
+        ),
+        StepData::RotateCopy { .. }
+        | StepData::TranslateCopy { .. }
+        | StepData::TransformCopy { .. }
+        | StepData::LinearArray { .. }
+        | StepData::PolarArray { .. } => {
+            let Ok((source, transforms)) = copy_parts(cx, step.data) else {
+                return;
+            };
+            for transform in transforms {
+                context.save();
+                match transform {
+                    Transform::Translate { dx, dy } => context.translate(dx, dy).unwrap(),
+                    Transform::Rotate { about, angle } => {
+                        context.translate(about.x, about.y).unwrap();
+                        context.rotate(angle.to_radians()).unwrap();
+                        context.translate(-about.x, -about.y).unwrap();
+                    }
+                    Transform::Scale { about, factor } => {
+                        context.translate(about.x, about.y).unwrap();
+                        context.scale(factor, factor).unwrap();
+                        context.translate(-about.x, -about.y).unwrap();
+                    }
+                    Transform::Matrix { a, b, c, d, e, f } => {
+                        context.transform(a, b, c, d, e, f).unwrap()
+                    }
+                }
+                draw_step(cx, context, &source);
+                context.restore();
+            }
+        }
+        StepData::Mirror { source, axis } => {
+            let Ok((source, (a, b))) = mirror_parts(cx, source.get(), axis.get()) else {
+                return;
+            };
+            // Reflection across the axis, so the source draws itself mirrored
+            let Transform::Matrix { a, b, c, d, e, f } = Transform::reflection(a, b) else {
+                unreachable!()
+            };
+            context.save();
+            context.transform(a, b, c, d, e, f).unwrap();
+            draw_step(cx, context, &source);
+            context.restore();
+        }
+        StepData::PointGrid { .. } => {
+            for r in step.snap_points(cx) {
+                draw_marker(
+                    context,
+                    ResolveToPoint::resolve(&r, cx),
+                    PointMarker::Cross,
+                    0.5,
+                );
+            }
+        }
+        // Drawn as the two arms with an arc between them
+        StepData::Angle { a, vertex, b } => {
+            let (a, vertex, b) = (
+                a.get().resolve(cx),
+                vertex.get().resolve(cx),
+                b.get().resolve(cx),
+            );
+            context.begin_path();
+            context.move_to(a.x, a.y);
+            context.line_to(vertex.x, vertex.y);
+            context.line_to(b.x, b.y);
+            context.stroke();
+
+            let radius = a.distance(vertex).min(b.distance(vertex)) / 3.0;
+            let from = (a.y - vertex.y).atan2(a.x - vertex.x);
+            let to = from + angle_between(a, vertex, b).to_radians();
+            context.begin_path();
+            context
+                .arc_with_anticlockwise(vertex.x, vertex.y, radius, from, to, to < from)
+                .unwrap();
+            context.stroke();
+        }
+        // Drawn as a dimension line, with bars across both ends
+        StepData::Distance { a, b } => {
+            let (a, b) = (a.get().resolve(cx), b.get().resolve(cx));
+            context.begin_path();
+            context.move_to(a.x, a.y);
+            context.line_to(b.x, b.y);
+            context.stroke();
+            draw_line_end_marker(context, LineEndMarker::Bar, a, b, 1.0);
+            draw_line_end_marker(context, LineEndMarker::Bar, b, a, 1.0);
+        }
+        StepData::Midpoint { .. }
+        | StepData::Centroid { .. }
+        | StepData::PointAlongLine { .. }
+        | StepData::Projection { .. }
+        | StepData::Intersection { .. } => draw_marker(
+            context,
+            ResolveToPoint::resolve(&step.prop_ref("self"), cx),
+            PointMarker::Cross,
+            0.5,
+        ),
+        StepData::DrawLine { start, end } => {
+            let start: Point = start().resolve(cx);
+            let end: Point = end().resolve(cx);
+
+            context.begin_path();
+            context.move_to(start.x, start.y);
+            context.line_to(end.x, end.y);
+            context.stroke();
+        }
+        StepData::DrawRay { .. }
+        | StepData::DrawInfiniteLine { .. }
+        | StepData::Perpendicular { .. }
+        | StepData::Tangent { .. }
+        | StepData::ParallelOffset { .. }
+        | StepData::TrimExtend { .. } => {
+            let Some((a, b)) = step.line_points(cx) else {
+                return;
+            };
+            match step.line_extent(cx) {
+                LineExtent::Segment => {
+                    context.begin_path();
+                    context.move_to(a.x, a.y);
+                    context.line_to(b.x, b.y);
+                    context.stroke();
+                }
+                LineExtent::Ray => draw_extended_line(cx, context, a, b, false),
+                LineExtent::Infinite => draw_extended_line(cx, context, a, b, true),
+            }
+        }
+        StepData::DrawArrow {
+            start,
+            end,
+            head_size,
+        } => {
+            let start: Point = start().resolve(cx);
+            let end: Point = end().resolve(cx);
+
+            context.begin_path();
+            context.move_to(start.x, start.y);
+            context.line_to(end.x, end.y);
+            context.stroke();
+            draw_line_end_marker(
+                context,
+                LineEndMarker::Arrow,
+                end,
+                start,
+                head_size.get().resolve(cx),
+            );
+        }
+        StepData::DrawImage {
+            src,
+            position,
+            scale,
+        } => {
+            let Some(image) = src.with(|src| cached_image(cx, src)) else {
+                return;
+            };
+            let position = position.get().resolve(cx);
+            let scale = scale.get().resolve(cx);
+            context
+                .draw_image_with_html_image_element_and_dw_and_dh(
+                    &image,
+                    position.x,
+                    position.y,
+                    image.natural_width() as f64 * scale,
+                    image.natural_height() as f64 * scale,
+                )
+                .unwrap();
+        }
+        StepData::DrawText {
+            anchor,
+            content,
+            size,
+            path,
+        } => {
+            let anchor = anchor.get().resolve(cx);
+            let path = path
+                .get()
+                .and_then(|r| r.step_id())
+                .and_then(|id| step_by_id(cx, id))
+                .and_then(|s| s.paths(cx))
+                .and_then(|paths| paths.into_iter().next())
+                .map(|(mut points, closed)| {
+                    if closed {
+                        points.extend(points.first().copied());
+                    }
+                    points
+                });
+
+            // Text is filled in the stroke color, like dots
+            context.save();
+            context.set_font(&format!("{}px sans-serif", size.get().resolve(cx)));
+            context.set_fill_style(&context.stroke_style());
+            match path {
+                // Each glyph centred on the path and turned to follow it, dropping any past its end
+                Some(points) => content.with(|content| {
+                    let mut along = 0.0;
+                    for glyph in content.chars() {
+                        let glyph = glyph.to_string();
+                        let width = context.measure_text(&glyph).unwrap().width();
+                        let Some((at, angle)) = polyline_at(&points, along + width / 2.0) else {
+                            break;
+                        };
+                        context.save();
+                        context.translate(at.x, at.y).unwrap();
+                        context.rotate(angle).unwrap();
+                        context.fill_text(&glyph, -width / 2.0, 0.0).unwrap();
+                        context.restore();
+                        along += width;
+                    }
+                }),
+                None => {
+                    content.with(|content| context.fill_text(content, anchor.x, anchor.y).unwrap())
+                }
+            }
+            context.restore();
+        }
+        StepData::ScaleBar {
+            origin,
+            length,
+            divisions,
+            unit,
+            size,
+        } => {
+            let origin = origin.get().resolve(cx);
+            let length = length.get().resolve(cx);
+            let size = size.get().resolve(cx);
+            let Ok(divisions) = array_count(divisions.get().resolve(cx)) else {
+                return;
+            };
+
+            // Alternate divisions are filled, like a map's scale bar
+            let (width, height) = (length / divisions as f64, size / 2.0);
+            context.save();
+            context.set_fill_style(&context.stroke_style());
+            for i in 0..divisions {
+                let x = origin.x + width * i as f64;
+                if i % 2 == 0 {
+                    context.fill_rect(x, origin.y, width, height);
+                }
+                context.stroke_rect(x, origin.y, width, height);
+            }
+
+            context.set_font(&format!("{}px sans-serif", size));
+            let label_y = origin.y + height + size;
+            context.set_text_align("left");
+            context.fill_text("0", origin.x, label_y).unwrap();
+            context.set_text_align("right");
+            // To 3 places, like the number readouts, so computed lengths don't trail digits
+            let rounded = (length * 1e3).round() / 1e3;
+            unit.with(|unit| {
+                context
+                    .fill_text(&format!("{} {}", rounded, unit), origin.x + length, label_y)
+                    .unwrap()
+            });
+            context.restore();
+        }
+        StepData::Axes { origin, size, .. } => {
+            let origin = origin.get().resolve(cx);
+            let size = size.get().resolve(cx);
+            let Ok((x_ticks, y_ticks)) = axes_ticks(cx, step.data) else {
+                return;
+            };
+            let prop = |prop_name| ResolveToPoint::resolve(&step.prop_ref(prop_name), cx);
+            let (x_start, x_end) = (prop("x_start"), prop("x_end"));
+            let (y_start, y_end) = (prop("y_start"), prop("y_end"));
+
+            context.begin_path();
+            context.move_to(x_start.x, x_start.y);
+            context.line_to(x_end.x, x_end.y);
+            context.move_to(y_start.x, y_start.y);
+            context.line_to(y_end.x, y_end.y);
+            let tick = size / 4.0;
+            for &x in &x_ticks {
+                context.move_to(origin.x + x, origin.y - tick);
+                context.line_to(origin.x + x, origin.y + tick);
+            }
+            for &y in &y_ticks {
+                context.move_to(origin.x - tick, origin.y - y);
+                context.line_to(origin.x + tick, origin.y - y);
+            }
+            context.stroke();
+            draw_line_end_marker(context, LineEndMarker::Arrow, x_end, x_start, size / 2.0);
+            draw_line_end_marker(context, LineEndMarker::Arrow, y_end, y_start, size / 2.0);
+
+            // The origin's 0 is left off both axes, where it would sit on the lines
+            let label = |value: f64| format!("{}", (value * 1e6).round() / 1e6);
+            context.save();
+            context.set_font(&format!("{}px sans-serif", size));
+            context.set_fill_style(&context.stroke_style());
+            context.set_text_align("center");
+            context.set_text_baseline("top");
+            for &x in x_ticks.iter().filter(|&&x| x != 0.0) {
+                context
+                    .fill_text(&label(x), origin.x + x, origin.y + tick * 2.0)
+                    .unwrap();
+            }
+            context
+                .fill_text("x", x_end.x + size, x_end.y - size / 2.0)
+                .unwrap();
+            context.set_text_align("right");
+            context.set_text_baseline("middle");
+            for &y in y_ticks.iter().filter(|&&y| y != 0.0) {
+                context
+                    .fill_text(&label(y), origin.x - tick * 2.0, origin.y - y)
+                    .unwrap();
+            }
+            context.set_text_align("center");
+            context.set_text_baseline("bottom");
+            context.fill_text("y", y_end.x, y_end.y - size).unwrap();
+            context.restore();
+        }
+        StepData::Callout {
+            anchor,
+            tip,
+            content,
+            size,
+        } => {
+            let (anchor, tip) = (anchor.get().resolve(cx), tip.get().resolve(cx));
+            let size = size.get().resolve(cx);
+
+            context.begin_path();
+            context.move_to(anchor.x, anchor.y);
+            context.line_to(tip.x, tip.y);
+            context.stroke();
+            draw_line_end_marker(context, LineEndMarker::Arrow, tip, anchor, size / 2.0);
+
+            context.save();
+            context.set_font(&format!("{}px sans-serif", size));
+            context.set_fill_style(&context.stroke_style());
+            content.with(|content| context.fill_text(content, anchor.x, anchor.y).unwrap());
+            context.restore();
+        }
+        StepData::DrawPath { points } => {
+            let points: Vec<Point> = points
+                .get()
+                .into_iter()
+                .map(|p| p.get().resolve(cx))
+                .collect();
+            draw_polyline(context, &points, false);
+        }
+        StepData::Spline { points } => {
+            let points: Vec<Point> = points
+                .get()
+                .into_iter()
+                .map(|p| p.get().resolve(cx))
+                .collect();
+            draw_spline(context, &points);
+        }
+        StepData::DrawRect { .. }
+        | StepData::DrawRoundedRect { .. }
+        | StepData::DrawPolygon { .. }
+        | StepData::DrawSector { .. }
+        | StepData::Annulus { .. }
+        | StepData::BoundingBox { .. }
+        | StepData::ShapeBoolean { .. } => {
+            context.begin_path();
+            trace_closed_shape(cx, context, step);
+            context.stroke();
+        }
+        StepData::DrawArc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let center = center.get().resolve(cx);
+
+            context.begin_path();
+            context
+                .arc(
+                    center.x,
+                    center.y,
+                    radius.get().resolve(cx).abs(),
+                    start_angle.get().resolve_in(cx, AngleUnit::Radians),
+                    end_angle.get().resolve_in(cx, AngleUnit::Radians),
+                )
+                .unwrap();
+            context.stroke();
+        }
+        StepData::Freehand { points, smooth } => {
+            points.with(|points| draw_polyline(context, points, smooth.get()));
+        }
+        StepData::FunctionPlot { .. } | StepData::ParametricCurve { .. } => {
+            for run in step.data.curve_runs(cx).unwrap_or_default() {
+                draw_polyline(context, &run, false);
+            }
+        }
+        StepData::OffsetCurve { .. } => {
+            for (points, closed) in step.paths(cx).unwrap_or_default() {
+                if !closed {
+                    draw_polyline(context, &points, false);
+                    continue;
+                }
+                context.begin_path();
+                context.move_to(points[0].x, points[0].y);
+                for p in points[1..].iter() {
+                    context.line_to(p.x, p.y);
+                }
+                context.close_path();
+                context.stroke();
+            }
+        }
+        StepData::Clip { shape } => {
+            // Saved here, restored by `draw_steps` once everything it covers is drawn
+            context.save();
+            let shape = shape.get().and_then(|r| r.step_id()).and_then(|id| {
+                use_context::<RwSignal<Vec<Step>>>(cx)
+                    .unwrap()
+                    .with(|steps| steps.iter().find(|s| s.id == id).cloned())
+            });
+            if let Some(shape) = shape {
+                context.begin_path();
+                if trace_closed_shape(cx, context, &shape) {
+                    context.clip_with_canvas_winding_rule(shape.fill_rule());
+                }
+            }
+        }
+    }
+}
+
+fn fill_step(cx: Scope, context: &web_sys::CanvasRenderingContext2d, step: &Step) {
+    let fill_style: wasm_bindgen::JsValue = match step_style(cx, step).fill {
+        Fill::None => return,
+        Fill::Solid(color) => wasm_bindgen::JsValue::from_str(&color.resolve_color(cx)),
+        Fill::LinearGradient {
+            start,
+            end,
+            from,
+            to,
+        } => {
+            let (start, end) = (start.get().resolve(cx), end.get().resolve(cx));
+            let gradient = context.create_linear_gradient(start.x, start.y, end.x, end.y);
+            gradient.add_color_stop(0.0, &from).unwrap();
+            gradient.add_color_stop(1.0, &to).unwrap();
+            gradient.into()
+        }
+        Fill::RadialGradient {
+            center,
+            radius,
+            from,
+            to,
+        } => {
+            let center = center.get().resolve(cx);
+            let gradient = context
+                .create_radial_gradient(
+                    center.x,
+                    center.y,
+                    0.0,
+                    center.x,
+                    center.y,
+                    radius.get().resolve(cx).abs(),
+                )
+                .unwrap();
+            gradient.add_color_stop(0.0, &from).unwrap();
+            gradient.add_color_stop(1.0, &to).unwrap();
+            gradient.into()
+        }
+    };
+
+    context.begin_path();
+    if trace_closed_shape(cx, context, step) {
+        context.set_fill_style(&fill_style);
+        context.fill_with_canvas_winding_rule(step.fill_rule());
+    }
+}
+
+/// Images of image steps by src, loaded the first time they're drawn
+#[derive(Clone, Debug, Default)]
+pub struct ImageCache {
+    loaded: HashMap<String, web_sys::HtmlImageElement>,
+    loading: Vec<String>,
+}
+
+/// The image at `src` if it has loaded, otherwise starts loading it
+fn cached_image(cx: Scope, src: &str) -> Option<web_sys::HtmlImageElement> {
+    if src.is_empty() {
+        return None;
+    }
+
+    let cache = use_context::<RwSignal<ImageCache>>(cx).unwrap();
+    if let Some(image) = cache.with(|c| c.loaded.get(src).cloned()) {
+        return Some(image);
+    }
+    if cache.with(|c| c.loading.iter().any(|s| s == src)) {
+        return None;
+    }
+
+    cache.update_untracked(|c| c.loading.push(src.to_string()));
+    let image = web_sys::HtmlImageElement::new().unwrap();
+    let image_clone = image.clone();
+    let src_clone = src.to_string();
+    let onload = wasm_bindgen::prelude::Closure::<dyn Fn()>::new(move || {
+        cache.update(|c| {
+            c.loading.retain(|s| *s != src_clone);
+            c.loaded.insert(src_clone.clone(), image_clone.clone());
+        });
+    })
+    .into_js_value();
+    image.set_onload(Some(onload.as_ref().unchecked_ref()));
+    image.set_src(src);
+    None
+}
+
+/// Stroke color of construction steps, whatever their own style says
+const CONSTRUCTION_STROKE: &str = "#94a3b8";
+
+/// Draws `steps` in order (images first), undoing any clipping they set up afterwards
+fn draw_steps<'a>(
+    cx: Scope,
+    context: &web_sys::CanvasRenderingContext2d,
+    steps: impl Iterator<Item = &'a Step>,
+) {
+    let pixels_per_unit = use_context::<RwSignal<ViewTransform>>(cx)
+        .unwrap()
+        .with(|vt| vt.pixels_per_unit());
+
+    // Images go beneath everything else but still inside earlier clips, so clips run twice
+    let steps: Vec<&Step> = steps.collect();
+    for images in [true, false] {
+        let mut clips = 0;
+        for step in steps.iter().filter(|s| match s.data {
+            StepData::DrawImage { .. } => images,
+            StepData::Clip { .. } => true,
+            _ => !images,
+        }) {
+            if let StepData::Clip { .. } = step.data {
+                clips += 1;
+            }
+
+            let construction = step.construction.get();
+            let style = step_style(cx, step);
+            let stroke = if construction {
+                CONSTRUCTION_STROKE.to_string()
+            } else {
+                style.stroke.resolve_color(cx)
+            };
+            context.set_stroke_style(&wasm_bindgen::JsValue::from_str(&stroke));
+            context.set_line_width(style.line_width / pixels_per_unit);
+            context.set_line_cap(style.line_cap.as_str());
+            context.set_line_join(style.line_join.as_str());
+            let dash = if construction {
+                js_sys::Array::of2(
+                    &(6.0 / pixels_per_unit).into(),
+                    &(4.0 / pixels_per_unit).into(),
+                )
+            } else {
+                style
+                    .dash
+                    .pattern(style.line_width)
+                    .into_iter()
+                    .map(|d| wasm_bindgen::JsValue::from_f64(d / pixels_per_unit))
+                    .collect()
+            };
+            context.set_line_dash(&dash).unwrap();
+
+            match &style.shadow {
+                Some(shadow) => {
+                    context.set_shadow_blur(shadow.blur);
+                    context.set_shadow_offset_x(shadow.offset_x);
+                    context.set_shadow_offset_y(shadow.offset_y);
+                    context.set_shadow_color(&shadow.color);
+                }
+                None => context.set_shadow_color("transparent"),
+            }
+            if construction {
+                context.set_shadow_color("transparent");
+            } else {
+                fill_step(cx, context, step);
+            }
+            draw_step(cx, context, step);
+
+            if let Some([start, end]) = step.line_ends(cx) {
+                draw_line_end_marker(
+                    context,
+                    style.start_marker,
+                    start.0,
+                    start.1,
+                    style.end_marker_size,
+                );
+                draw_line_end_marker(
+                    context,
+                    style.end_marker,
+                    end.0,
+                    end.1,
+                    style.end_marker_size,
+                );
+            }
+        }
+        context.set_shadow_color("transparent");
+        context.set_line_dash(&js_sys::Array::new()).unwrap();
+        for _ in 0..clips {
+            context.restore();
+        }
+    }
+}
+
+/// In print mode the drawing fills the page, without the sidebar or editing overlays
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DisplayMode {
+    #[default]
+    Edit,
+    Print,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CanvasTool {
+    /// Clicks place pending infer targets
+    #[default]
+    Pointer,
+    /// Dragging records a freehand stroke
+    Sketch,
+    /// Clicks add a point step at the clicked position, for tracing the background
+    Digitize,
+    /// The next two clicks mark a known distance on the background (see `Calibration`)
+    Calibrate,
+    /// Dragging sets the components of vector data to the drag's
+    DragVector {
+        dx: RwSignal<f64>,
+        dy: RwSignal<f64>,
+    },
+}
+
+/// Two clicks on the background image spanning a known distance, for rescaling it
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Calibration {
+    pub points: Vec<Point>,
+}
+
+impl Calibration {
+    /// Rescales `background` about the first point so the two end up `distance` apart
+    pub fn apply(&self, background: &mut BackgroundImage, distance: f64) {
+        let [a, b] = self.points[..] else {
+            return;
+        };
+        let measured = a.distance(b);
+        if measured == 0.0 || distance <= 0.0 {
+            return;
+        }
+
+        let factor = distance / measured;
+        background.scale *= factor;
+        background.position = Point {
+            x: a.x + (background.position.x - a.x) * factor,
+            y: a.y + (background.position.y - a.y) * factor,
+        };
+    }
+}
+
+/// A `length` ref (or `value` ref) to the first line step within `radius` of `p`
+fn line_near(cx: Scope, steps: &[Step], p: Point, radius: f64) -> Option<DataRef> {
+    steps.iter().find_map(|step| match step.data {
+        StepData::DrawLine { start, end } | StepData::DrawArrow { start, end, .. } => {
+            let dist = distance_to_segment(p, start.get().resolve(cx), end.get().resolve(cx));
+            (dist < radius).then(|| step.prop_ref("length"))
+        }
+        StepData::Distance { a, b } => {
+            let dist = distance_to_segment(p, a.get().resolve(cx), b.get().resolve(cx));
+            (dist < radius).then(|| step.prop_ref("value"))
+        }
+        _ => None,
+    })
+}
+
+/// A step's fully literal point within `radius` of `p`, along with the step's id
+fn draggable_point_near(steps: &[Step], p: Point, radius: f64) -> Option<(usize, PointSignal)> {
+    steps
+        .iter()
+        .filter(|s| is_resolvable(steps, s))
+        .find_map(|step| {
+            step.infer_targets().into_iter().find_map(|t| {
+                let InferTarget::Point(sig) = t else {
+                    return None;
+                };
+                let ResolvableTo::T(ps) = sig.get() else {
+                    return None;
+                };
+                let (ResolvableTo::T(x), ResolvableTo::T(y)) = (ps.x.get(), ps.y.get()) else {
+                    return None;
+                };
+                let at = Point {
+                    x: x.get(),
+                    y: y.get(),
+                };
+                (at.distance(p) < radius).then_some((step.id, ps))
+            })
+        })
+}
+
+/// The x and y of snap points outside `exclude_step` which `p` lines up with
+fn alignment_guides(
+    cx: Scope,
+    snap_points: &[DataRef],
+    exclude_step: usize,
+    p: Point,
+    threshold: f64,
+) -> (Option<f64>, Option<f64>) {
+    let mut guides: (Option<f64>, Option<f64>) = (None, None);
+    for sp in snap_points
+        .iter()
+        .filter(|sp| sp.step_id() != Some(exclude_step))
+    {
+        let sp = ResolveToPoint::resolve(sp, cx);
+        if (sp.x - p.x).abs() < threshold
+            && guides
+                .0
+                .map_or(true, |x| (sp.x - p.x).abs() < (x - p.x).abs())
+        {
+            guides.0 = Some(sp.x);
+        }
+        if (sp.y - p.y).abs() < threshold
+            && guides
+                .1
+                .map_or(true, |y| (sp.y - p.y).abs() < (y - p.y).abs())
+        {
+            guides.1 = Some(sp.y);
+        }
+    }
+    guides
+}
+
+fn context_2d(canvas: &web_sys::HtmlCanvasElement) -> web_sys::CanvasRenderingContext2d {
+    canvas
+        .get_context("2d")
+        .unwrap()
+        .unwrap()
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .unwrap()
+}
+
+/// World-space distance under which sketch samples are dropped by simplification
+const SKETCH_TOLERANCE: f64 = 0.15;
+/// World-space distance from a literal point within which a press starts dragging it
+const DRAG_RADIUS: f64 = 1.5;
+/// World-space distance within which a dragged point snaps onto an alignment guide
+const GUIDE_SNAP: f64 = 0.5;
+
+#[component]
+pub fn DrawlingCanvasView(cx: Scope, steps: RwSignal<Vec<Step>>) -> impl IntoView {
+    let view_transform = use_context::<RwSignal<ViewTransform>>(cx).unwrap();
+    let snap_settings = use_context::<RwSignal<SnapSettings>>(cx).unwrap();
+    let display_mode = use_context::<RwSignal<DisplayMode>>(cx).unwrap();
+
+    let canvas = view! { cx,
+        <canvas class="border-2 border-gray-800 max-w-screen max-h-screen print:border-0 print:!w-full print:h-auto print:max-h-none" />
+    };
+    let canvas_clone_mousemove = canvas.clone();
+
+    let (mouse_pos, set_mouse_pos) = create_signal(cx, Point::default());
+
+    let canvas_tool = use_context::<RwSignal<CanvasTool>>(cx).unwrap();
+    let calibration = use_context::<RwSignal<Calibration>>(cx).unwrap();
+    let last_pointer = use_context::<RwSignal<LastPointer>>(cx).unwrap();
+    // Raw pointer samples of the stroke currently being sketched
+    let sketch_samples = create_rw_signal::<Option<Vec<Point>>>(cx, None);
+    // Where the vector being dragged out started
+    let vector_drag = create_rw_signal::<Option<Point>>(cx, None);
+
+    // A signal so it can be fetched again after the browser throws the canvas away
+    let context = create_rw_signal(cx, context_2d(&canvas));
+
+    let document_settings = use_context::<RwSignal<DocumentSettings>>(cx).unwrap();
+    let canvas_clone_resize = canvas.clone();
+    // Resizing clears the canvas, so only the active artboard and scale trigger it
+    let canvas_extent = create_memo(cx, move |_| {
+        document_settings.with(|s| (s.active().clone(), s.scale_factor))
+    });
+    create_effect(cx, move |_| {
+        let (artboard, scale_factor) = canvas_extent.get();
+        let canvas_width = (artboard.width * scale_factor).ceil();
+        let canvas_height = (artboard.height * scale_factor).ceil();
+        let dpr = window().device_pixel_ratio();
+
+        // The backing store gets the device pixels; the CSS width keeps the natural size
+        canvas_clone_resize.set_width((canvas_width * dpr).ceil() as u32);
+        canvas_clone_resize.set_height((canvas_height * dpr).ceil() as u32);
+        canvas_clone_resize
+            .set_attribute("style", &format!("width: {}px", canvas_width))
+            .unwrap();
+        view_transform.update(|vt| {
+            vt.scale_factor = scale_factor;
+            vt.pan = artboard.origin;
+            vt.canvas_width = canvas_width;
+            vt.canvas_height = canvas_height;
+            vt.dpr = dpr;
+        });
+    });
+
+    // The loaded background image, once it has finished loading
+    let background_image = create_rw_signal::<Option<web_sys::HtmlImageElement>>(cx, None);
+    let background_src = create_memo(cx, move |_| {
+        document_settings.with(|s| s.background.as_ref().map(|b| b.src.clone()))
+    });
+    create_effect(cx, move |_| {
+        background_image.set(None);
+        let Some(src) = background_src.get() else {
+            return;
+        };
+
+        let image = web_sys::HtmlImageElement::new().unwrap();
+        let image_clone = image.clone();
+        let onload = wasm_bindgen::prelude::Closure::<dyn Fn()>::new(move || {
+            background_image.set(Some(image_clone.clone()));
+        })
+        .into_js_value();
+        image.set_onload(Some(onload.as_ref().unchecked_ref()));
+        image.set_src(&src);
+    });
+    // Offset from the background's corner to the mouse, while dragging the background
+    let background_drag = create_rw_signal::<Option<Point>>(cx, None);
+    // The literal point being dragged, and the step it belongs to
+    let point_drag = create_rw_signal::<Option<(usize, PointSignal)>>(cx, None);
+    // Alignment guides (x of the vertical one, y of the horizontal one) while dragging
+    let guides = create_rw_signal::<(Option<f64>, Option<f64>)>(cx, (None, None));
+
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+    let snap_points: Memo<Vec<DataRef>> = create_memo(cx, move |_| {
+        console_log("Memoizing snap points!");
+        let mut snap_points: Vec<DataRef> = steps.with(|all| {
+            all.iter()
+                .filter(|s| is_resolvable(all, s))
+                .map(|s| s.snap_points(cx))
+                .flatten()
+                .collect()
+        });
+        // Point data too, so it can be picked as a shared position
+        snap_points.extend(datas.with(|datas| {
+            datas
+                .iter()
+                .filter(|d| !d.trashed.get())
+                .flat_map(|d| data_points(d))
+                .collect::<Vec<_>>()
+        }));
+        snap_points
+    });
+
+    // todo(chad): make mouse_pos a PointSignal
+    let hover_infer_target = create_rw_signal(
+        cx,
+        Some(ResolvableTo::T(PointSignal {
+            x: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, mouse_pos().x))),
+            y: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, mouse_pos().y))),
+        })),
+    );
+
+    let mousemove_callback = move |e: web_sys::MouseEvent| {
+        let rect = canvas_clone_mousemove.get_bounding_client_rect();
+        let client = Point {
+            x: e.client_x() as f64,
+            y: e.client_y() as f64,
+        };
+        let world = view_transform.with(|vt| vt.client_to_world(client, &rect));
+        set_mouse_pos.set(world);
+        last_pointer.set(LastPointer(Some(world)));
+
+        if sketch_samples.with(|s| s.is_some()) {
+            sketch_samples.update(|s| s.as_mut().unwrap().push(world));
+        }
+
+        if let (Some(start), CanvasTool::DragVector { dx, dy }) =
+            (vector_drag.get(), canvas_tool.get())
+        {
+            let end = snap_settings.with(|snap| snap.snap_to_grid(world));
+            dx.set(end.x - start.x);
+            dy.set(end.y - start.y);
+        }
+
+        if let Some((step_id, point)) = point_drag.get() {
+            let mut p = snap_settings.with(|snap| snap.snap_to_grid(world));
+            let (guide_x, guide_y) =
+                snap_points.with(|sps| alignment_guides(cx, sps, step_id, p, GUIDE_SNAP));
+            p.x = guide_x.unwrap_or(p.x);
+            p.y = guide_y.unwrap_or(p.y);
+            guides.set((guide_x, guide_y));
+
+            if let (ResolvableTo::T(x), ResolvableTo::T(y)) = (point.x.get(), point.y.get()) {
+                x.set(p.x);
+                y.set(p.y);
+            }
+        }
+
+        if let Some(offset) = background_drag.get() {
+            document_settings.update(|s| {
+                if let Some(background) = &mut s.background {
+                    background.position = Point {
+                        x: world.x - offset.x,
+                        y: world.y - offset.y,
+                    };
+                }
+            });
+        }
+    };
+    let mousemove_closure =
+        wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(mousemove_callback).into_js_value();
+    canvas
+        .add_event_listener_with_callback("mousemove", mousemove_closure.as_ref().unchecked_ref())
+        .unwrap();
+
+    let mousedown_callback = move |_e: web_sys::MouseEvent| {
+        match canvas_tool.get() {
+            CanvasTool::Pointer => {}
+            CanvasTool::Sketch => {
+                sketch_samples.set(Some(vec![mouse_pos.get()]));
+                return;
+            }
+            CanvasTool::Digitize => {
+                push_step(
+                    cx,
+                    StepData::DrawPoint {
+                        point: create_rw_signal(
+                            cx,
+                            ResolvableTo::T(PointSignal::literal(cx, mouse_pos.get())),
+                        ),
+                        marker: create_rw_signal(cx, PointMarker::default()),
+                        size: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                    },
+                );
+                return;
+            }
+            CanvasTool::DragVector { .. } => {
+                vector_drag.set(Some(
+                    snap_settings.with(|snap| snap.snap_to_grid(mouse_pos.get())),
+                ));
+                return;
+            }
+            CanvasTool::Calibrate => {
+                calibration.update(|c| {
+                    if c.points.len() == 2 {
+                        c.points.clear();
+                    }
+                    c.points.push(mouse_pos.get());
+                });
+                if calibration.with(|c| c.points.len() == 2) {
+                    canvas_tool.set(CanvasTool::Pointer);
+                }
+                return;
+            }
+        }
+
+        let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+
+        if context_infer_target.get().is_none() {
+            let grabbed =
+                steps.with(|steps| draggable_point_near(steps, mouse_pos.get(), DRAG_RADIUS));
+            if grabbed.is_some() {
+                point_drag.set(grabbed);
+                return;
+            }
+        }
+
+        // With nothing else to do, clicks on an unlocked background start dragging it
+        if context_infer_target.get().is_none() {
+            let mouse = mouse_pos.get();
+            let grabbed = document_settings.with(|s| {
+                let background = s.background.as_ref().filter(|b| !b.locked)?;
+                let image = background_image.get()?;
+                let (w, h) = (
+                    image.natural_width() as f64 * background.scale,
+                    image.natural_height() as f64 * background.scale,
+                );
+                let offset = Point {
+                    x: mouse.x - background.position.x,
+                    y: mouse.y - background.position.y,
+                };
+                (offset.x >= 0.0 && offset.y >= 0.0 && offset.x <= w && offset.y <= h)
+                    .then_some(offset)
+            });
+            background_drag.set(grabbed);
+            return;
+        }
+
+        match (context_infer_target.get(), hover_infer_target.get()) {
+            (Some(InferTarget::Point(it)), Some(hover_infer_target)) => {
+                commit_binding_preview(cx);
+                it.set(hover_infer_target);
+                advance_placement(cx);
+            }
+            (Some(InferTarget::Eyedropper { target, coord }), hover) => {
+                let picked = match (hover, coord) {
+                    (Some(ResolvableTo::Ref(r)), Some(coord)) => {
+                        let mut path = r.0;
+                        path.push(DataRefPathEl::PropName(coord));
+                        Some(DataRef(path))
+                    }
+                    _ => steps.with(|steps| line_near(cx, steps, mouse_pos.get(), 1.0)),
+                };
+                // e.g. a circle's radius can't be picked off its own edge
+                let picked = picked.filter(|picked| {
+                    steps.with(|steps| {
+                        datas.with(|datas| {
+                            binding_allowed(steps, datas, &InferTarget::Number(target), picked)
+                        })
+                    })
+                });
+                if let Some(picked) = picked {
+                    target.set(ResolvableTo::Ref(picked));
+                    context_infer_target.set(None);
+                }
+            }
+            (Some(InferTarget::Redirect(step_id)), Some(ResolvableTo::Ref(r)))
+                if r.step_id() != Some(step_id) =>
+            {
+                redirect_step_refs(cx, step_id, &r);
+                remove_step(cx, step_id);
+                context_infer_target.set(None);
+            }
+            _ => {}
+        }
+    };
+    let mousedown_closure =
+        wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(mousedown_callback).into_js_value();
+    canvas
+        .add_event_listener_with_callback("mousedown", mousedown_closure.as_ref().unchecked_ref())
+        .unwrap();
+
+    let mouseup_callback = move |_e: web_sys::MouseEvent| {
+        background_drag.set(None);
+        point_drag.set(None);
+        guides.set((None, None));
+
+        if vector_drag.get().is_some() {
+            vector_drag.set(None);
+            canvas_tool.set(CanvasTool::Pointer);
+            return;
+        }
+
+        let Some(samples) = sketch_samples.get() else {
+            return;
+        };
+        sketch_samples.set(None);
+
+        let points = simplify_polyline(&samples, SKETCH_TOLERANCE);
+        if points.len() < 2 {
+            return;
+        }
+
+        push_step(
+            cx,
+            StepData::Freehand {
+                points: create_rw_signal(cx, points),
+                smooth: create_rw_signal(cx, true),
+            },
+        );
+    };
+    let mouseup_closure =
+        wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(mouseup_callback).into_js_value();
+    canvas
+        .add_event_listener_with_callback("mouseup", mouseup_closure.as_ref().unchecked_ref())
+        .unwrap();
+
+    // Double-clicking empty canvas drops a point there
+    let dblclick_callback = move |_e: web_sys::MouseEvent| {
+        let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+        if canvas_tool.get() != CanvasTool::Pointer || context_infer_target.get().is_some() {
+            return;
+        }
+        let mouse = mouse_pos.get();
+        let occupied = steps.with(|steps| {
+            draggable_point_near(steps, mouse, DRAG_RADIUS).is_some()
+                || line_near(cx, steps, mouse, 1.0).is_some()
+        });
+        if occupied {
+            return;
+        }
+
+        let at = snap_settings.with(|snap| snap.snap_to_grid(mouse));
+        push_step(
+            cx,
+            StepData::DrawPoint {
+                point: create_rw_signal(cx, ResolvableTo::T(PointSignal::literal(cx, at))),
+                marker: create_rw_signal(cx, PointMarker::default()),
+                size: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+            },
+        );
+    };
+    let dblclick_closure =
+        wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(dblclick_callback).into_js_value();
+    canvas
+        .add_event_listener_with_callback("dblclick", dblclick_closure.as_ref().unchecked_ref())
+        .unwrap();
+
+    // Asks for a lost backing store back, then refetches the context and redraws
+    let contextlost_closure =
+        wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(|e: web_sys::Event| e.prevent_default())
+            .into_js_value();
+    canvas
+        .add_event_listener_with_callback(
+            "contextlost",
+            contextlost_closure.as_ref().unchecked_ref(),
+        )
+        .unwrap();
+    let canvas_clone_restore = canvas.clone();
+    let reinit = move || {
+        context.set(context_2d(&canvas_clone_restore));
+    };
+    let contextrestored_closure =
+        wasm_bindgen::prelude::Closure::<dyn Fn()>::new(reinit.clone()).into_js_value();
+    canvas
+        .add_event_listener_with_callback(
+            "contextrestored",
+            contextrestored_closure.as_ref().unchecked_ref(),
+        )
+        .unwrap();
+    let visibilitychange_closure = wasm_bindgen::prelude::Closure::<dyn Fn()>::new(move || {
+        if !document().hidden() {
+            reinit();
+        }
+    })
+    .into_js_value();
+    document()
+        .add_event_listener_with_callback(
+            "visibilitychange",
+            visibilitychange_closure.as_ref().unchecked_ref(),
+        )
+        .unwrap();
+
+    create_effect(cx, move |_| {
+        // console_log("running the effect!");
+
+        let context = context.get();
+        let vt = view_transform.get();
+        context.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0).unwrap();
+        context.clear_rect(
+            0.0,
+            0.0,
+            vt.canvas_width * vt.dpr,
+            vt.canvas_height * vt.dpr,
+        );
+
+        vt.apply(&context);
+        context.set_line_width(4.0 / vt.pixels_per_unit());
+
+        let printing = display_mode.get() == DisplayMode::Print;
+
+        // Reference image to trace over, beneath everything else and never printed
+        if let Some(image) = background_image.get().filter(|_| !printing) {
+            document_settings.with(|s| {
+                if let Some(background) = &s.background {
+                    context.save();
+                    context.set_global_alpha(background.opacity);
+                    context
+                        .draw_image_with_html_image_element_and_dw_and_dh(
+                            &image,
+                            background.position.x,
+                            background.position.y,
+                            image.natural_width() as f64 * background.scale,
+                            image.natural_height() as f64 * background.scale,
+                        )
+                        .unwrap();
+                    context.restore();
+                }
+            });
+        }
+
+        snap_settings.with(|snap| {
+            if snap.grid && !printing {
+                context.save();
+                context.set_stroke_style(&wasm_bindgen::JsValue::from_str("#e2e8f0"));
+                context.set_line_width(1.0 / vt.pixels_per_unit());
+                snap.draw_grid(&context, vt.visible_world_rect());
+                context.restore();
+            }
+        });
+
+        // Page outline
+        let active_artboard = document_settings.with(|settings| {
+            let artboard = settings.active();
+            if printing {
+                return artboard.id;
+            }
+            context.save();
+            context.set_stroke_style(&wasm_bindgen::JsValue::from_str("#cbd5e1"));
+            context.stroke_rect(
+                artboard.origin.x,
+                artboard.origin.y,
+                artboard.width,
+                artboard.height,
+            );
+            context.restore();
+            artboard.id
+        });
+
+        steps.with(|steps| {
+            draw_steps(
+                cx,
+                &context,
+                steps
+                    .iter()
+                    .filter(|s| s.on_artboard(active_artboard) && is_resolvable(steps, s))
+                    .filter(|s| !(printing && s.construction.get())),
+            );
+        });
+
+        if printing {
+            return;
+        }
+
+        sketch_samples.with(|samples| {
+            if let Some(samples) = samples {
+                draw_polyline(&context, samples, false);
+            }
+        });
+        if let (Some(start), CanvasTool::DragVector { dx, dy }) =
+            (vector_drag.get(), canvas_tool.get())
+        {
+            let end = Point {
+                x: start.x + dx.get(),
+                y: start.y + dy.get(),
+            };
+            draw_polyline(&context, &[start, end], false);
+        }
+
+        snap_points.with(|snap_points| {
+            for sp in snap_points.iter() {
+                context.set_stroke_style(&wasm_bindgen::JsValue::from_str("red"));
+
+                let sp = ResolveToPoint::resolve(sp, cx);
+
+                context.begin_path();
+                context
+                    .arc(sp.x, sp.y, 1.3, 0.0, std::f64::consts::PI * 2.0)
+                    .unwrap();
+                context.stroke();
+            }
+        });
+
+        let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+        if let Some(target) = context_infer_target.get() {
+            let snap = snap_settings.get();
+
+            // Worked out in full before setting it once, since a ref here gets previewed
+            let mut hover =
+                ResolvableTo::T(PointSignal::literal(cx, snap.snap_to_grid(mouse_pos())));
+            let allowed = |sp: &DataRef| {
+                steps.with(|steps| datas.with(|datas| binding_allowed(steps, datas, &target, sp)))
+            };
+
+            // Grid crossings first, with a tighter radius, so nearby geometry snap points still win
+            if snap.grid && snap.geometry && snap.grid_kind == GridKind::Square {
+                let crossings =
+                    steps.with(|steps| grid_crossing_snap_points(steps, &snap, mouse_pos()));
+                for sp in crossings.iter() {
+                    let spr = ResolveToPoint::resolve(sp, cx);
+                    let dist =
+                        ((spr.x - mouse_pos().x).powi(2) + (spr.y - mouse_pos().y).powi(2)).sqrt();
+                    if dist < snap.grid_size && allowed(sp) {
+                        hover = ResolvableTo::Ref(sp.clone());
+                    }
+                }
+            }
+
+            // todo(chad): @Performance
+            // This subscribes the effect to any mouse move changes, which is a lot of unnecessary runs.
+            // We should only run this effect when the mouse movement causes a change to the currently selected snap point.
+            if snap.geometry {
+                snap_points.with(|snap_points| {
+                    for sp in snap_points.iter() {
+                        let spr = ResolveToPoint::resolve(sp, cx);
+                        let dist = ((spr.x - mouse_pos().x).powi(2)
+                            + (spr.y - mouse_pos().y).powi(2))
+                        .sqrt();
+                        if dist < 5.0 && allowed(sp) {
+                            hover = ResolvableTo::Ref(sp.clone());
+                        }
+                    }
+                });
+            }
+
+            hover_infer_target.set(Some(hover));
+        }
+
+        // Highlight the line the eyedropper would take the length of
+        if let Some(InferTarget::Eyedropper { coord, .. }) = context_infer_target.get() {
+            let on_snap_point = matches!(hover_infer_target.get(), Some(ResolvableTo::Ref(_)));
+            if coord.is_none() || !on_snap_point {
+                let line = steps.with(|steps| line_near(cx, steps, mouse_pos(), 1.0));
+                if let Some(step) = line
+                    .and_then(|l| l.step_id())
+                    .and_then(|id| step_by_id(cx, id))
+                {
+                    context.save();
+                    context.set_stroke_style(&wasm_bindgen::JsValue::from_str("green"));
+                    draw_step(cx, &context, &step);
+                    context.restore();
+                }
+            }
+        }
+
+        let (guide_x, guide_y) = guides.get();
+        if guide_x.is_some() || guide_y.is_some() {
+            let (min, max) = vt.visible_world_rect();
+            context.save();
+            context.set_stroke_style(&wasm_bindgen::JsValue::from_str("#d946ef"));
+            context.set_line_width(1.0 / vt.pixels_per_unit());
+            context.begin_path();
+            if let Some(x) = guide_x {
+                context.move_to(x, min.y);
+                context.line_to(x, max.y);
+            }
+            if let Some(y) = guide_y {
+                context.move_to(min.x, y);
+                context.line_to(max.x, y);
+            }
+            context.stroke();
+            context.restore();
+        }
+
+        // The calibration span picked so far
+        calibration.with(|c| {
+            if c.points.is_empty() {
+                return;
+            }
+            context.save();
+            context.set_stroke_style(&wasm_bindgen::JsValue::from_str("#d946ef"));
+            context.set_line_width(1.0 / vt.pixels_per_unit());
+            draw_polyline(&context, &c.points, false);
+            for p in c.points.iter() {
+                draw_marker(&context, *p, PointMarker::Cross, 0.5);
+            }
+            context.restore();
+        });
+
+        if let Some(hit) = hover_infer_target.get() {
+            let mut fill = false;
+            if let ResolvableTo::Ref(_) = hit {
+                context.set_fill_style(&wasm_bindgen::JsValue::from_str("green"));
+                fill = true;
+            }
+
+            let hit = hit.resolve(cx);
+            context.begin_path();
+            context.set_stroke_style(&wasm_bindgen::JsValue::from_str("green"));
+            context
+                .arc(hit.x, hit.y, 1.0, 0.0, std::f64::consts::PI * 2.0)
+                .unwrap();
+            context.stroke();
+            if fill {
+                context.fill();
+            }
+        }
+    });
+
+    // Preview binding a pending point to whichever snap point is hovered
+    create_effect(cx, move |_| {
+        let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+        match (context_infer_target.get(), hover_infer_target.get()) {
+            (Some(target @ InferTarget::Point(_)), Some(ResolvableTo::Ref(candidate))) => {
+                start_binding_preview(cx, target, candidate)
+            }
+            _ => end_binding_preview(cx),
+        }
+    });
+
+    view! { cx,
+        <div class="block grow self-center">
+            { canvas }
+        </div>
+    }
+}
+
+#[component]
+fn SettingsNumberInput(
+    cx: Scope,
+    label: &'static str,
+    value: Signal<f64>,
+    set: SignalSetter<f64>,
+) -> impl IntoView {
+    view! { cx,
+        <label class="flex flex-row justify-between">
+            {label}
+            <input
+                type="number"
+                class="w-20 ml-2"
+                prop:value=move || value.get().to_string()
+                on:change=move |ev| {
+                    if let Ok(v) = event_target_value(&ev).parse::<f64>() {
+                        if v > 0.0 {
+                            set.set(v);
+                        }
+                    }
+                }
+            />
+        </label>
+    }
+}
+
+/// The step whose card is briefly highlighted after jumping to it, if any
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FlashedStep(pub Option<usize>);
+
+/// Selects the step, scrolls its card into view and flashes it
+fn jump_to_step(cx: Scope, step_id: usize) {
+    let selection = use_context::<RwSignal<Selection>>(cx).unwrap();
+    selection.set(Selection {
+        steps: vec![step_id],
+    });
+    if let Some(card) = document().get_element_by_id(&format!("step-{}", step_id)) {
+        card.scroll_into_view();
+    }
+
+    let flashed = use_context::<RwSignal<FlashedStep>>(cx).unwrap();
+    flashed.set(FlashedStep(Some(step_id)));
+    set_timeout(
+        move || {
+            // Unless another jump has flashed something else since
+            if flashed.get() == FlashedStep(Some(step_id)) {
+                flashed.set(FlashedStep(None));
+            }
+        },
+        std::time::Duration::from_millis(800),
+    );
+}
+
+/// A ref's path with the step it starts from linking to that step's card
+#[component]
+fn RefBreadcrumbs(cx: Scope, r: DataRef) -> impl IntoView {
+    let Some(id) = r.step_id() else {
+        return view! { cx, <span>{r.desc()}</span> }.into_view(cx);
+    };
+    let rest = DataRef(r.0[2..].to_vec()).desc();
+
+    view! { cx,
+        <span>
+            <button class="underline" on:click=move |_| jump_to_step(cx, id)>
+                "step[" {id} "]"
+            </button>
+            {rest}
+        </span>
+    }
+    .into_view(cx)
+}
+
+/// Lists everything `validate` finds, either kept up to date live or checked on demand
+#[component]
+fn ProblemsView(cx: Scope) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+
+    let live = create_rw_signal(cx, true);
+    let problems = create_rw_signal(cx, Vec::<Problem>::new());
+    let check = move || {
+        let found = steps.with(|steps| datas.with(|datas| validate(cx, steps, datas)));
+        problems.set(found);
+    };
+    create_effect(cx, move |_| {
+        if live.get() {
+            check();
+        }
+    });
+
+    view! { cx,
+        <div class="flex flex-col mb-6 w-[12rem] max-w-[85%] self-center">
+            <div class="flex flex-row justify-between items-center">
+                <p>{move || match problems.with(|p| p.len()) {
+                    0 => "No problems".to_string(),
+                    1 => "1 problem".to_string(),
+                    n => format!("{} problems", n),
+                }}</p>
+                <label class="text-sm">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || live.get()
+                        on:change=move |_| live.update(|l| *l = !*l)
+                    />
+                    " Live"
+                </label>
+            </div>
+            {move || (!live.get()).then(|| view! { cx,
+                <button class="border-2 border-gray-800 mt-1" on:click=move |_| check()>
+                    "Check now"
+                </button>
+            })}
+            {move || problems.get().into_iter().map(|problem| view! { cx,
+                <div class="flex flex-row justify-between text-sm text-red-700 mt-1">
+                    <p>{problem.message}</p>
+                    {problem.step_id.map(|id| view! { cx,
+                        <button class="underline ml-1" on:click=move |_| jump_to_step(cx, id)>
+                            "step[" {id} "]"
+                        </button>
+                    })}
+                </div>
+            }).collect::<Vec<_>>()}
+        </div>
+    }
+}
+
+/// Every tag in use, with the style preset it applies and bulk actions on its steps
+#[component]
+fn TagsView(cx: Scope) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let settings = use_context::<RwSignal<DocumentSettings>>(cx).unwrap();
+    let selection = use_context::<RwSignal<Selection>>(cx).unwrap();
+
+    let open = create_rw_signal(cx, false);
+    // Each tag with the ids of the (untrashed) steps carrying it, by name
+    let tagged = move || {
+        steps.with(|steps| {
+            let mut tagged: Vec<(String, Vec<usize>)> = Vec::new();
+            for step in steps.iter().filter(|s| !s.trashed.get()) {
+                step.tags.with(|tags| {
+                    for tag in tags {
+                        match tagged.iter_mut().find(|(t, _)| t == tag) {
+                            Some((_, ids)) => ids.push(step.id),
+                            None => tagged.push((tag.clone(), vec![step.id])),
+                        }
+                    }
+                });
+            }
+            tagged.sort_by(|a, b| a.0.cmp(&b.0));
+            tagged
+        })
+    };
+    let remove_tag = move |tag: String| {
+        steps.with(|steps| {
+            for step in steps {
+                step.tags.update(|tags| tags.retain(|t| *t != tag));
+            }
+        });
+        settings.update(|s| s.set_tag_preset(&tag, None));
+    };
+
+    view! { cx,
+        <div class="flex flex-col mb-6 w-[12rem] max-w-[85%] self-center">
+            <button
+                class="bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded"
+                on:click=move |_| open.update(|o| *o = !*o)
+            >
+                {move || format!("Tags ({})", tagged().len())}
+            </button>
+            {move || open.get().then(|| view! { cx,
+                <div class="flex flex-col mt-2">
+                    {move || tagged().into_iter().map(|(tag, ids)| {
+                        let tag = store_value(cx, tag);
+                        let ids = store_value(cx, ids);
+                        view! { cx,
+                            <div class="flex flex-col text-sm mt-1">
+                                <p>{tag.get()} " (" {ids.get().len()} ")"</p>
+                                <select on:change=move |ev| {
+                                    let preset = event_target_value(&ev).parse::<usize>().ok();
+                                    settings.update(|s| s.set_tag_preset(&tag.get(), preset));
+                                }>
+                                    <option value="none" selected=move || settings.with(|s| s.tag_preset(&tag.get()).is_none())>
+                                        "(no preset)"
+                                    </option>
+                                    {move || settings.with(|s| {
+                                        s.style_presets
+                                            .iter()
+                                            .map(|p| {
+                                                let id = p.id;
+                                                view! { cx,
+                                                    <option
+                                                        value=id.to_string()
+                                                        selected=move || settings.with(|s| s.tag_preset(&tag.get()) == Some(id))
+                                                    >
+                                                        {p.name.clone()}
+                                                    </option>
+                                                }
+                                            })
+                                            .collect::<Vec<_>>()
+                                    })}
+                                </select>
+                                <div class="flex flex-row justify-between">
+                                    <button
+                                        class="border-2 border-gray-800 px-1"
+                                        on:click=move |_| selection.update(|sel| sel.steps = ids.get())
+                                    >
+                                        "Select"
+                                    </button>
+                                    <button
+                                        class="border-2 border-gray-800 px-1"
+                                        on:click=move |_| remove_tag(tag.get())
+                                    >
+                                        "Remove"
+                                    </button>
+                                </div>
+                            </div>
+                        }
+                    }).collect::<Vec<_>>()}
+                </div>
+            })}
+        </div>
+    }
+}
+
+/// One row of the watch panel: the watched value and where it comes from
+#[component]
+fn WatchView(cx: Scope, watch: Watch) -> impl IntoView {
+    let watches = use_context::<RwSignal<Vec<Watch>>>(cx).unwrap();
+    let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+
+    let value = move || watches.with(|w| watch_value(cx, w, watch.id, &mut Vec::new()));
+    let picking = move || match (context_infer_target.get(), watch.kind) {
+        (Some(InferTarget::Number(t)), WatchKind::Number(n))
+        | (Some(InferTarget::Eyedropper { target: t, .. }), WatchKind::Number(n)) => t == n,
+        (Some(InferTarget::Point(t)), WatchKind::Point(p)) => t == p,
+        _ => false,
+    };
+    let watched_ref = move || watch.infer_targets().first().and_then(|t| t.data_ref());
+    let remove = move |_| {
+        if picking() {
+            context_infer_target.set(None);
+        }
+        watches.update(|w| w.retain(|w| w.id != watch.id));
+    };
+
+    let source = move || {
+        match watch.kind {
+            WatchKind::Expr(src) => view! { cx,
+                <input
+                    class="w-full border border-gray-300"
+                    placeholder="w0 * 2"
+                    prop:value=move || src.get()
+                    on:input=move |ev| src.set(event_target_value(&ev))
+                />
+            }
+            .into_view(cx),
+            _ if picking() => view! { cx,
+                <div class="flex flex-row">
+                    <p>"pick..."</p>
+                    <button class="border-2 border-gray-800 ml-2" on:click=move |_| context_infer_target.set(None)>
+                        "Cancel"
+                    </button>
+                </div>
+            }
+            .into_view(cx),
+            kind => view! { cx,
+                <div class="flex flex-row justify-between">
+                    {move || match watched_ref() {
+                        Some(r) => view! { cx, <RefBreadcrumbs r /> }.into_view(cx),
+                        None => view! { cx, <span class="text-gray-400">"nothing picked"</span> }.into_view(cx),
+                    }}
+                    <div class="flex flex-row">
+                        <button class="border-2 border-gray-800" on:click=move |_| {
+                            context_infer_target.set(watch.infer_targets().first().copied());
+                        }>"Pick"</button>
+                        {match kind {
+                            WatchKind::Number(n) => Some(view! { cx,
+                                <button class="border-2 border-gray-800" on:click=move |_| {
+                                    context_infer_target.set(Some(InferTarget::Eyedropper { target: n, coord: None }));
+                                }>"E"</button>
+                            }),
+                            _ => None,
+                        }}
+                    </div>
+                </div>
+            }
+            .into_view(cx),
+        }
+    };
+
+    view! { cx,
+        <div class="p-2 m-1 shadow bg-white w-[90%] rounded-lg relative group">
+            <button
+                class="absolute left-[90%] opacity-0 group-hover:opacity-100 transition-all"
+                on:click=remove>
+                "x"
+            </button>
+            <div class="flex flex-col">
+                <p class="text-sm text-gray-600">{format!("w{}", watch.id)}</p>
+                {source}
+                {move || match value() {
+                    Ok(v) => view! { cx, <p class="font-mono">"= " {v.desc()}</p> }.into_view(cx),
+                    Err(e) => view! { cx, <p class="text-sm text-red-600">{e}</p> }.into_view(cx),
+                }}
+            </div>
+        </div>
+    }
+}
+
+/// Values kept an eye on while editing, each updating live with the drawing
+#[component]
+fn WatchesView(cx: Scope) -> impl IntoView {
+    let watches = use_context::<RwSignal<Vec<Watch>>>(cx).unwrap();
+
+    view! { cx,
+        <div class="flex flex-col">
+            <h3 class="text-3xl text-center m-3">"Watch"</h3>
+            <div class="flex flex-row justify-center mb-6">
+                <button class="bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded mx-1" on:click=move |_| add_watch(cx, "number")>"+ Number"</button>
+                <button class="bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded mx-1" on:click=move |_| add_watch(cx, "point")>"+ Point"</button>
+                <button class="bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded mx-1" on:click=move |_| add_watch(cx, "expr")>"+ Expression"</button>
+            </div>
+            <div class="flex flex-col items-center">
+                <For
+                    each=move || watches.get()
+                    key=|watch| watch.id
+                    view=move |watch: Watch| view! { cx, <WatchView watch /> }
+                />
+            </div>
+        </div>
+    }
+}
+
+/// One cell of the parameter table, with angles in the document's unit
+#[component]
+fn ParameterCell(cx: Scope, row: usize, col: usize, data_id: usize) -> impl IntoView {
+    let table = use_context::<RwSignal<ParameterTable>>(cx).unwrap();
+    let is_angle = matches!(
+        find_data(cx, data_id).map(|d| d.data),
+        Some(DataData::Angle(_))
+    );
+    let value = move || {
+        table.with(|t| {
+            t.configurations
+                .get(col)
+                .and_then(|c| c.values.get(row))
+                .copied()
+        })
+    };
+    let shown = move || {
+        value()
+            .map(|v| {
+                if is_angle {
+                    angle_unit(cx).from_radians(v)
+                } else {
+                    v
+                }
+            })
+            .map(shown_value)
+            .unwrap_or_default()
+    };
+
+    view! { cx,
+        <input
+            type="number"
+            step="any"
+            class="w-20"
+            prop:value=shown
+            on:change=move |ev| {
+                let Ok(v) = event_target_value(&ev).parse::<f64>() else {
+                    return;
+                };
+                let v = if is_angle { angle_unit(cx).to_radians(v) } else { v };
+                table.update(|t| {
+                    if let Some(cell) = t.configurations.get_mut(col).and_then(|c| c.values.get_mut(row)) {
+                        *cell = v;
+                    }
+                });
+                // The active configuration is what the data holds
+                if table.with(|t| t.active) == Some(col) {
+                    set_parameter_value(cx, data_id, v);
+                }
+            }
+        />
+    }
+}
+
+#[component]
+fn ParameterTableView(cx: Scope) -> impl IntoView {
+    let table = use_context::<RwSignal<ParameterTable>>(cx).unwrap();
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+
+    // Data which could still be added as a row
+    let candidates = move || {
+        let params = table.with(|t| t.params.clone());
+        datas.with(|datas| {
+            datas
+                .iter()
+                .filter(|d| {
+                    !d.trashed.get()
+                        && !params.contains(&d.id)
+                        && matches!(
+                            d.data,
+                            DataData::Number(_)
+                                | DataData::Slider { .. }
+                                | DataData::Angle(_)
+                                | DataData::Bool(_)
+                        )
+                })
+                .map(|d| d.id)
+                .collect::<Vec<_>>()
+        })
+    };
+    let columns = move || table.with(|t| t.configurations.len());
+    let (template, set_template) = create_slice(
+        cx,
+        table,
+        |t| t.filename_template.clone(),
+        |t, v| t.filename_template = v,
+    );
+    let (export_error, set_export_error) = create_slice(
+        cx,
+        use_context::<RwSignal<ExportErrors>>(cx).unwrap(),
+        |e| e.configurations.clone(),
+        |e, v| e.configurations = v,
+    );
+
+    view! { cx,
+        <div class="flex flex-col">
+            <h3 class="text-3xl text-center m-3">"Configurations"</h3>
+            <div class="flex flex-row justify-center mb-6">
+                <select class="mx-1" on:change=move |ev| {
+                    if let Ok(id) = event_target_value(&ev).parse::<usize>() {
+                        add_parameter(cx, id);
+                    }
+                }>
+                    <option value="none" selected=true>"+ Parameter"</option>
+                    {move || candidates().into_iter().map(|id| view! { cx,
+                        <option value=id.to_string()>{format!("Data #{}", id)}</option>
+                    }).collect::<Vec<_>>()}
+                </select>
+                <button class="bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded mx-1" on:click=move |_| add_configuration(cx)>"+ Configuration"</button>
+            </div>
+            <div class="overflow-scroll self-center max-w-[95%]">
+                <table class="text-sm">
+                    <tr>
+                        <th></th>
+                        {move || (0..columns()).map(|col| {
+                            let (name, set_name) = create_slice(
+                                cx,
+                                table,
+                                move |t| t.configurations.get(col).map(|c| c.name.clone()).unwrap_or_default(),
+                                move |t, v| {
+                                    if let Some(c) = t.configurations.get_mut(col) {
+                                        c.name = v;
+                                    }
+                                },
+                            );
+                            let active = move || table.with(|t| t.active) == Some(col);
+                            view! { cx,
+                                <th class=move || if active() { "bg-blue-100" } else { "" }>
+                                    <input class="w-20" prop:value=name on:change=move |ev| set_name.set(event_target_value(&ev)) />
+                                    <div class="flex flex-row justify-center">
+                                        <button class="border-2 border-gray-800 px-1" prop:disabled=active on:click=move |_| activate_configuration(cx, col)>
+                                            "Use"
+                                        </button>
+                                        <button class="border-2 border-gray-800 px-1 ml-1" on:click=move |_| table.update(|t| {
+                                            t.configurations.remove(col);
+                                            t.active = match t.active {
+                                                Some(a) if a == col => None,
+                                                Some(a) if a > col => Some(a - 1),
+                                                a => a,
+                                            };
+                                        })>
+                                            "x"
+                                        </button>
+                                    </div>
+                                </th>
+                            }
+                        }).collect::<Vec<_>>()}
+                    </tr>
+                    {move || table.with(|t| t.params.clone()).into_iter().enumerate().map(|(row, data_id)| view! { cx,
+                        <tr>
+                            <td>
+                                <div class="flex flex-row">
+                                    <p>{format!("Data #{}", data_id)}</p>
+                                    <button class="border-2 border-gray-800 px-1 ml-1" on:click=move |_| table.update(|t| {
+                                        t.params.remove(row);
+                                        for c in t.configurations.iter_mut() {
+                                            c.values.remove(row);
+                                        }
+                                    })>
+                                        "x"
+                                    </button>
+                                </div>
+                            </td>
+                            {(0..columns()).map(|col| view! { cx,
+                                <td><ParameterCell row col data_id /></td>
+                            }).collect::<Vec<_>>()}
+                        </tr>
+                    }).collect::<Vec<_>>()}
+                </table>
+            </div>
+            <div class="flex flex-col mt-2 w-[12rem] max-w-[85%] self-center">
+                <label class="text-sm flex flex-row justify-between">
+                    "File names: "
+                    <input class="w-28" prop:value=template on:change=move |ev| set_template.set(event_target_value(&ev)) />
+                </label>
+                <button
+                    class="bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded mt-1 disabled:opacity-50"
+                    prop:disabled=move || columns() == 0
+                    on:click=move |_| set_export_error.set(export_configurations_png(cx).err())
+                >
+                    "Export All (PNG)"
+                </button>
+                <p class="text-sm">"{name} and {n} are filled in per configuration"</p>
+                {move || export_error.get().map(|e| view! { cx, <p class="text-sm text-red-600">{e}</p> })}
+            </div>
+        </div>
+    }
+}
+
+/// Deleted steps and data, which can be restored until the trash is emptied
+#[component]
+fn TrashView(cx: Scope) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+
+    let open = create_rw_signal(cx, false);
+    let trashed_steps = move || {
+        steps.with(|s| {
+            s.iter()
+                .filter(|s| s.trashed.get())
+                .copied()
+                .collect::<Vec<_>>()
+        })
+    };
+    let trashed_datas = move || {
+        datas.with(|d| {
+            d.iter()
+                .filter(|d| d.trashed.get())
+                .copied()
+                .collect::<Vec<_>>()
+        })
+    };
+    let count = move || trashed_steps().len() + trashed_datas().len();
+
+    view! { cx,
+        <div class="flex flex-col mb-6 w-[12rem] max-w-[85%] self-center">
+            <button
+                class="bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded"
+                on:click=move |_| open.update(|o| *o = !*o)
+            >
+                {move || format!("Trash ({})", count())}
+            </button>
+            {move || open.get().then(|| view! { cx,
+                <div class="flex flex-col mt-2">
+                    {move || trashed_steps().into_iter().map(|step| view! { cx,
+                        <div class="flex flex-row justify-between">
+                            <p>"Step #" {step.id}</p>
+                            <button class="border-2 border-gray-800 px-1" on:click=move |_| step.trashed.set(false)>
+                                "Restore"
+                            </button>
+                        </div>
+                    }).collect::<Vec<_>>()}
+                    {move || trashed_datas().into_iter().map(|data| view! { cx,
+                        <div class="flex flex-row justify-between">
+                            <p>"Data #" {data.id}</p>
+                            <button class="border-2 border-gray-800 px-1" on:click=move |_| data.trashed.set(false)>
+                                "Restore"
+                            </button>
+                        </div>
+                    }).collect::<Vec<_>>()}
+                    <button
+                        class="border-2 border-gray-800 mt-2 disabled:opacity-50"
+                        prop:disabled=move || count() == 0
+                        on:click=move |_| empty_trash(cx)
+                    >
+                        "Empty trash"
+                    </button>
+                </div>
+            })}
+        </div>
+    }
+}
+
+/// Retargets every reference to one point at another, across the whole document
+#[component]
+fn ReplaceRefsView(cx: Scope) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+    let watches = use_context::<RwSignal<Vec<Watch>>>(cx).unwrap();
+    let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+
+    let open = create_rw_signal(cx, false);
+    let from = create_rw_signal(cx, None::<DataRef>);
+    // Picked off the canvas; only a snap point (a ref) is a usable replacement
+    let to = create_rw_signal(
+        cx,
+        ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+    );
+    let to_ref = move || match to.get() {
+        ResolvableTo::Ref(r) => Some(r),
+        ResolvableTo::T(_) | ResolvableTo::Expr(_) => None,
+    };
+    let status = create_rw_signal(cx, None::<String>);
+
+    let sources = move || point_refs_in_use(cx);
+    // What each would-be replaced slot belongs to, like "step #3"
+    let affected = move || {
+        let Some(from) = from.get() else {
+            return Vec::new();
+        };
+        let refers = |targets: Vec<InferTarget>| {
+            targets
+                .iter()
+                .filter_map(|t| t.data_ref())
+                .any(|r| referenced_point(&r) == from)
+        };
+        let mut affected: Vec<String> = steps.with(|steps| {
+            steps
+                .iter()
+                .filter(|s| refers(s.infer_targets()))
+                .map(|s| format!("step #{}", s.id))
+                .collect()
+        });
+        affected.extend(datas.with(|datas| {
+            datas
+                .iter()
+                .filter(|d| refers(d.infer_targets()))
+                .map(|d| format!("data #{}", d.id))
+                .collect::<Vec<_>>()
+        }));
+        affected.extend(watches.with(|watches| {
+            watches
+                .iter()
+                .filter(|w| refers(w.infer_targets()))
+                .map(|w| format!("watch #{}", w.id))
+                .collect::<Vec<_>>()
+        }));
+        affected
+    };
+
+    let replace = move |_| {
+        if let (Some(from_ref), Some(to_ref)) = (from.get(), to_ref()) {
+            let changed = replace_point_refs(cx, &from_ref, &to_ref);
+            status.set(Some(format!("Replaced {} references", changed)));
+            from.set(None);
+        }
+    };
+
+    view! { cx,
+        <div class="flex flex-col mb-6 w-[12rem] max-w-[85%] self-center">
+            <button
+                class="bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded"
+                on:click=move |_| open.update(|o| *o = !*o)
+            >
+                "Replace References…"
+            </button>
+            {move || open.get().then(|| view! { cx,
+                <div class="flex flex-col mt-2">
+                    <p>"Replace"</p>
+                    <select on:change=move |ev| {
+                        let desc = event_target_value(&ev);
+                        from.set(sources().into_iter().find(|r| r.desc() == desc));
+                        status.set(None);
+                    }>
+                        <option value="" selected=move || from.get().is_none()>"(pick a ref)"</option>
+                        {move || sources().into_iter().map(|r| {
+                            let desc = r.desc();
+                            let selected = from.get().as_ref() == Some(&r);
+                            view! { cx, <option value=desc.clone() selected=selected>{desc.clone()}</option> }
+                        }).collect::<Vec<_>>()}
+                    </select>
+
+                    <p class="mt-2">"with"</p>
+                    <p>{move || to_ref().map_or("(nothing yet)".to_string(), |r| r.desc())}</p>
+                    <button class="border-2 border-gray-800 mt-1" on:click=move |_| {
+                        context_infer_target.set(Some(InferTarget::Point(to)));
+                    }>
+                        "Pick snap point"
+                    </button>
+
+                    {move || {
+                        let affected = affected();
+                        (!affected.is_empty()).then(|| {
+                            let list = affected.join(", ");
+                            view! { cx, <p class="mt-2 text-sm">"Affects " {list}</p> }
+                        })
+                    }}
+                    <button
+                        class="border-2 border-gray-800 mt-2 disabled:opacity-50"
+                        prop:disabled=move || from.get().is_none() || to_ref().is_none()
+                        on:click=replace
+                    >
+                        "Replace"
+                    </button>
+                    {move || status.get().map(|s| view! { cx, <p class="text-sm">{s}</p> })}
+                </div>
+            })}
+        </div>
+    }
+}
+
+/// Numeric translate / rotate / scale of the selected steps
+#[component]
+fn TransformView(cx: Scope) -> impl IntoView {
+    let selection = use_context::<RwSignal<Selection>>(cx).unwrap();
+    let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
+
+    let open = create_rw_signal(cx, false);
+    let kind = create_rw_signal(cx, "translate".to_string());
+    let dx = create_rw_signal(cx, 0.0);
+    let dy = create_rw_signal(cx, 0.0);
+    let angle = create_rw_signal(cx, 0.0);
+    let factor = create_rw_signal(cx, 1.0);
+    let about_x = create_rw_signal(cx, 0.0);
+    let about_y = create_rw_signal(cx, 0.0);
+
+    // Picking the pivot off the canvas copies the picked point into about_x/about_y
+    let pick = create_rw_signal(
+        cx,
+        ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+    );
+    create_effect(cx, move |_| {
+        let picked = pick.get();
+        let p = cx.untrack(move || picked.resolve(cx));
+        about_x.set(p.x);
+        about_y.set(p.y);
+    });
+
+    let transform = move || {
+        let about = Point {
+            x: about_x.get(),
+            y: about_y.get(),
+        };
+        match kind.get().as_str() {
+            // Entered in the document's unit
+            "rotate" => Transform::Rotate {
+                about,
+                angle: AngleUnit::Degrees.convert(angle.get(), angle_unit(cx)),
+            },
+            "scale" => Transform::Scale {
+                about,
+                factor: factor.get(),
+            },
+            _ => Transform::Translate {
+                dx: dx.get(),
+                dy: dy.get(),
+            },
+        }
+    };
+    let external_refs = move || {
+        let selected = selection.with(|s| s.steps.clone());
+        external_ref_slots(cx, &selected).len()
+    };
+    let apply = move |bake: bool| {
+        let selected = selection.with(|s| s.steps.clone());
+        transform_steps(cx, &selected, &transform(), bake);
+    };
+
+    let number_field = move |label: &'static str, value: RwSignal<f64>| {
+        view! { cx,
+            <label class="flex flex-row justify-between">
+                {label}
+                <input
+                    type="number"
+                    class="w-20 ml-2"
+                    prop:value=move || value.get().to_string()
+                    on:change=move |ev| {
+                        if let Ok(v) = event_target_value(&ev).parse::<f64>() {
+                            value.set(v);
+                        }
+                    }
+                />
+            </label>
+        }
+    };
+
+    view! { cx,
+        <div class="flex flex-col mb-6 w-[12rem] max-w-[85%] self-center">
+            <button
+                class="bg-blue-500 hover:bg-blue-700 disabled:bg-blue-300 py-2 px-1 text-white rounded"
+                prop:disabled=move || selection.with(|sel| sel.steps.is_empty())
+                on:click=move |_| open.update(|o| *o = !*o)
+            >
+                "Transform…"
+            </button>
+            {move || (open.get() && selection.with(|sel| !sel.steps.is_empty())).then(|| view! { cx,
+                <div class="flex flex-col mt-2">
+                    <select on:change=move |ev| kind.set(event_target_value(&ev))>
+                        {["translate", "rotate", "scale"].into_iter().map(|k| view! { cx,
+                            <option value=k selected=move || kind.get() == k>{k}</option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                    {move || match kind.get().as_str() {
+                        "translate" => view! { cx,
+                            <div class="flex flex-col">
+                                {number_field("dx", dx)}
+                                {number_field("dy", dy)}
+                            </div>
+                        },
+                        k => view! { cx,
+                            <div class="flex flex-col">
+                                {if k == "rotate" {
+                                    number_field(match angle_unit(cx) {
+                                        AngleUnit::Degrees => "angle (deg)",
+                                        AngleUnit::Radians => "angle (rad)",
+                                    }, angle)
+                                } else {
+                                    number_field("factor", factor)
+                                }}
+                                {number_field("about x", about_x)}
+                                {number_field("about y", about_y)}
+                                <button class="border-2 border-gray-800 mt-1" on:click=move |_| {
+                                    context_infer_target.set(Some(InferTarget::Point(pick)));
+                                }>
+                                    "Pick pivot"
+                                </button>
+                            </div>
+                        },
+                    }}
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| apply(false)>
+                        "Apply"
+                    </button>
+                    {move || {
+                        let count = external_refs();
+                        (count > 0).then(|| view! { cx,
+                            <div class="flex flex-col mt-2">
+                                <p>{count} " ref-driven values won't move"</p>
+                                <button class="border-2 border-gray-800 mt-1" on:click=move |_| apply(true)>
+                                    "Bake and apply"
+                                </button>
+                            </div>
+                        })
+                    }}
+                </div>
+            })}
+        </div>
+    }
+}
+
+/// Creates a run of point steps from `x(i)` and `y(i)` expressions
+#[component]
+fn PointSeriesView(cx: Scope) -> impl IntoView {
+    let x_expr = create_rw_signal(cx, "i".to_string());
+    let y_expr = create_rw_signal(cx, "0".to_string());
+    let count = create_rw_signal(cx, 10usize);
+    let error = create_rw_signal(cx, None::<String>);
+
+    let create_points = move |_| match point_series(&x_expr.get(), &y_expr.get(), count.get()) {
+        Ok(points) => {
+            error.set(None);
+            for point in points {
+                push_step(
+                    cx,
+                    StepData::DrawPoint {
+                        point: create_rw_signal(
+                            cx,
+                            ResolvableTo::T(PointSignal::literal(cx, point)),
+                        ),
+                        marker: create_rw_signal(cx, PointMarker::default()),
+                        size: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                    },
+                );
+            }
+        }
+        Err(e) => error.set(Some(e)),
+    };
+
+    // Previewed at the first and last point
+    let expr_field = move |label: &'static str, value: RwSignal<String>| {
+        let ends: Rc<dyn Fn() -> Vec<f64>> = Rc::new(move || match count.get() {
+            0 => Vec::new(),
+            n => vec![0.0, (n - 1) as f64],
+        });
+        view! { cx, <ExprInput label value var="i" at=ends /> }
+    };
+
+    view! { cx,
+        <div class="flex flex-col mb-6 w-[12rem] max-w-[85%] self-center">
+            {expr_field("x(i)", x_expr)}
+            {expr_field("y(i)", y_expr)}
+            <label class="flex flex-row justify-between">
+                "N"
+                <input
+                    type="number"
+                    class="w-28 ml-2"
+                    prop:value=move || count.get().to_string()
+                    on:change=move |ev| {
+                        if let Ok(n) = event_target_value(&ev).parse::<usize>() {
+                            count.set(n);
+                        }
+                    }
+                />
+            </label>
+            <button class="mt-2 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded" on:click=create_points>"Point Series"</button>
+            {move || error.get().map(|e| view! { cx, <p class="text-red-700">{e}</p> })}
+        </div>
+    }
+}
+
+#[component]
+fn BackgroundImageView(cx: Scope) -> impl IntoView {
+    let settings = use_context::<RwSignal<DocumentSettings>>(cx).unwrap();
+    let canvas_tool = use_context::<RwSignal<CanvasTool>>(cx).unwrap();
+    let calibration = use_context::<RwSignal<Calibration>>(cx).unwrap();
+
+    let on_file = move |ev: web_sys::Event| {
+        let input = ev
+            .target()
+            .unwrap()
+            .unchecked_into::<web_sys::HtmlInputElement>();
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+
+        let reader = web_sys::FileReader::new().unwrap();
+        let reader_clone = reader.clone();
+        let onload = wasm_bindgen::prelude::Closure::<dyn Fn()>::new(move || {
+            if let Some(src) = reader_clone.result().ok().and_then(|r| r.as_string()) {
+                settings.update(|s| s.background = Some(BackgroundImage::new(src)));
+            }
+        })
+        .into_js_value();
+        reader.set_onload(Some(onload.as_ref().unchecked_ref()));
+        reader.read_as_data_url(&file).unwrap();
+    };
+
+    let number_field = move |label: &'static str,
+                             get: fn(&BackgroundImage) -> f64,
+                             set: fn(&mut BackgroundImage, f64)| {
+        view! { cx,
+            <label class="flex flex-row justify-between">
+                {label}
+                <input
+                    type="number"
+                    class="w-20 ml-2"
+                    prop:disabled=move || settings.with(|s| s.background.as_ref().map_or(true, |b| b.locked))
+                    prop:value=move || settings.with(|s| s.background.as_ref().map(get).unwrap_or_default().to_string())
+                    on:change=move |ev| {
+                        if let Ok(v) = event_target_value(&ev).parse::<f64>() {
+                            settings.update(|s| {
+                                if let Some(background) = &mut s.background {
+                                    set(background, v);
+                                }
+                            });
+                        }
+                    }
+                />
+            </label>
+        }
+    };
+
+    view! { cx,
+        <div class="flex flex-col mt-2">
+            <p>"Background"</p>
+            <input type="file" accept="image/*" class="text-sm" on:change=on_file />
+            {move || settings.with(|s| s.background.is_some()).then(|| view! { cx,
+                <div class="flex flex-col">
+                    {number_field("x", |b| b.position.x, |b, v| b.position.x = v)}
+                    {number_field("y", |b| b.position.y, |b, v| b.position.y = v)}
+                    {number_field("scale", |b| b.scale, |b, v| b.scale = v.max(0.0))}
+                    <label class="flex flex-row justify-between">
+                        "opacity"
+                        <input
+                            type="range"
+                            min="0"
+                            max="1"
+                            step="0.05"
+                            class="w-20 ml-2"
+                            prop:value=move || settings.with(|s| s.background.as_ref().map(|b| b.opacity).unwrap_or_default().to_string())
+                            on:input=move |ev| {
+                                if let Ok(v) = event_target_value(&ev).parse::<f64>() {
+                                    settings.update(|s| {
+                                        if let Some(background) = &mut s.background {
+                                            background.opacity = v;
+                                        }
+                                    });
+                                }
+                            }
+                        />
+                    </label>
+                    <label>
+                        <input
+                            type="checkbox"
+                            prop:checked=move || settings.with(|s| s.background.as_ref().map_or(false, |b| b.locked))
+                            on:change=move |_| settings.update(|s| {
+                                if let Some(background) = &mut s.background {
+                                    background.locked = !background.locked;
+                                }
+                            })
+                        />
+                        " Locked"
+                    </label>
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| {
+                        calibration.set(Calibration::default());
+                        canvas_tool.set(CanvasTool::Calibrate);
+                    }>
+                        {move || if canvas_tool.get() == CanvasTool::Calibrate {
+                            "Click two points on the image…"
+                        } else {
+                            "Calibrate Scale"
+                        }}
+                    </button>
+                    {move || calibration.with(|c| c.points.len() == 2).then(|| view! { cx,
+                        <div class="flex flex-col">
+                            <p class="text-sm">
+                                {move || calibration.with(|c| format!("Measured {:.3}", c.points[0].distance(c.points[1])))}
+                            </p>
+                            <label class="flex flex-row justify-between">
+                                "real distance"
+                                <input
+                                    type="number"
+                                    class="w-20 ml-2"
+                                    on:change=move |ev| {
+                                        if let Ok(v) = event_target_value(&ev).parse::<f64>() {
+                                            settings.update(|s| {
+                                                if let Some(background) = &mut s.background {
+                                                    calibration.with(|c| c.apply(background, v));
+                                                }
+                                            });
+                                            calibration.set(Calibration::default());
+                                        }
+                                    }
+                                />
+                            </label>
+                        </div>
+                    })}
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| canvas_tool.update(|t| {
+                        *t = if *t == CanvasTool::Digitize { CanvasTool::Pointer } else { CanvasTool::Digitize };
+                    })>
+                        {move || if canvas_tool.get() == CanvasTool::Digitize { "Stop Digitizing" } else { "Digitize" }}
+                    </button>
+                    <button class="border-2 border-gray-800 mt-2" on:click=move |_| settings.update(|s| s.background = None)>
+                        "Remove Background"
+                    </button>
+                </div>
+            })}
+        </div>
+    }
+}
+
+/// Where the add-step buttons put new steps, and how big they make them
+#[component]
+fn NewStepDefaultsView(cx: Scope) -> impl IntoView {
+    let defaults = use_context::<RwSignal<NewStepDefaults>>(cx).unwrap();
+
+    let placements = [
+        (NewStepPlacement::Origin, "origin"),
+        (NewStepPlacement::ViewCenter, "center of view"),
+        (NewStepPlacement::LastPointer, "last pointer position"),
+    ];
+
+    view! { cx,
+        <div class="flex flex-col mb-6 w-[12rem] max-w-[85%] self-center">
+            <label class="flex flex-row justify-between">
+                "New steps at"
+                <select class="ml-2" on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    if let Some((placement, _)) = placements.iter().find(|(_, name)| *name == value) {
+                        defaults.update(|d| d.placement = *placement);
+                    }
+                }>
+                    {placements.iter().map(|(placement, name)| {
+                        let placement = *placement;
+                        view! { cx,
+                            <option value=*name selected=move || defaults.with(|d| d.placement == placement)>
+                                {*name}
+                            </option>
+                        }
+                    }).collect::<Vec<_>>()}
+                </select>
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || defaults.with(|d| d.place_by_clicking)
+                    on:change=move |_| defaults.update(|d| d.place_by_clicking = !d.place_by_clicking)
+                />
+                " Place new steps by clicking"
+            </label>
+            <label class="flex flex-row justify-between">
+                "New step size"
+                <input
+                    type="number"
+                    class="w-20 ml-2"
+                    prop:value=move || defaults.with(|d| d.size.to_string())
+                    on:change=move |ev| {
+                        if let Ok(v) = event_target_value(&ev).parse::<f64>() {
+                            if v > 0.0 {
+                                defaults.update(|d| d.size = v);
+                            }
+                        }
+                    }
+                />
+            </label>
+        </div>
+    }
+}
+
+#[component]
+pub fn DocumentSettingsView(cx: Scope) -> impl IntoView {
+    let settings = use_context::<RwSignal<DocumentSettings>>(cx).unwrap();
+
+    // Slices need `Eq`, which f64 isn't, so these read and write through the settings
+    let width = Signal::derive(cx, move || settings.with(|s| s.active().width));
+    let set_width = SignalSetter::map(cx, move |v| settings.update(|s| s.active_mut().width = v));
+    let height = Signal::derive(cx, move || settings.with(|s| s.active().height));
+    let set_height = SignalSetter::map(cx, move |v| settings.update(|s| s.active_mut().height = v));
+    let scale = Signal::derive(cx, move || settings.with(|s| s.scale_factor));
+    let set_scale = SignalSetter::map(cx, move |v| settings.update(|s| s.scale_factor = v));
+
+    view! { cx,
+        <div class="flex flex-col self-center w-[12rem] max-w-[85%] mb-6">
+            <div class="flex flex-row justify-between mb-2">
+                <button class="border-2 border-gray-800 px-1" on:click=move |_| settings.update(|s| s.cycle_active(-1))>"<"</button>
+                <select on:change=move |ev| {
+                    if let Ok(id) = event_target_value(&ev).parse::<usize>() {
+                        settings.update(|s| s.active_artboard = id);
+                    }
+                }>
+                    {move || settings.with(|s| {
+                        s.artboards
+                            .iter()
+                            .map(|a| {
+                                let selected = a.id == s.active_artboard;
+                                view! { cx,
+                                    <option value=a.id.to_string() selected=selected>{a.name.clone()}</option>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })}
+                </select>
+                <button class="border-2 border-gray-800 px-1" on:click=move |_| settings.update(|s| s.cycle_active(1))>">"</button>
+            </div>
+            <label class="flex flex-row justify-between">
+                "Name"
+                <input
+                    class="w-20 ml-2"
+                    prop:value=move || settings.with(|s| s.active().name.clone())
+                    on:change=move |ev| settings.update(|s| s.active_mut().name = event_target_value(&ev))
+                />
+            </label>
+            <SettingsNumberInput label="Width" value=width set=set_width />
+            <SettingsNumberInput label="Height" value=height set=set_height />
+            <SettingsNumberInput label="Scale" value=scale set=set_scale />
+            <label class="flex flex-row justify-between">
+                "Angles"
+                <select class="ml-2" on:change=move |ev| {
+                    let unit = match event_target_value(&ev).as_str() {
+                        "radians" => AngleUnit::Radians,
+                        _ => AngleUnit::Degrees,
+                    };
+                    settings.update(|s| s.angle_unit = unit);
+                }>
+                    {[AngleUnit::Degrees, AngleUnit::Radians].into_iter().map(|unit| view! { cx,
+                        <option
+                            value=unit.name()
+                            selected=move || settings.with(|s| s.angle_unit) == unit
+                        >
+                            {unit.name()}
+                        </option>
+                    }).collect::<Vec<_>>()}
+                </select>
+            </label>
+            <p class="text-sm text-gray-600">
+                "Aspect " {move || format!("{:.3}", settings.with(|s| s.active().aspect_ratio()))}
+            </p>
+            <button class="border-2 border-gray-800 mt-2" on:click=move |_| settings.update(|s| s.add_artboard())>
+                "+ Artboard"
+            </button>
+            <BackgroundImageView />
+            <StylePresetsView />
+        </div>
+    }
+}
+
+#[component]
+fn StylePresetsView(cx: Scope) -> impl IntoView {
+    let settings = use_context::<RwSignal<DocumentSettings>>(cx).unwrap();
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+
+    let preset_row = move |id: usize| {
+        let preset_colors = move || {
+            settings.with(|s| {
+                s.style_presets
+                    .iter()
+                    .find(|p| p.id == id)
+                    .map(|p| (p.stroke, p.fill))
+            })
+        };
+        let stroke = preset_colors().map(|(stroke, _)| {
+            let (value, set) = create_slice(cx, stroke, |c| c.clone(), |c, v| *c = v);
+            view! { cx, <ColorInput value set /> }
+        });
+        let fill = move || {
+            preset_colors().and_then(|(_, fill)| fill).map(|fill| {
+                let (value, set) = create_slice(cx, fill, |c| c.clone(), |c, v| *c = v);
+                view! { cx, <ColorInput value set /> }
+            })
+        };
+        let with_preset = move |f: fn(&StylePreset) -> String| {
+            settings.with(|s| s.style_presets.iter().find(|p| p.id == id).map(f))
+        };
+        let update = move |f: Box<dyn FnOnce(&mut StylePreset)>| {
+            settings.update(|s| {
+                if let Some(p) = s.style_preset_mut(id) {
+                    f(p);
+                }
+            })
+        };
+        let has_fill = move || {
+            settings.with(|s| {
+                s.style_presets
+                    .iter()
+                    .any(|p| p.id == id && p.fill.is_some())
+            })
+        };
+
+        view! { cx,
+            <div class="flex flex-col border-t border-gray-300 mt-2 pt-1">
+                <div class="flex flex-row justify-between">
+                    <input
+                        class="w-24"
+                        prop:value=move || with_preset(|p| p.name.clone()).unwrap_or_default()
+                        on:change=move |ev| {
+                            let name = event_target_value(&ev);
+                            update(Box::new(move |p| p.name = name));
+                        }
+                    />
+                    <button
+                        class="border-2 border-gray-800 px-1"
+                        on:click=move |_| {
+                            settings.update(|s| s.remove_style_preset(id));
+                            // Steps using it go back to their own style
+                            steps.with(|steps| {
+                                for step in steps {
+                                    if step.style.with(|s| s.preset == Some(id)) {
+                                        step.style.update(|s| s.preset = None);
+                                    }
+                                }
+                            });
+                        }
+                    >
+                        "Remove"
+                    </button>
+                </div>
+                <label class="flex flex-row">
+                    "stroke: "
+                    {stroke}
+                </label>
+                <label>
+                    "width: "
+                    <input
+                        type="number"
+                        class="w-16"
+                        prop:value=move || with_preset(|p| p.line_width.to_string()).unwrap_or_default()
+                        on:change=move |ev| {
+                            if let Ok(w) = event_target_value(&ev).parse::<f64>() {
+                                update(Box::new(move |p| p.line_width = w.max(0.0)));
+                            }
+                        }
+                    />
+                </label>
+                <label>
+                    "dash: "
+                    <select on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        if let Some(dash) = LineDash::ALL.into_iter().find(|d| d.as_str() == value) {
+                            update(Box::new(move |p| p.dash = dash));
+                        }
+                    }>
+                        {LineDash::ALL.into_iter().map(|dash| view! { cx,
+                            <option
+                                value=dash.as_str()
+                                selected=move || with_preset(|p| p.dash.as_str().to_string()).as_deref() == Some(dash.as_str())
+                            >
+                                {dash.as_str()}
+                            </option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label class="flex flex-row">
+                    <input
+                        type="checkbox"
+                        prop:checked=has_fill
+                        on:change=move |_| update(Box::new(move |p| {
+                            p.fill = match p.fill {
+                                Some(_) => None,
+                                None => Some(create_rw_signal(cx, "#cbd5e1".to_string())),
+                            }
+                        }))
+                    />
+                    " fill "
+                    {fill}
+                </label>
+            </div>
+        }
+    };
+
+    // Rows are only rebuilt when presets come and go, not on every edit to one
+    let preset_ids = create_memo(cx, move |_| {
+        settings.with(|s| s.style_presets.iter().map(|p| p.id).collect::<Vec<_>>())
+    });
+
+    view! { cx,
+        <div class="flex flex-col mt-4">
+            <p>"Style Presets"</p>
+            {move || preset_ids.get().into_iter().map(preset_row).collect::<Vec<_>>()}
+            <button class="border-2 border-gray-800 mt-2" on:click=move |_| settings.update(|s| s.add_style_preset(cx))>
+                "+ Style Preset"
+            </button>
+        </div>
+    }
+}
+
+/// Picks the bool data (if any) a step's Suppress follows
+#[component]
+fn SuppressWithSelect(cx: Scope, step: Step) -> impl IntoView {
+    let datas = use_context::<RwSignal<Vec<Data>>>(cx).unwrap();
+    let toggles = move || {
+        datas.with(|datas| {
+            datas
+                .iter()
+                .filter(|d| !d.trashed.get() && matches!(d.data, DataData::Bool(_)))
+                .map(|d| d.id)
+                .collect::<Vec<_>>()
+        })
+    };
+    let data_id = move || step.suppress_with.get().and_then(|r| r.data_id());
+
+    view! { cx,
+        <select class="text-sm" on:change=move |ev| {
+            step.suppress_with.set(event_target_value(&ev).parse::<usize>().ok().map(|id| {
+                DataRef(vec![DataRefPathEl::Data, DataRefPathEl::WithId(id)])
+            }));
+        }>
+            <option value="none" selected=move || data_id().is_none()>"by hand"</option>
+            {move || toggles().into_iter().map(|id| view! { cx,
+                <option value=id.to_string() selected=move || data_id() == Some(id)>
+                    {format!("unless Data #{}", id)}
+                </option>
+            }).collect::<Vec<_>>()}
+        </select>
+    }
+}
+
+#[component]
+fn StepArtboardSelect(cx: Scope, step: Step) -> impl IntoView {
+    let settings = use_context::<RwSignal<DocumentSettings>>(cx).unwrap();
+
+    view! { cx,
+        <select class="text-sm" on:change=move |ev| {
+            step.artboard.set(event_target_value(&ev).parse::<usize>().ok());
+        }>
+            <option value="all" selected=move || step.artboard.get().is_none()>"All artboards"</option>
+            {move || settings.with(|s| {
+                s.artboards
+                    .iter()
+                    .map(|a| {
+                        let id = a.id;
+                        view! { cx,
+                            <option value=id.to_string() selected=move || step.artboard.get() == Some(id)>
+                                {a.name.clone()}
+                            </option>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })}
+        </select>
+    }
+}
+
+/// Something the command palette can run
+#[derive(Clone)]
+pub struct PaletteCommand {
+    pub name: String,
+    pub run: Rc<dyn Fn()>,
+}
+
+impl PaletteCommand {
+    pub fn new(name: impl Into<String>, run: impl Fn() + 'static) -> Self {
+        Self {
+            name: name.into(),
+            run: Rc::new(run),
+        }
+    }
+}
+
+/// How many characters `name` skips to match `query` as a case-insensitive subsequence
+pub fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = next + name[next..].iter().position(|c| *c == q)?;
+        score += found - next;
+        next = found + 1;
+    }
+    Some(score)
+}
+
+/// Ctrl+K overlay for searching and running `commands`, plus jumping to any step
+#[component]
+fn CommandPalette(cx: Scope, commands: Vec<PaletteCommand>, open: RwSignal<bool>) -> impl IntoView {
+    let steps = use_context::<RwSignal<Vec<Step>>>(cx).unwrap();
+    let commands = store_value(cx, commands);
+
+    let query = create_rw_signal(cx, String::new());
+    let highlighted = create_rw_signal(cx, 0usize);
+    create_effect(cx, move |_| {
+        if open.get() {
+            query.set(String::new());
+        }
+    });
+
+    let matches = move || {
+        let mut all = commands.get();
+        all.extend(steps.with(|steps| {
+            steps
+                .iter()
+                .filter(|s| !s.trashed.get())
+                .map(|s| {
+                    let id = s.id;
+                    PaletteCommand::new(format!("Go to step[{}]", id), move || jump_to_step(cx, id))
+                })
+                .collect::<Vec<_>>()
+        }));
+
+        let query = query.get();
+        let mut scored: Vec<(usize, PaletteCommand)> = all
+            .into_iter()
+            .filter_map(|c| fuzzy_score(&query, &c.name).map(|score| (score, c)))
+            .collect();
+        // Stable, so equally good matches keep their listed order
+        scored.sort_by_key(|(score, _)| *score);
+        scored.into_iter().map(|(_, c)| c).collect::<Vec<_>>()
+    };
+    create_effect(cx, move |_| {
+        query.with(|_| ());
+        highlighted.set(0);
+    });
+
+    let run = move |command: PaletteCommand| {
+        open.set(false);
+        (command.run)();
+    };
+
+    let on_keydown = move |ev: web_sys::KeyboardEvent| match ev.key().as_str() {
+        "Escape" => open.set(false),
+        "Enter" => {
+            if let Some(command) = matches().get(highlighted.get()).cloned() {
+                run(command);
+            }
+        }
+        "ArrowDown" => {
+            ev.prevent_default();
+            let count = matches().len();
+            highlighted.update(|h| *h = (*h + 1).min(count.saturating_sub(1)));
+        }
+        "ArrowUp" => {
+            ev.prevent_default();
+            highlighted.update(|h| *h = h.saturating_sub(1));
+        }
+        _ => {}
+    };
+
+    view! { cx,
+        {move || open.get().then(|| view! { cx,
+            <div
+                class="fixed inset-0 z-10 flex justify-center items-start pt-24 bg-black/30 print:hidden"
+                on:click=move |_| open.set(false)
+            >
+                <div
+                    class="flex flex-col w-[28rem] max-h-[60vh] bg-white rounded shadow-lg"
+                    on:click=|ev| ev.stop_propagation()
+                >
+                    <input
+                        id="command-palette-input"
+                        class="border-b-2 border-gray-300 p-2 outline-none"
+                        placeholder="Type a command..."
+                        prop:value=move || query.get()
+                        on:input=move |ev| query.set(event_target_value(&ev))
+                        on:keydown=on_keydown
+                    />
+                    <div class="flex flex-col overflow-scroll">
+                        {move || matches().into_iter().enumerate().map(|(i, command)| {
+                            let name = command.name.clone();
+                            view! { cx,
+                                <button
+                                    class=move || if highlighted.get() == i {
+                                        "text-left px-2 py-1 bg-blue-100"
+                                    } else {
+                                        "text-left px-2 py-1 hover:bg-slate-100"
+                                    }
+                                    on:mouseenter=move |_| highlighted.set(i)
+                                    on:click=move |_| run(command.clone())
+                                >
+                                    {name}
+                                </button>
+                            }
+                        }).collect::<Vec<_>>()}
+                    </div>
+                </div>
+            </div>
+        })}
+    }
+}
+
+#[component]
+pub fn DrawlingView(cx: Scope) -> impl IntoView {
+    let datas = create_rw_signal::<Vec<Data>>(cx, Vec::new());
+    provide_context(cx, datas);
+
+    let steps = create_rw_signal::<Vec<Step>>(cx, Vec::new());
+    provide_context(cx, steps);
+
+    let infer_target: RwSignal<Option<InferTarget>> = create_rw_signal(cx, None);
+    provide_context(cx, infer_target);
+
+    let binding_preview: RwSignal<Option<BindingPreview>> = create_rw_signal(cx, None);
+    provide_context(cx, binding_preview);
+
+    let pending_placement = create_rw_signal(cx, PendingPlacement::default());
+    provide_context(cx, pending_placement);
+    // Cancelling a pending binding drops whatever it was previewing
+    create_effect(cx, move |_| {
+        if infer_target.get().is_none() {
+            end_binding_preview(cx);
+            // Cancelling drops the rest of a new step's placement too
+            pending_placement.set(PendingPlacement::default());
+        }
+    });
+
+    let selection = create_rw_signal(cx, Selection::default());
+    provide_context(cx, selection);
+
+    let document_settings = create_rw_signal(cx, DocumentSettings::default());
+    provide_context(cx, document_settings);
+
+    let view_transform = create_rw_signal(cx, ViewTransform::default());
+    provide_context(cx, view_transform);
+
+    let snap_settings = create_rw_signal(cx, SnapSettings::default());
+    provide_context(cx, snap_settings);
+
+    let canvas_tool = create_rw_signal(cx, CanvasTool::default());
+    provide_context(cx, canvas_tool);
+
+    let calibration = create_rw_signal(cx, Calibration::default());
+    provide_context(cx, calibration);
+
+    let display_mode = create_rw_signal(cx, DisplayMode::default());
+    provide_context(cx, display_mode);
+
+    let image_cache = create_rw_signal(cx, ImageCache::default());
+    provide_context(cx, image_cache);
+
+    let new_step_defaults = create_rw_signal(cx, NewStepDefaults::default());
+    provide_context(cx, new_step_defaults);
+
+    let last_pointer = create_rw_signal(cx, LastPointer::default());
+    provide_context(cx, last_pointer);
+
+    let flashed_step = create_rw_signal(cx, FlashedStep::default());
+    provide_context(cx, flashed_step);
+
+    let watches = create_rw_signal::<Vec<Watch>>(cx, Vec::new());
+    provide_context(cx, watches);
+
+    let parameter_table = create_rw_signal(cx, ParameterTable::default());
+    provide_context(cx, parameter_table);
+
+    let export_errors = create_rw_signal(cx, ExportErrors::default());
+    provide_context(cx, export_errors);
+
+    // Steps suppressed along with bool data follow it as it's toggled
+    create_effect(cx, move |_| {
+        for step in steps.get() {
+            let Some(r) = step.suppress_with.get() else {
+                continue;
+            };
+            let on = match r.data_id().and_then(|id| find_data(cx, id)) {
+                Some(Data {
+                    data: DataData::Bool(b),
+                    ..
+                }) => b.get(),
+                _ => continue,
+            };
+            if step.suppressed.get() == on {
+                step.suppressed.set(!on);
+            }
+        }
+    });
+
+    // Also catches printing from the browser menu, not just the Print button
+    for (event, mode) in [
+        ("beforeprint", DisplayMode::Print),
+        ("afterprint", DisplayMode::Edit),
+    ] {
+        let closure =
+            wasm_bindgen::prelude::Closure::<dyn Fn()>::new(move || display_mode.set(mode))
+                .into_js_value();
+        window()
+            .add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())
+            .unwrap();
+    }
+
+    console_log("DrawlingView Setup");
+
+    let add_draw_line_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawLine {
+                start: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal {
+                        x: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+                        y: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+                    }),
+                ),
+                end: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal {
+                        x: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+                        y: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+                    }),
+                ),
+            },
+        );
+    };
+    let add_draw_image_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawImage {
+                src: create_rw_signal(cx, String::new()),
+                position: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                scale: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.1))),
+            },
+        );
+    };
+    let add_draw_text_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawText {
+                anchor: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                content: create_rw_signal(cx, "Label".to_string()),
+                size: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 2.0))),
+                path: create_rw_signal(cx, None),
+            },
+        );
+    };
+    let add_scale_bar_step = move || {
+        push_new_step(
+            cx,
+            StepData::ScaleBar {
+                origin: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                length: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 10.0))),
+                divisions: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 5.0))),
+                unit: create_rw_signal(cx, "m".to_string()),
+                size: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+            },
+        );
+    };
+    let add_axes_step = move || {
+        let number = |n: f64| create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, n)));
+        push_new_step(
+            cx,
+            StepData::Axes {
+                origin: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                x_min: number(-10.0),
+                x_max: number(10.0),
+                y_min: number(-10.0),
+                y_max: number(10.0),
+                spacing: number(2.0),
+                size: number(1.0),
+            },
+        );
+    };
+    let add_callout_step = move || {
+        push_new_step(
+            cx,
+            StepData::Callout {
+                anchor: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                tip: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point { x: 2.0, y: 2.0 })),
+                ),
+                content: create_rw_signal(cx, "Label".to_string()),
+                size: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 2.0))),
+            },
+        );
+    };
+    let add_centroid_step = move || {
+        let point = |x, y| {
+            create_rw_signal(
+                cx,
+                ResolvableTo::T(PointSignal::literal(cx, Point { x, y })),
+            )
+        };
+        push_new_step(
+            cx,
+            StepData::Centroid {
+                points: create_rw_signal(
+                    cx,
+                    vec![point(0.0, 0.0), point(2.0, 0.0), point(1.0, 2.0)],
+                ),
+            },
+        );
+    };
+    let add_draw_path_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawPath {
+                points: create_rw_signal(
+                    cx,
+                    vec![
+                        create_rw_signal(
+                            cx,
+                            ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                        ),
+                        create_rw_signal(
+                            cx,
+                            ResolvableTo::T(PointSignal::literal(cx, Point { x: 1.0, y: 1.0 })),
+                        ),
+                    ],
+                ),
+            },
+        );
+    };
+    let add_draw_rect_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawRect {
+                origin: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                width: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                height: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+            },
+        );
+    };
+    let add_draw_rounded_rect_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawRoundedRect {
+                origin: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                width: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 2.0))),
+                height: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                radius: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.25))),
+            },
+        );
+    };
+    let add_draw_polygon_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawPolygon {
+                center: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                radius: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                sides: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 6.0))),
+            },
+        );
+    };
+    let add_point_grid_step = move || {
+        push_new_step(
+            cx,
+            StepData::PointGrid {
+                origin: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                col_spacing: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                row_spacing: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                cols: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 3.0))),
+                rows: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 3.0))),
+            },
+        );
+    };
+    let add_annulus_step = move || {
+        push_new_step(
+            cx,
+            StepData::Annulus {
+                center: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                inner_radius: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.5))),
+                outer_radius: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+            },
+        );
+    };
+    let add_draw_ray_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawRay {
+                origin: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                through: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point { x: 1.0, y: 0.0 })),
+                ),
+            },
+        );
+    };
+    let add_midpoint_step = move || {
+        push_new_step(
+            cx,
+            StepData::Midpoint {
+                a: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                b: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point { x: 2.0, y: 0.0 })),
+                ),
+            },
+        );
+    };
+    let add_angle_step = move || {
+        push_new_step(
+            cx,
+            StepData::Angle {
+                a: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point { x: 2.0, y: 0.0 })),
+                ),
+                vertex: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                b: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point { x: 0.0, y: 2.0 })),
+                ),
+            },
+        );
+    };
+    let add_distance_step = move || {
+        push_new_step(
+            cx,
+            StepData::Distance {
+                a: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                b: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point { x: 2.0, y: 0.0 })),
+                ),
+            },
+        );
+    };
+    let add_draw_infinite_line_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawInfiniteLine {
+                a: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                b: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point { x: 1.0, y: 0.0 })),
+                ),
+            },
+        );
+    };
+    let add_draw_arrow_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawArrow {
+                start: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                end: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point { x: 5.0, y: 0.0 })),
+                ),
+                head_size: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+            },
+        );
+    };
+    let add_draw_arc_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawArc {
+                center: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                radius: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                start_angle: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+                end_angle: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(create_rw_signal(cx, std::f64::consts::PI)),
+                ),
+            },
+        );
+    };
+    let add_compass_step = move || push_compass_steps(cx, false);
+    let add_transfer_length_step = move || push_compass_steps(cx, true);
+    let add_draw_sector_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawSector {
+                center: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                radius: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                start_angle: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+                end_angle: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(create_rw_signal(cx, std::f64::consts::FRAC_PI_2)),
+                ),
+            },
+        );
+    };
+    let add_perpendicular_step = move || {
+        push_new_step(
+            cx,
+            StepData::Perpendicular {
+                line: create_rw_signal(cx, None),
+                through: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+            },
+        );
+    };
+    let add_tangent_step = move || {
+        push_new_step(
+            cx,
+            StepData::Tangent {
+                circle: create_rw_signal(cx, None),
+                from: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                other_side: create_rw_signal(cx, false),
+            },
+        );
+    };
+    let add_parallel_offset_step = move || {
+        push_new_step(
+            cx,
+            StepData::ParallelOffset {
+                line: create_rw_signal(cx, None),
+                distance: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+            },
+        );
+    };
+    let add_offset_curve_step = move || {
+        push_new_step(
+            cx,
+            StepData::OffsetCurve {
+                source: create_rw_signal(cx, None),
+                distance: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                join: create_rw_signal(cx, LineJoin::Miter),
+            },
+        );
+    };
+    let add_trim_extend_step = move || {
+        push_new_step(
+            cx,
+            StepData::TrimExtend {
+                line: create_rw_signal(cx, None),
+                boundary: create_rw_signal(cx, None),
+                keep_end: create_rw_signal(cx, false),
+            },
+        );
+    };
+    let add_mirror_step = move || {
+        push_new_step(
+            cx,
+            StepData::Mirror {
+                source: create_rw_signal(cx, None),
+                axis: create_rw_signal(cx, None),
+            },
+        );
+    };
+    let add_rotate_copy_step = move || {
+        push_new_step(
+            cx,
+            StepData::RotateCopy {
+                source: create_rw_signal(cx, None),
+                center: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                angle: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 90.0))),
+            },
+        );
+    };
+    let add_translate_copy_step = move || {
+        push_new_step(
+            cx,
+            StepData::TranslateCopy {
+                source: create_rw_signal(cx, None),
+                dx: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                dy: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+            },
+        );
+    };
+    let add_transform_copy_step = move || {
+        push_new_step(
+            cx,
+            StepData::TransformCopy {
+                source: create_rw_signal(cx, None),
+                transform: create_rw_signal(cx, None),
+            },
+        );
+    };
+    let add_linear_array_step = move || {
+        push_new_step(
+            cx,
+            StepData::LinearArray {
+                source: create_rw_signal(cx, None),
+                count: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 3.0))),
+                dx: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+                dy: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+            },
+        );
+    };
+    let add_polar_array_step = move || {
+        push_new_step(
+            cx,
+            StepData::PolarArray {
+                source: create_rw_signal(cx, None),
+                center: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                count: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 6.0))),
+                sweep: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 360.0))),
+            },
+        );
+    };
+    let add_projection_step = move || {
+        push_new_step(
+            cx,
+            StepData::Projection {
+                point: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
+                ),
+                line: create_rw_signal(cx, None),
+            },
+        );
+    };
+    let add_point_along_line_step = move || {
+        push_new_step(
+            cx,
+            StepData::PointAlongLine {
+                line: create_rw_signal(cx, None),
+                t: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.25))),
+            },
+        );
+    };
+    let add_intersection_step = move || {
+        push_new_step(
+            cx,
+            StepData::Intersection {
+                line_a: create_rw_signal(cx, None),
+                line_b: create_rw_signal(cx, None),
+            },
+        );
+    };
+    let add_clip_step = move || {
+        push_new_step(
+            cx,
+            StepData::Clip {
+                shape: create_rw_signal(cx, None),
+            },
+        );
+    };
+    let add_shape_boolean_step = move || {
+        push_new_step(
+            cx,
+            StepData::ShapeBoolean {
+                op: create_rw_signal(cx, BoolOp::Union),
+                a: create_rw_signal(cx, None),
+                b: create_rw_signal(cx, None),
+            },
+        );
+    };
+    let add_bounding_box_step = move || {
+        push_new_step(
+            cx,
+            StepData::BoundingBox {
+                sources: create_rw_signal(cx, vec![create_rw_signal(cx, None)]),
+            },
+        );
+    };
+    let add_function_plot_step = move || {
+        push_new_step(
+            cx,
+            StepData::FunctionPlot {
+                f: create_rw_signal(cx, "sin(x)".to_string()),
+                x_min: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+                x_max: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 10.0))),
+            },
+        );
+    };
+    let add_parametric_curve_step = move || {
+        push_new_step(
+            cx,
+            StepData::ParametricCurve {
+                x: create_rw_signal(cx, "cos(t)".to_string()),
+                y: create_rw_signal(cx, "sin(t)".to_string()),
+                t_min: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+                t_max: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(create_rw_signal(cx, std::f64::consts::PI * 2.0)),
+                ),
+                samples: create_rw_signal(cx, 100),
+            },
+        );
+    };
+    let add_draw_point_step = move || {
+        push_new_step(
+            cx,
+            StepData::DrawPoint {
+                point: create_rw_signal(
+                    cx,
+                    ResolvableTo::T(PointSignal {
+                        x: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+                        y: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
+                    }),
+                ),
+                marker: create_rw_signal(cx, PointMarker::default()),
+                size: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 1.0))),
+            },
+        );
     };
-    let canvas_clone_mousemove = canvas.clone();
-
-    let (mouse_pos, set_mouse_pos) = create_signal(cx, Point::default());
-
-    let context = canvas
-        .get_context("2d")
-        .unwrap()
-        .unwrap()
-        .dyn_into::<web_sys::CanvasRenderingContext2d>()
-        .unwrap();
-
-    let current_aspect_ratio = canvas.width() as f64 / canvas.height() as f64;
-    let desired_aspect_ratio = current_aspect_ratio;
-    let width_scale_factor = (desired_aspect_ratio / current_aspect_ratio).sqrt();
-    let height_scale_factor = 1.0 / width_scale_factor;
-
-    canvas.set_width((canvas.width() as f64 * scale_factor * width_scale_factor).ceil() as u32);
-    canvas.set_height((canvas.height() as f64 * scale_factor * height_scale_factor).ceil() as u32);
-
-    context.scale(scale_factor, scale_factor).unwrap();
-    context.set_line_width(4.0 / scale_factor);
-
-    let canvas_width = canvas.width();
-    let canvas_height = canvas.height();
-
-    // todo(chad): make mouse_pos a PointSignal
-    let hover_infer_target = create_rw_signal(
-        cx,
-        Some(ResolvableTo::T(PointSignal {
-            x: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, mouse_pos().x))),
-            y: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, mouse_pos().y))),
-        })),
-    );
 
-    let mousemove_callback = move |e: web_sys::MouseEvent| {
-        let rect = canvas_clone_mousemove.get_bounding_client_rect();
-        set_mouse_pos.set(Point {
-            x: (e.client_x() as f64 - rect.x() as f64) / rect.width() as f64 * canvas_width as f64
-                / scale_factor,
-            y: (e.client_y() as f64 - rect.y() as f64) / rect.height() as f64
-                * canvas_height as f64
-                / scale_factor,
+    // Steps which couldn't be ordered by the last dependency sort because of a cycle
+    let cyclic_steps = create_rw_signal(cx, Vec::<usize>::new());
+    let sort_steps = move |_| {
+        steps.update(|s| {
+            let (ordered, cyclic) = topological_order(s);
+            let mut sorted: Vec<Step> = ordered
+                .iter()
+                .chain(cyclic.iter())
+                .filter_map(|id| s.iter().find(|s| s.id == *id).cloned())
+                .collect();
+            std::mem::swap(s, &mut sorted);
+            cyclic_steps.set(cyclic);
         });
     };
-    let mousemove_closure =
-        wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(mousemove_callback).into_js_value();
-    canvas
-        .add_event_listener_with_callback("mousemove", mousemove_closure.as_ref().unchecked_ref())
-        .unwrap();
-
-    let mousedown_callback = move |_e: web_sys::MouseEvent| {
-        let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
 
-        if let (Some(InferTarget::Point(it)), Some(hover_infer_target)) =
-            (context_infer_target.get(), hover_infer_target.get())
-        {
-            it.set(hover_infer_target);
-            context_infer_target.set(None);
+    // (original, copy) ids from the last duplicate, for repeating its placement
+    let last_duplicate = create_rw_signal(cx, Vec::<(usize, usize)>::new());
+    // Forgotten once any of its steps are deleted, so there's nothing stale to measure
+    create_effect(cx, move |_| {
+        let removed = steps.with(|steps| {
+            last_duplicate.with(|pairs| {
+                pairs
+                    .iter()
+                    .flat_map(|(o, c)| [o, c])
+                    .any(|id| !steps.iter().any(|s| s.id == *id && !s.trashed.get()))
+            })
+        });
+        if removed {
+            last_duplicate.set(Vec::new());
         }
-    };
-    let mousedown_closure =
-        wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(mousedown_callback).into_js_value();
-    canvas
-        .add_event_listener_with_callback("mousedown", mousedown_closure.as_ref().unchecked_ref())
-        .unwrap();
-
-    let snap_points: Memo<Vec<DataRef>> = create_memo(cx, move |_| {
-        console_log("Memoizing snap points!");
-        steps.with(|steps| steps.iter().map(|s| s.snap_points()).flatten().collect())
     });
+    let duplicate_selection = move |_| {
+        let mut selected = selection.get().steps;
+        selected.sort();
 
-    create_effect(cx, move |_| {
-        // console_log("running the effect!");
-
-        context.clear_rect(0.0, 0.0, canvas_width as f64, canvas_height as f64);
-
-        steps.with(|steps| {
-            for step in steps.iter() {
-                match step.data {
-                    StepData::DrawPoint(point) => match point() {
-                        ResolvableTo::T(point) => {
-                            context.begin_path();
-                            context
-                                .arc(
-                                    point.x.get().resolve(cx),
-                                    point.y.get().resolve(cx),
-                                    1.0,
-                                    0.0,
-                                    std::f64::consts::PI * 2.0,
-                                )
-                                .unwrap();
-                            context.stroke();
-                        }
-                        ResolvableTo::Ref { .. } => todo!(),
-                    },
-                    StepData::DrawLine { start, end } => {
-                        let start: Point = start().resolve(cx);
-                        let end: Point = end().resolve(cx);
-
-                        context.begin_path();
-                        context.move_to(start.x, start.y);
-                        context.line_to(end.x, end.y);
-                        context.stroke();
-                    }
-                }
-            }
+        // Duplicating fresh copies again moves them on by the same offset, building an array
+        let offset = last_duplicate.with(|pairs| {
+            let mut last_copies: Vec<usize> = pairs.iter().map(|(_, copy)| *copy).collect();
+            last_copies.sort();
+            (!pairs.is_empty() && last_copies == selected)
+                .then(|| placement_offset(cx, pairs))
+                .flatten()
         });
 
-        snap_points.with(|snap_points| {
-            for sp in snap_points.iter() {
-                context.set_stroke_style(&wasm_bindgen::JsValue::from_str("red"));
-
-                let sp = ResolveToPoint::resolve(sp, cx);
-
-                context.begin_path();
-                context
-                    .arc(sp.x, sp.y, 1.3, 0.0, std::f64::consts::PI * 2.0)
-                    .unwrap();
-                context.stroke();
-            }
+        let originals: Vec<usize> = steps.with(|steps| {
+            steps
+                .iter()
+                .filter(|s| selected.contains(&s.id))
+                .map(|s| s.id)
+                .collect()
         });
-
-        let context_infer_target = use_context::<RwSignal<Option<InferTarget>>>(cx).unwrap();
-        if context_infer_target.get().is_some() {
-            hover_infer_target.set(Some(ResolvableTo::T(PointSignal {
-                x: create_rw_signal(
-                    cx,
-                    ResolvableTo::T(create_rw_signal(cx, mouse_pos().x.round())),
-                ),
-                y: create_rw_signal(
-                    cx,
-                    ResolvableTo::T(create_rw_signal(cx, mouse_pos().y.round())),
-                ),
-            })));
-
-            // todo(chad): @Performance
-            // This subscribes the effect to any mouse move changes, which is a lot of unnecessary runs.
-            // We should only run this effect when the mouse movement causes a change to the currently selected snap point.
-            snap_points.with(|snap_points| {
-                for sp in snap_points.iter() {
-                    let spr = ResolveToPoint::resolve(sp, cx);
-                    let dist =
-                        ((spr.x - mouse_pos().x).powi(2) + (spr.y - mouse_pos().y).powi(2)).sqrt();
-                    if dist < 5.0 {
-                        hover_infer_target.set(Some(ResolvableTo::Ref(sp.clone())));
-                    }
-                }
-            });
+        let copies = duplicate_steps(cx, &selected);
+        if let Some(offset) = offset {
+            for id in copies.iter() {
+                find_step(cx, *id).translate(offset);
+            }
         }
 
-        if let Some(hit) = hover_infer_target.get() {
-            let mut fill = false;
-            if let ResolvableTo::Ref(_) = hit {
-                context.set_fill_style(&wasm_bindgen::JsValue::from_str("green"));
-                fill = true;
-            }
+        last_duplicate.set(originals.into_iter().zip(copies.iter().copied()).collect());
+        selection.set(Selection { steps: copies });
+    };
 
-            let hit = hit.resolve(cx);
-            context.begin_path();
-            context.set_stroke_style(&wasm_bindgen::JsValue::from_str("green"));
-            context
-                .arc(hit.x, hit.y, 1.0, 0.0, std::f64::consts::PI * 2.0)
-                .unwrap();
-            context.stroke();
-            if fill {
-                context.fill();
+    let explode_error = create_rw_signal::<Option<String>>(cx, None);
+    let explode_selection = move |_| {
+        let mut exploded = Vec::new();
+        let mut errors = Vec::new();
+        for id in selection.get().steps {
+            if !step_by_id(cx, id).map_or(false, |s| s.is_copy()) {
+                continue;
+            }
+            match explode_step(cx, id) {
+                Ok(ids) => exploded.extend(ids),
+                Err(e) => errors.push(format!("Couldn't explode step[{}]: {}", id, e)),
             }
         }
-    });
-
-    view! { cx,
-        <div class="block grow self-center">
-            { canvas }
-        </div>
-    }
-}
-
-#[component]
-pub fn DrawlingView(cx: Scope) -> impl IntoView {
-    let datas = create_rw_signal::<Vec<Data>>(cx, Vec::new());
-
-    let steps = create_rw_signal::<Vec<Step>>(cx, Vec::new());
-    provide_context(cx, steps);
-
-    let infer_target: RwSignal<Option<InferTarget>> = create_rw_signal(cx, None);
-    provide_context(cx, infer_target);
-
-    console_log("DrawlingView Setup");
+        explode_error.set((!errors.is_empty()).then(|| errors.join("\n")));
+        selection.set(Selection { steps: exploded });
+    };
 
-    let add_draw_line_step = move |_| {
-        steps.update(|s| {
-            s.push(Step {
-                id: s.len(),
-                data: StepData::DrawLine {
-                    start: create_rw_signal(
-                        cx,
-                        ResolvableTo::T(PointSignal {
-                            x: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
-                            y: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
-                        }),
-                    ),
-                    end: create_rw_signal(
+    let add_number_data = move || {
+        datas.update(|d| {
+            d.push(Data {
+                id: next_data_id(d),
+                data: DataData::Number(create_rw_signal(cx, 0.0)),
+                trashed: create_rw_signal(cx, false),
+            })
+        });
+    };
+    let add_slider_data = move || {
+        datas.update(|d| {
+            d.push(Data {
+                id: next_data_id(d),
+                data: DataData::Slider {
+                    value: create_rw_signal(cx, 0.5),
+                    bounds: create_rw_signal(
                         cx,
-                        ResolvableTo::T(PointSignal {
-                            x: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
-                            y: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
-                        }),
+                        SliderBounds {
+                            min: Some(0.0),
+                            max: Some(1.0),
+                            step: Some(0.01),
+                        },
                     ),
                 },
+                trashed: create_rw_signal(cx, false),
             })
         });
     };
-    let add_draw_point_step = move |_| {
-        steps.update(|s| {
-            s.push(Step {
-                id: s.len(),
-                data: StepData::DrawPoint(create_rw_signal(
-                    cx,
-                    ResolvableTo::T(PointSignal {
-                        x: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
-                        y: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
-                    }),
-                )),
+    let add_angle_data = move || {
+        datas.update(|d| {
+            d.push(Data {
+                id: next_data_id(d),
+                data: DataData::Angle(create_rw_signal(cx, 0.0)),
+                trashed: create_rw_signal(cx, false),
             })
         });
     };
-
-    let add_number_data = move |_| {
+    let add_vector_data = move || {
         datas.update(|d| {
             d.push(Data {
-                id: d.len(),
-                data: DataData::Number(create_rw_signal(cx, 0.0)),
+                id: next_data_id(d),
+                data: DataData::Vector {
+                    dx: create_rw_signal(cx, 10.0),
+                    dy: create_rw_signal(cx, 0.0),
+                },
+                trashed: create_rw_signal(cx, false),
+            })
+        });
+    };
+    let add_transform_data = move || {
+        datas.update(|d| {
+            d.push(Data {
+                id: next_data_id(d),
+                data: DataData::Transform {
+                    dx: create_rw_signal(cx, 0.0),
+                    dy: create_rw_signal(cx, 0.0),
+                    angle: create_rw_signal(cx, 0.0),
+                    scale: create_rw_signal(cx, 1.0),
+                    about_x: create_rw_signal(cx, 0.0),
+                    about_y: create_rw_signal(cx, 0.0),
+                },
+                trashed: create_rw_signal(cx, false),
+            })
+        });
+    };
+    let add_point_list_data = move || {
+        datas.update(|d| {
+            d.push(Data {
+                id: next_data_id(d),
+                data: DataData::PointList(create_rw_signal(cx, Vec::new())),
+                trashed: create_rw_signal(cx, false),
+            })
+        });
+    };
+    let add_bool_data = move || {
+        datas.update(|d| {
+            d.push(Data {
+                id: next_data_id(d),
+                data: DataData::Bool(create_rw_signal(cx, true)),
+                trashed: create_rw_signal(cx, false),
+            })
+        });
+    };
+    let add_color_data = move || {
+        datas.update(|d| {
+            d.push(Data {
+                id: next_data_id(d),
+                data: DataData::Color(create_rw_signal(cx, "#000000".to_string())),
+                trashed: create_rw_signal(cx, false),
             })
         });
     };
-    let add_point_data = move |_| {
+    let add_point_data = move || {
         datas.update(|d| {
             d.push(Data {
-                id: d.len(),
+                id: next_data_id(d),
                 data: DataData::Point(create_rw_signal(
                     cx,
-                    PointSignal {
-                        x: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
-                        y: create_rw_signal(cx, ResolvableTo::T(create_rw_signal(cx, 0.0))),
-                    },
+                    ResolvableTo::T(PointSignal::literal(cx, Point::default())),
                 )),
+                trashed: create_rw_signal(cx, false),
             })
         });
     };
 
+    let palette_open = create_rw_signal(cx, false);
+    let palette_commands = vec![
+        PaletteCommand::new("Draw Point", add_draw_point_step),
+        PaletteCommand::new("Draw Line", add_draw_line_step),
+        PaletteCommand::new("Draw Arrow", add_draw_arrow_step),
+        PaletteCommand::new("Draw Ray", add_draw_ray_step),
+        PaletteCommand::new("Draw Infinite Line", add_draw_infinite_line_step),
+        PaletteCommand::new("Midpoint", add_midpoint_step),
+        PaletteCommand::new("Centroid", add_centroid_step),
+        PaletteCommand::new("Distance", add_distance_step),
+        PaletteCommand::new("Angle", add_angle_step),
+        PaletteCommand::new("Point Along Line", add_point_along_line_step),
+        PaletteCommand::new("Projection", add_projection_step),
+        PaletteCommand::new("Intersection", add_intersection_step),
+        PaletteCommand::new("Perpendicular", add_perpendicular_step),
+        PaletteCommand::new("Tangent", add_tangent_step),
+        PaletteCommand::new("Parallel Offset", add_parallel_offset_step),
+        PaletteCommand::new("Offset Curve", add_offset_curve_step),
+        PaletteCommand::new("Trim / Extend", add_trim_extend_step),
+        PaletteCommand::new("Mirror", add_mirror_step),
+        PaletteCommand::new("Rotate Copy", add_rotate_copy_step),
+        PaletteCommand::new("Translate Copy", add_translate_copy_step),
+        PaletteCommand::new("Transform Copy", add_transform_copy_step),
+        PaletteCommand::new("Linear Array", add_linear_array_step),
+        PaletteCommand::new("Polar Array", add_polar_array_step),
+        PaletteCommand::new("Draw Path", add_draw_path_step),
+        PaletteCommand::new("Draw Rect", add_draw_rect_step),
+        PaletteCommand::new("Draw Rounded Rect", add_draw_rounded_rect_step),
+        PaletteCommand::new("Draw Polygon", add_draw_polygon_step),
+        PaletteCommand::new("Annulus", add_annulus_step),
+        PaletteCommand::new("Point Grid", add_point_grid_step),
+        PaletteCommand::new("Draw Text", add_draw_text_step),
+        PaletteCommand::new("Callout", add_callout_step),
+        PaletteCommand::new("Scale Bar", add_scale_bar_step),
+        PaletteCommand::new("Axes", add_axes_step),
+        PaletteCommand::new("Draw Image", add_draw_image_step),
+        PaletteCommand::new("Draw Arc", add_draw_arc_step),
+        PaletteCommand::new("Compass", add_compass_step),
+        PaletteCommand::new("Transfer Length", add_transfer_length_step),
+        PaletteCommand::new("Draw Sector", add_draw_sector_step),
+        PaletteCommand::new("Clip", add_clip_step),
+        PaletteCommand::new("Shape Boolean", add_shape_boolean_step),
+        PaletteCommand::new("Bounding Box", add_bounding_box_step),
+        PaletteCommand::new("Function Plot", add_function_plot_step),
+        PaletteCommand::new("Parametric Curve", add_parametric_curve_step),
+        PaletteCommand::new("+ Number Data", add_number_data),
+        PaletteCommand::new("+ Point Data", add_point_data),
+        PaletteCommand::new("+ Angle Data", add_angle_data),
+        PaletteCommand::new("+ Color Data", add_color_data),
+        PaletteCommand::new("+ Bool Data", add_bool_data),
+        PaletteCommand::new("+ Vector Data", add_vector_data),
+        PaletteCommand::new("+ Point List Data", add_point_list_data),
+        PaletteCommand::new("+ Slider Data", add_slider_data),
+        PaletteCommand::new("+ Transform Data", add_transform_data),
+        PaletteCommand::new("+ Number Watch", move || add_watch(cx, "number")),
+        PaletteCommand::new("+ Point Watch", move || add_watch(cx, "point")),
+        PaletteCommand::new("+ Expression Watch", move || add_watch(cx, "expr")),
+        PaletteCommand::new("+ Configuration", move || add_configuration(cx)),
+        PaletteCommand::new("Toggle Grid Snap", move || {
+            snap_settings.update(|s| s.grid = !s.grid)
+        }),
+        PaletteCommand::new("Toggle Geometry Snap", move || {
+            snap_settings.update(|s| s.geometry = !s.geometry)
+        }),
+        PaletteCommand::new("Toggle Sketching", move || {
+            canvas_tool.update(|t| {
+                *t = if *t == CanvasTool::Sketch {
+                    CanvasTool::Pointer
+                } else {
+                    CanvasTool::Sketch
+                };
+            })
+        }),
+        PaletteCommand::new("Print / Export", || window().print().unwrap()),
+        PaletteCommand::new("Export Selection as PNG", move || {
+            let error = export_selection_png(cx, false).err();
+            export_errors.update(|e| e.selection = error);
+        }),
+        PaletteCommand::new("Export All Configurations as PNG", move || {
+            let error = export_configurations_png(cx).err();
+            export_errors.update(|e| e.configurations = error);
+        }),
+    ];
+
+    let palette_keydown =
+        wasm_bindgen::prelude::Closure::<dyn Fn(_)>::new(move |ev: web_sys::KeyboardEvent| {
+            if (ev.ctrl_key() || ev.meta_key()) && ev.key().eq_ignore_ascii_case("k") {
+                ev.prevent_default();
+                palette_open.update(|o| *o = !*o);
+                if palette_open.get() {
+                    if let Some(input) = document().get_element_by_id("command-palette-input") {
+                        input
+                            .unchecked_into::<web_sys::HtmlElement>()
+                            .focus()
+                            .unwrap();
+                    }
+                }
+            }
+        })
+        .into_js_value();
+    window()
+        .add_event_listener_with_callback("keydown", palette_keydown.as_ref().unchecked_ref())
+        .unwrap();
+
     view! { cx,
         <div class="flex flex-row h-screen w-screen">
-            <div class="flex flex-col basis-1/6 max-w-[20rem] min-w-[13rem] bg-slate-200">
+            <CommandPalette commands=palette_commands open=palette_open />
+            <div class=move || {
+                let hidden = if display_mode.get() == DisplayMode::Print { " hidden" } else { "" };
+                format!("flex flex-col basis-1/6 max-w-[20rem] min-w-[13rem] bg-slate-200 print:hidden{}", hidden)
+            }>
+                <h3 class="text-3xl text-center m-3">"Document"</h3>
+                <DocumentSettingsView />
+                <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| {
+                    window().print().unwrap();
+                }>
+                    "Print"
+                </button>
+                <ExportSelectionView />
+
                 <h3 class="text-3xl text-center m-3">"Data"</h3>
                 <div class="flex flex-col justify-self-end self-center">
-                    <button class= "mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=add_number_data>"+ Number"</button>
-                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=add_point_data>"+ Point"</button>
+                    <button class= "mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_number_data()>"+ Number"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_point_data()>"+ Point"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_angle_data()>"+ Angle"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_color_data()>"+ Color"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_bool_data()>"+ Bool"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_vector_data()>"+ Vector"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_point_list_data()>"+ Point List"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_transform_data()>"+ Transform"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_slider_data()>"+ Slider"</button>
                 </div>
                 <div class="flex flex-col items-center overflow-scroll">
                     <For
-                        each=datas
+                        each=move || datas.with(|d| d.iter().filter(|d| !d.trashed.get()).copied().collect::<Vec<_>>())
                         key=|data| data.id
                         view=move |data: Data| {
                             view! { cx,
@@ -864,10 +14252,14 @@ pub fn DrawlingView(cx: Scope) -> impl IntoView {
                     />
                 </div>
 
+                <WatchesView />
+
+                <ParameterTableView />
+
                 <h3 class="text-3xl text-center m-3">"Steps"</h3>
                 <div class="flex flex-col items-center overflow-scroll">
                     <For
-                        each=steps
+                        each=move || steps.with(|s| s.iter().filter(|s| !s.trashed.get()).copied().collect::<Vec<_>>())
                         key=|step| step.id
                         view=move |step: Step| {
                             view! { cx,
@@ -877,8 +14269,143 @@ pub fn DrawlingView(cx: Scope) -> impl IntoView {
                     />
                 </div>
                 <div class="flex flex-col justify-self-end self-center">
-                    <button class= "mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=add_draw_point_step>"Draw Point"</button>
-                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=add_draw_line_step>"Draw Line"</button>
+                    <NewStepDefaultsView />
+                    <button class= "mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_point_step()>"Draw Point"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_line_step()>"Draw Line"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_arrow_step()>"Draw Arrow"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_ray_step()>"Draw Ray"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_infinite_line_step()>"Draw Infinite Line"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_midpoint_step()>"Midpoint"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_centroid_step()>"Centroid"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_distance_step()>"Distance"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_angle_step()>"Angle"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_point_along_line_step()>"Point Along Line"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_projection_step()>"Projection"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_intersection_step()>"Intersection"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_perpendicular_step()>"Perpendicular"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_tangent_step()>"Tangent"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_parallel_offset_step()>"Parallel Offset"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_offset_curve_step()>"Offset Curve"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_trim_extend_step()>"Trim / Extend"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_mirror_step()>"Mirror"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_rotate_copy_step()>"Rotate Copy"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_translate_copy_step()>"Translate Copy"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_transform_copy_step()>"Transform Copy"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_linear_array_step()>"Linear Array"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_polar_array_step()>"Polar Array"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_path_step()>"Draw Path"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_rect_step()>"Draw Rect"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_rounded_rect_step()>"Draw Rounded Rect"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_polygon_step()>"Draw Polygon"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_annulus_step()>"Annulus"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_point_grid_step()>"Point Grid"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_text_step()>"Draw Text"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_callout_step()>"Callout"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_scale_bar_step()>"Scale Bar"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_axes_step()>"Axes"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_image_step()>"Draw Image"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_arc_step()>"Draw Arc"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_compass_step()>"Compass"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_transfer_length_step()>"Transfer Length"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_draw_sector_step()>"Draw Sector"</button>
+                    <PointSeriesView />
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_clip_step()>"Clip"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_shape_boolean_step()>"Shape Boolean"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_bounding_box_step()>"Bounding Box"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_function_plot_step()>"Function Plot"</button>
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=move |_| add_parametric_curve_step()>"Parametric Curve"</button>
+                    <button
+                        class="mb-6 bg-blue-500 hover:bg-blue-700 disabled:bg-blue-300 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center"
+                        prop:disabled=move || selection.with(|sel| sel.steps.is_empty())
+                        on:click=duplicate_selection
+                    >
+                        "Duplicate Selected"
+                    </button>
+                    <button
+                        class="mb-6 bg-blue-500 hover:bg-blue-700 disabled:bg-blue-300 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center"
+                        prop:disabled=move || {
+                            !selection.with(|sel| sel.steps.iter().any(|id| step_by_id(cx, *id).map_or(false, |s| s.is_copy())))
+                        }
+                        on:click=explode_selection
+                    >
+                        "Explode Selected"
+                    </button>
+                    {move || explode_error.get().map(|e| view! { cx,
+                        <p class="mb-6 text-sm text-red-600 whitespace-pre-line w-[12rem] max-w-[85%] self-center">{e}</p>
+                    })}
+                    <TransformView />
+                    <ReplaceRefsView />
+                    <TagsView />
+                    <TrashView />
+                    <ProblemsView />
+                    <button class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center" on:click=sort_steps>"Sort by Dependencies"</button>
+                    <button
+                        class="mb-6 bg-blue-500 hover:bg-blue-700 py-2 px-1 text-white rounded w-[12rem] max-w-[85%] self-center"
+                        on:click=move |_| canvas_tool.update(|t| {
+                            *t = if *t == CanvasTool::Sketch { CanvasTool::Pointer } else { CanvasTool::Sketch };
+                        })
+                    >
+                        {move || if canvas_tool.get() == CanvasTool::Sketch { "Stop Sketching" } else { "Sketch" }}
+                    </button>
+                    <div class="flex flex-row mb-6 self-center">
+                        <label class="mr-3">
+                            <input
+                                type="checkbox"
+                                prop:checked=move || snap_settings.with(|s| s.grid)
+                                on:change=move |_| snap_settings.update(|s| s.grid = !s.grid)
+                            />
+                            " Grid snap"
+                        </label>
+                        <label>
+                            <input
+                                type="checkbox"
+                                prop:checked=move || snap_settings.with(|s| s.geometry)
+                                on:change=move |_| snap_settings.update(|s| s.geometry = !s.geometry)
+                            />
+                            " Geometry snap"
+                        </label>
+                    </div>
+                    <div class="flex flex-row mb-6 self-center">
+                        <label class="mr-3">
+                            <input
+                                type="checkbox"
+                                prop:checked=move || snap_settings.with(|s| s.grid_kind == GridKind::Isometric)
+                                on:change=move |_| snap_settings.update(|s| {
+                                    s.grid_kind = match s.grid_kind {
+                                        GridKind::Square => GridKind::Isometric,
+                                        GridKind::Isometric => GridKind::Square,
+                                    };
+                                })
+                            />
+                            " Isometric grid"
+                        </label>
+                        <label>
+                            <input
+                                type="checkbox"
+                                prop:checked=move || view_transform.with(|vt| vt.projection == Projection::Isometric)
+                                on:change=move |_| view_transform.update(|vt| {
+                                    vt.projection = match vt.projection {
+                                        Projection::Plan => Projection::Isometric,
+                                        Projection::Isometric => Projection::Plan,
+                                    };
+                                })
+                            />
+                            " Isometric projection"
+                        </label>
+                    </div>
+                    {move || {
+                        let cyclic = cyclic_steps.get();
+                        (!cyclic.is_empty()).then(|| {
+                            let cyclic = cyclic
+                                .iter()
+                                .map(|id| format!("#{}", id))
+                                .collect::<Vec<String>>()
+                                .join(", ");
+                            view! { cx,
+                                <p class="mb-6 text-red-700 self-center">"Reference cycle through steps " {cyclic}</p>
+                            }
+                        })
+                    }}
                 </div>
             </div>
 
@@ -886,3 +14413,36 @@ pub fn DrawlingView(cx: Scope) -> impl IntoView {
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remapped_expr_remaps_mapped_steps_only() {
+        let id_map = HashMap::from([(3, 7), (4, 12)]);
+        assert_eq!(
+            remapped_expr("step[3].length * 2 + step[5].size", &id_map),
+            "step[7].length * 2 + step[5].size"
+        );
+        assert_eq!(
+            remapped_expr("step[4].center.x-step[34].t+data[3]", &id_map),
+            "step[12].center.x-step[34].t+data[3]"
+        );
+        assert_eq!(
+            remapped_expr("mystep[3] + step[3].x2", &id_map),
+            "mystep[3] + step[7].x2"
+        );
+        assert_eq!(remapped_expr("1 + 2", &id_map), "1 + 2");
+    }
+
+    #[test]
+    fn replaced_expr_names_replaces_whole_names() {
+        let replace = |name: &str| (name == "step[3].x").then(|| "(-2)".to_string());
+        assert_eq!(
+            replaced_expr_names("2^step[3].x + step[3].xy * 3.5 - sin(step[3].x)", replace),
+            "2^(-2) + step[3].xy * 3.5 - sin((-2))"
+        );
+        assert_eq!(replaced_expr_names("1.5 + x", replace), "1.5 + x");
+    }
+}